@@ -0,0 +1,312 @@
+//! The skill catalog `to-prompt` prints, in the format a given agent expects, optionally cut
+//! down to a token budget.
+//!
+//! Under a budget, skills are ranked by their `priority` metadata (an integer, higher first,
+//! default 0), then by description length (shorter first), then by name. Skills are added in
+//! that order while they fit; one that doesn't fit gets its description truncated at a word
+//! boundary, or is omitted if even a short description won't fit. The kept skills stay in
+//! their original order.
+
+use std::cmp::Reverse;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use super::prompt::{to_prompt_with_location, SkillWithLocation};
+use super::tokens::Tokenizer;
+
+/// Metadata key ranking skills under `--max-tokens`.
+pub const PRIORITY_KEY: &str = "priority";
+
+/// A truncated description keeps at least this many words; otherwise the skill is omitted.
+const MIN_TRUNCATED_WORDS: usize = 5;
+
+const CLAUDE_MD_INSTRUCTIONS: &str = "\
+Each skill below is a folder of instructions, scripts and resources for one kind of task. \
+When a task matches a skill's description, read its SKILL.md with the Read tool before \
+starting and follow it. Paths inside a skill are relative to its folder.";
+
+const AGENTS_MD_INSTRUCTIONS: &str = "\
+Each skill below is a folder of instructions, scripts and resources for one kind of task. \
+When a task matches a skill's description, print its SKILL.md (for example with `cat`) \
+before starting and follow it. Paths inside a skill are relative to its folder.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum CatalogFormat {
+    /// `<available_skills>` XML
+    #[default]
+    Xml,
+    /// JSON array of name, description and location
+    Json,
+    /// Markdown list
+    Markdown,
+    /// `## Skills` section for Claude Code's CLAUDE.md
+    ClaudeMd,
+    /// `## Skills` section for Codex's AGENTS.md
+    AgentsMd,
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    name: &'a str,
+    description: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<&'a str>,
+}
+
+pub fn render_catalog(format: CatalogFormat, skills: &[SkillWithLocation]) -> String {
+    match format {
+        CatalogFormat::Xml => to_prompt_with_location(skills),
+        CatalogFormat::Json => {
+            let entries: Vec<JsonEntry<'_>> = skills
+                .iter()
+                .map(|skill| JsonEntry {
+                    name: &skill.properties.name,
+                    description: &skill.properties.description,
+                    location: skill.location.as_deref(),
+                })
+                .collect();
+            serde_json::to_string_pretty(&entries).expect("catalog entries always serialize")
+        }
+        CatalogFormat::Markdown => markdown("# Available skills", None, skills),
+        CatalogFormat::ClaudeMd => markdown("## Skills", Some(CLAUDE_MD_INSTRUCTIONS), skills),
+        CatalogFormat::AgentsMd => markdown("## Skills", Some(AGENTS_MD_INSTRUCTIONS), skills),
+    }
+}
+
+fn markdown(heading: &str, instructions: Option<&str>, skills: &[SkillWithLocation]) -> String {
+    let mut blocks = vec![heading.to_string()];
+    blocks.extend(instructions.map(str::to_string));
+    let items: Vec<String> = skills
+        .iter()
+        .map(|skill| {
+            let description = skill
+                .properties
+                .description
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            match &skill.location {
+                Some(location) => format!("- **{}** (`{location}`): {description}", skill.properties.name),
+                None => format!("- **{}**: {description}", skill.properties.name),
+            }
+        })
+        .collect();
+    if !items.is_empty() {
+        blocks.push(items.join("\n"));
+    }
+    blocks.join("\n\n")
+}
+
+/// A budget too small for the catalog's heading and instructions alone.
+#[derive(Debug, Error)]
+#[error("an empty catalog already takes {overhead} tokens, more than {max_tokens}")]
+pub struct BudgetTooSmall {
+    pub max_tokens: usize,
+    pub overhead: usize,
+}
+
+/// The skills kept under a token budget, and what was cut to get there.
+#[derive(Debug)]
+pub struct Selection {
+    /// In their original order; descriptions of truncated skills end in `…`.
+    pub skills: Vec<SkillWithLocation>,
+    /// Names of the skills left out, in their original order.
+    pub omitted: Vec<String>,
+    /// Names of the skills whose description was shortened, in their original order.
+    pub truncated: Vec<String>,
+}
+
+/// Keep the highest-ranked skills whose rendered catalog fits in `max_tokens`.
+pub fn fit_to_budget(
+    skills: Vec<SkillWithLocation>,
+    format: CatalogFormat,
+    tokenizer: &impl Tokenizer,
+    max_tokens: usize,
+) -> Result<Selection, BudgetTooSmall> {
+    let overhead = tokenizer.count(&render_catalog(format, &[]));
+    if overhead > max_tokens {
+        return Err(BudgetTooSmall { max_tokens, overhead });
+    }
+    let cost = |skill: &SkillWithLocation| {
+        tokenizer
+            .count(&render_catalog(format, std::slice::from_ref(skill)))
+            .saturating_sub(overhead)
+    };
+
+    let mut ranked: Vec<usize> = (0..skills.len()).collect();
+    ranked.sort_by_key(|&i| {
+        let properties = &skills[i].properties;
+        (
+            Reverse(priority(&skills[i])),
+            properties.description.chars().count(),
+            properties.name.clone(),
+        )
+    });
+
+    let mut remaining = max_tokens - overhead;
+    let mut kept: Vec<Option<SkillWithLocation>> = vec![None; skills.len()];
+    let mut truncated = vec![false; skills.len()];
+    for &i in &ranked {
+        let skill = &skills[i];
+        let whole = cost(skill);
+        if whole <= remaining {
+            remaining -= whole;
+            kept[i] = Some(skill.clone());
+        } else if let Some((shortened, shortened_cost)) = truncate_to_fit(skill, remaining, &cost) {
+            remaining -= shortened_cost;
+            kept[i] = Some(shortened);
+            truncated[i] = true;
+        }
+    }
+
+    // Entry costs don't add up exactly (tokens can merge across entries); drop the
+    // lowest-ranked skills until the whole catalog fits.
+    let render_kept = |kept: &[Option<SkillWithLocation>]| {
+        let skills: Vec<SkillWithLocation> = kept.iter().flatten().cloned().collect();
+        tokenizer.count(&render_catalog(format, &skills))
+    };
+    for &i in ranked.iter().rev() {
+        if render_kept(&kept) <= max_tokens {
+            break;
+        }
+        kept[i] = None;
+    }
+
+    let mut selection = Selection {
+        skills: Vec::new(),
+        omitted: Vec::new(),
+        truncated: Vec::new(),
+    };
+    for (i, skill) in kept.into_iter().enumerate() {
+        match skill {
+            Some(skill) => {
+                if truncated[i] {
+                    selection.truncated.push(skill.properties.name.clone());
+                }
+                selection.skills.push(skill);
+            }
+            None => selection.omitted.push(skills[i].properties.name.clone()),
+        }
+    }
+    Ok(selection)
+}
+
+fn priority(skill: &SkillWithLocation) -> i64 {
+    skill
+        .properties
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get(PRIORITY_KEY))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// The longest word-boundary truncation of the description that fits in `budget`, with its
+/// cost.
+fn truncate_to_fit(
+    skill: &SkillWithLocation,
+    budget: usize,
+    cost: &impl Fn(&SkillWithLocation) -> usize,
+) -> Option<(SkillWithLocation, usize)> {
+    let words: Vec<&str> = skill.properties.description.split_whitespace().collect();
+    let with_words = |count: usize| {
+        let mut shortened = skill.clone();
+        shortened.properties.description = format!("{}…", words[..count].join(" "));
+        let shortened_cost = cost(&shortened);
+        (shortened, shortened_cost)
+    };
+
+    let (mut low, mut high) = (MIN_TRUNCATED_WORDS, words.len().checked_sub(1)?);
+    let mut best = None;
+    while low <= high {
+        let middle = (low + high) / 2;
+        let (shortened, shortened_cost) = with_words(middle);
+        if shortened_cost <= budget {
+            best = Some((shortened, shortened_cost));
+            low = middle + 1;
+        } else {
+            high = middle - 1;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentskills::models::SkillProperties;
+    use crate::agentskills::tokens::ApproxBpe;
+
+    fn skill(name: &str, description: &str, priority: Option<&str>) -> SkillWithLocation {
+        SkillWithLocation {
+            properties: SkillProperties {
+                name: name.to_string(),
+                description: description.to_string(),
+                compatibility: None,
+                license: None,
+                allowed_tools: None,
+                metadata: priority.map(|p| [(PRIORITY_KEY.to_string(), p.to_string())].into()),
+            },
+            location: Some(format!("skills/{name}/SKILL.md")),
+        }
+    }
+
+    #[test]
+    fn renders_markdown_and_json() {
+        let skills = [skill("csv", "Analyze CSV\n  files.", None)];
+        assert_eq!(
+            render_catalog(CatalogFormat::Markdown, &skills),
+            "# Available skills\n\n- **csv** (`skills/csv/SKILL.md`): Analyze CSV files."
+        );
+        assert!(
+            render_catalog(CatalogFormat::AgentsMd, &skills).starts_with("## Skills\n\nEach skill below is a folder")
+        );
+        assert_eq!(
+            render_catalog(CatalogFormat::Json, &skills),
+            r#"[
+  {
+    "name": "csv",
+    "description": "Analyze CSV\n  files.",
+    "location": "skills/csv/SKILL.md"
+  }
+]"#
+        );
+    }
+
+    #[test]
+    fn fits_skills_to_the_budget_by_priority() {
+        let long = "Convert spreadsheets between formats, clean messy columns, merge sheets and \
+                    produce charts and summary tables for reports";
+        let skills = vec![
+            skill("charts", long, Some("5")),
+            skill("csv", "Analyze CSV files.", Some("10")),
+            skill("pdf", "Read PDF files and extract their tables.", Some("-1")),
+        ];
+        let format = CatalogFormat::Markdown;
+        let whole = ApproxBpe.count(&render_catalog(format, &skills));
+
+        let all = fit_to_budget(skills.clone(), format, &ApproxBpe, whole).unwrap();
+        assert!(all.omitted.is_empty() && all.truncated.is_empty());
+
+        let without_pdf = ApproxBpe.count(&render_catalog(format, &skills[..2]));
+        let cut = fit_to_budget(skills.clone(), format, &ApproxBpe, without_pdf).unwrap();
+        assert_eq!(cut.omitted, ["pdf"]);
+        assert!(cut.truncated.is_empty());
+
+        let budget = without_pdf - 10;
+        let tight = fit_to_budget(skills.clone(), format, &ApproxBpe, budget).unwrap();
+        let names: Vec<&str> = tight.skills.iter().map(|s| s.properties.name.as_str()).collect();
+        assert_eq!(names, ["charts", "csv"]);
+        assert_eq!(tight.truncated, ["charts"]);
+        assert_eq!(tight.omitted, ["pdf"]);
+        assert!(tight.skills[0].properties.description.ends_with('…'));
+        assert!(ApproxBpe.count(&render_catalog(format, &tight.skills)) <= budget);
+
+        let overhead = ApproxBpe.count(&render_catalog(format, &[]));
+        let empty = fit_to_budget(skills.clone(), format, &ApproxBpe, overhead).unwrap();
+        assert_eq!(empty.omitted, ["charts", "csv", "pdf"]);
+        let too_small = fit_to_budget(skills, format, &ApproxBpe, overhead - 1).unwrap_err();
+        assert_eq!(too_small.overhead, overhead);
+    }
+}
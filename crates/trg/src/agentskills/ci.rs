@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use serde::Serialize;
@@ -6,6 +8,7 @@ use serde::Deserialize;
 
 use super::evals::{check_workspace, EvalError, WorkspaceCheckOptions, WorkspaceCheckReport};
 use super::report::RunRecord;
+use super::sarif::{self, Finding, Level, Rule};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct CiPolicy {
@@ -74,6 +77,67 @@ pub enum CiViolationKind {
     DurationRegression,
 }
 
+impl CiViolationKind {
+    pub const ALL: [CiViolationKind; 11] = [
+        Self::RunnerFailure,
+        Self::FailedAssertion,
+        Self::MissingGrading,
+        Self::PassRateBelowMinimum,
+        Self::PassRateRegression,
+        Self::TokenBudgetExceeded,
+        Self::InputTokenBudgetExceeded,
+        Self::OutputTokenBudgetExceeded,
+        Self::TokenRegression,
+        Self::DurationExceeded,
+        Self::DurationRegression,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RunnerFailure => "runner_failure",
+            Self::FailedAssertion => "failed_assertion",
+            Self::MissingGrading => "missing_grading",
+            Self::PassRateBelowMinimum => "pass_rate_below_minimum",
+            Self::PassRateRegression => "pass_rate_regression",
+            Self::TokenBudgetExceeded => "token_budget_exceeded",
+            Self::InputTokenBudgetExceeded => "input_token_budget_exceeded",
+            Self::OutputTokenBudgetExceeded => "output_token_budget_exceeded",
+            Self::TokenRegression => "token_regression",
+            Self::DurationExceeded => "duration_exceeded",
+            Self::DurationRegression => "duration_regression",
+        }
+    }
+
+    /// SARIF rule id, e.g. `eval/failed_assertion`.
+    pub fn rule_id(&self) -> String {
+        format!("eval/{}", self.as_str())
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::RunnerFailure => "Every runner invocation completes",
+            Self::FailedAssertion => "Every eval assertion passes",
+            Self::MissingGrading => "Every completed run has a grading.json",
+            Self::PassRateBelowMinimum => "Assertion pass rate is at least --min-pass-rate",
+            Self::PassRateRegression => "Assertion pass rate does not drop below the baseline report",
+            Self::TokenBudgetExceeded => "Total tokens stay within --max-tokens",
+            Self::InputTokenBudgetExceeded => "Input tokens stay within --max-input-tokens",
+            Self::OutputTokenBudgetExceeded => "Output tokens stay within --max-output-tokens",
+            Self::TokenRegression => "Total tokens do not exceed the baseline report",
+            Self::DurationExceeded => "No run takes longer than --max-duration-ms",
+            Self::DurationRegression => "The slowest run is not slower than in the baseline report",
+        }
+    }
+}
+
+/// Where in the skill's sources a violation belongs, e.g. the assertion's line in `evals.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceLocation {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CiViolation {
     pub kind: CiViolationKind,
@@ -86,6 +150,10 @@ pub struct CiViolation {
     pub file: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<u32>,
+    /// Location in `evals.json`, when the violation can be traced back to it; `file` and `line`
+    /// point into the report bundle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceLocation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -202,6 +270,7 @@ pub fn run_ci_checks(
             workspace: None,
             file: None,
             line: None,
+            source: None,
         });
     }
 
@@ -214,6 +283,7 @@ pub fn run_ci_checks(
                 workspace: Some(workspace.clone()),
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -227,6 +297,7 @@ pub fn run_ci_checks(
                 workspace: Some(detail.workspace.clone()),
                 file: Some(detail.file.clone()),
                 line: Some(detail.line),
+                source: detail.source.clone(),
             });
         }
     }
@@ -240,6 +311,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -253,6 +325,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -266,6 +339,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -279,6 +353,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -295,6 +370,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -315,6 +391,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
 
@@ -329,6 +406,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
 
@@ -343,6 +421,7 @@ pub fn run_ci_checks(
                 workspace: None,
                 file: None,
                 line: None,
+                source: None,
             });
         }
     }
@@ -362,6 +441,7 @@ pub struct FailedAssertionDetail {
     pub file: String,
     pub line: u32,
     pub text: String,
+    pub source: Option<SourceLocation>,
 }
 
 pub fn collect_failed_assertions(report_dir: &Path) -> Result<Vec<FailedAssertionDetail>, EvalError> {
@@ -378,6 +458,10 @@ pub fn collect_failed_assertions(report_dir: &Path) -> Result<Vec<FailedAssertio
         )?;
     }
 
+    if let Some(evals) = EvalsSource::load(report_dir) {
+        evals.locate_failed_assertions(&mut details);
+    }
+
     Ok(details)
 }
 
@@ -407,6 +491,7 @@ pub fn collect_failed_assertions_in_workspace(
                 file: grading_path.display().to_string(),
                 line: index as u32 + 1,
                 text: result.assertion.clone(),
+                source: None,
             });
         }
     }
@@ -433,27 +518,208 @@ pub fn collect_missing_grading_workspaces(report_dir: &Path) -> Result<Vec<Strin
     Ok(missing)
 }
 
-pub fn format_github_annotations(violations: &[CiViolation]) -> Vec<String> {
+/// The eval suite a report bundle was run from, for tracing violations back to `evals.json`.
+#[derive(Debug)]
+pub struct EvalsSource {
+    /// `evals.json` as the report recorded it: relative to where `eval run` was invoked,
+    /// which in CI is the checkout root.
+    path: String,
+    content: String,
+    /// Eval case id and byte range of each element of the `evals` array.
+    cases: Vec<(String, Range<usize>)>,
+    case_by_run: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportSourcesDocument {
+    suite: ReportSuiteRef,
+    runs: Vec<ReportRunRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportSuiteRef {
+    evals_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportRunRef {
+    id: String,
+    eval_case_id: String,
+}
+
+impl EvalsSource {
+    /// `None` when the report or the `evals.json` it names can't be read from here.
+    pub fn load(report_dir: &Path) -> Option<Self> {
+        let report = std::fs::read_to_string(report_dir.join("report.json")).ok()?;
+        let report: ReportSourcesDocument = serde_json::from_str(&report).ok()?;
+        let content = std::fs::read_to_string(&report.suite.evals_path).ok()?;
+        let cases = top_level_array_objects(&content, "evals")
+            .into_iter()
+            .filter_map(|range| {
+                let case: serde_json::Value = serde_json::from_str(&content[range.clone()]).ok()?;
+                let id = match case.get("id")? {
+                    serde_json::Value::String(id) => id.trim().to_string(),
+                    other => other.to_string(),
+                };
+                Some((id, range))
+            })
+            .collect();
+        Some(Self {
+            path: report.suite.evals_path,
+            content,
+            cases,
+            case_by_run: report.runs.into_iter().map(|run| (run.id, run.eval_case_id)).collect(),
+        })
+    }
+
+    /// Point each failed assertion of a known run at its line in `evals.json`, or at its eval
+    /// case when the assertion text isn't found verbatim.
+    pub fn locate_failed_assertions(&self, details: &mut [FailedAssertionDetail]) {
+        for detail in details {
+            let Some(case_id) = detail.run_id.as_ref().and_then(|id| self.case_by_run.get(id)) else {
+                continue;
+            };
+            let Some((_, range)) = self.cases.iter().find(|(id, _)| id == case_id) else {
+                continue;
+            };
+            let needle = serde_json::to_string(&detail.text).unwrap_or_default();
+            let offset = self.content[range.clone()]
+                .find(&needle)
+                .map_or(range.start, |found| range.start + found);
+            detail.source = Some(SourceLocation {
+                file: self.path.clone(),
+                line: Some(line_at(&self.content, offset)),
+            });
+        }
+    }
+
+    /// Attach the suite-wide violations (budgets, pass rate, regressions) that have no location
+    /// of their own to `evals.json` as a whole.
+    pub fn locate_suite_violations(&self, violations: &mut [CiViolation]) {
+        for violation in violations {
+            if violation.source.is_none() && violation.file.is_none() {
+                violation.source = Some(SourceLocation {
+                    file: self.path.clone(),
+                    line: None,
+                });
+            }
+        }
+    }
+}
+
+/// The run whose workspace is `workspace`, for commands pointed at a single run.
+pub fn run_id_for_workspace(report_dir: &Path, workspace: &Path) -> Option<String> {
+    let workspace = workspace.canonicalize().ok()?;
+    load_report_runs(report_dir).ok()?.into_iter().find_map(|run| {
+        let candidate = report_dir.join(&run.paths.workspace).canonicalize().ok()?;
+        (candidate == workspace).then_some(run.id)
+    })
+}
+
+/// Byte ranges of the object elements of the array under `key` in a JSON document's root
+/// object. Assumes the document is valid JSON; `serde_json` has no span information to offer.
+fn top_level_array_objects(content: &str, key: &str) -> Vec<Range<usize>> {
+    let quoted_key = format!("\"{key}\"");
+    let mut ranges = Vec::new();
+    let mut depth = 0usize;
+    let mut in_target = false;
+    let mut last_root_string: Option<Range<usize>> = None;
+    let mut string_start: Option<usize> = None;
+    let mut escaped = false;
+    let mut object_start = 0;
+
+    for (index, byte) in content.bytes().enumerate() {
+        if let Some(start) = string_start {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                string_start = None;
+                if depth == 1 {
+                    last_root_string = Some(start..index + 1);
+                }
+            }
+            continue;
+        }
+        match byte {
+            b'"' => string_start = Some(index),
+            b'{' | b'[' => {
+                depth += 1;
+                if byte == b'[' && depth == 2 {
+                    in_target = last_root_string
+                        .as_ref()
+                        .is_some_and(|range| content[range.clone()] == quoted_key);
+                } else if byte == b'{' && depth == 3 && in_target {
+                    object_start = index;
+                }
+            }
+            b'}' | b']' => {
+                if byte == b'}' && depth == 3 && in_target {
+                    ranges.push(object_start..index + 1);
+                } else if depth == 2 {
+                    in_target = false;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+fn line_at(content: &str, offset: usize) -> u32 {
+    content[..offset].matches('\n').count() as u32 + 1
+}
+
+/// One SARIF rule per [`CiViolationKind`].
+pub fn ci_rules() -> Vec<Rule> {
+    CiViolationKind::ALL
+        .iter()
+        .map(|kind| Rule {
+            id: kind.rule_id(),
+            description: kind.description().to_string(),
+        })
+        .collect()
+}
+
+/// Findings for CI violations, located in `evals.json` where known and otherwise in the
+/// report bundle file that recorded them.
+pub fn violation_findings(violations: &[CiViolation]) -> Vec<Finding> {
     violations
         .iter()
-        .filter(|violation| violation.kind == CiViolationKind::FailedAssertion)
         .map(|violation| {
-            let file = violation.file.as_deref().unwrap_or("grading.json");
-            let line = violation.line.unwrap_or(1);
-            let message = escape_github_message(&violation.message);
-            format!("::error file={file},line={line}::{message}")
+            let (path, line) = match &violation.source {
+                Some(source) => (Some(source.file.clone()), source.line),
+                None => (violation.file.clone(), violation.line),
+            };
+            Finding {
+                rule_id: violation.kind.rule_id(),
+                level: Level::Error,
+                message: violation.message.clone(),
+                path,
+                line,
+            }
         })
         .collect()
 }
 
+pub fn format_github_annotations(violations: &[CiViolation]) -> Vec<String> {
+    violation_findings(violations)
+        .iter()
+        .map(sarif::github_annotation)
+        .collect()
+}
+
 pub fn emit_github_annotations(violations: &[CiViolation]) {
-    if std::env::var("GITHUB_ACTIONS").ok().as_deref() != Some("true") {
-        return;
-    }
+    sarif::emit_github_annotations(&violation_findings(violations));
+}
 
-    for line in format_github_annotations(violations) {
-        println!("{line}");
-    }
+/// Write the violations as a SARIF log to `path`.
+pub fn write_sarif(path: &Path, violations: &[CiViolation]) -> std::io::Result<()> {
+    let log = sarif::to_sarif(&ci_rules(), &violation_findings(violations));
+    let json = serde_json::to_string_pretty(&log).expect("JSON values always serialize");
+    std::fs::write(path, json + "\n")
 }
 
 pub fn print_human_summary(check: &CiCheckResult) {
@@ -488,10 +754,6 @@ fn compute_pass_rate(passed: usize, total: usize) -> f64 {
     }
 }
 
-fn escape_github_message(message: &str) -> String {
-    message.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
-}
-
 #[derive(Debug, serde::Deserialize)]
 struct GradingForAnnotations {
     assertion_results: Vec<AssertionForAnnotations>,
@@ -697,6 +959,7 @@ mod tests {
             workspace: Some("runs/run-001/workspace".to_string()),
             file: Some("runs/run-001/workspace/grading.json".to_string()),
             line: Some(2),
+            source: None,
         }];
 
         let lines = format_github_annotations(&violations);
@@ -717,6 +980,7 @@ mod tests {
             workspace: None,
             file: Some("grading.json".to_string()),
             line: Some(1),
+            source: None,
         }];
 
         std::env::remove_var("GITHUB_ACTIONS");
@@ -728,6 +992,96 @@ mod tests {
         std::env::remove_var("GITHUB_ACTIONS");
     }
 
+    #[test]
+    fn violations_are_located_in_evals_json() {
+        let tmp = tempfile::tempdir().unwrap();
+        let evals_path = tmp.path().join("evals.json");
+        fs::write(
+            &evals_path,
+            r#"{
+  "skill_name": "charts",
+  "evals": [
+    {
+      "id": 1,
+      "prompt": "Plot it",
+      "assertions": ["has a \"title\"", "missing chart"]
+    },
+    {
+      "assertions": ["missing chart"],
+      "id": "second"
+    }
+  ]
+}
+"#,
+        )
+        .unwrap();
+        let report_dir = tmp.path().join("report");
+        fs::create_dir_all(&report_dir).unwrap();
+        let report = serde_json::json!({
+            "suite": { "evals_path": evals_path.display().to_string() },
+            "runs": [
+                { "id": "run-001", "eval_case_id": "1" },
+                { "id": "run-002", "eval_case_id": "second" }
+            ]
+        });
+        fs::write(report_dir.join("report.json"), report.to_string()).unwrap();
+
+        let detail = |run_id: &str, text: &str| FailedAssertionDetail {
+            run_id: Some(run_id.to_string()),
+            workspace: "ws".to_string(),
+            file: "grading.json".to_string(),
+            line: 1,
+            text: text.to_string(),
+            source: None,
+        };
+        let mut details = vec![
+            detail("run-001", "missing chart"),
+            detail("run-001", "has a \"title\""),
+            detail("run-002", "missing chart"),
+            detail("run-002", "not in the suite"),
+        ];
+        let evals = EvalsSource::load(&report_dir).unwrap();
+        evals.locate_failed_assertions(&mut details);
+        let lines: Vec<_> = details.iter().map(|d| d.source.as_ref().and_then(|s| s.line)).collect();
+        assert_eq!(lines, [Some(7), Some(7), Some(10), Some(9)]);
+
+        let mut check = run_ci_checks(
+            &sample_metrics(0.5, 100, 10),
+            CiPolicy::strict_ci(),
+            &ThresholdConfig {
+                min_pass_rate: Some(0.9),
+                ..ThresholdConfig::default()
+            },
+            &details[..1],
+            &[],
+        );
+        evals.locate_suite_violations(&mut check.violations);
+        let findings = violation_findings(&check.violations);
+        let evals_file = evals_path.display().to_string();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].rule_id, "eval/failed_assertion");
+        assert_eq!(
+            (findings[0].path.as_deref(), findings[0].line),
+            (Some(evals_file.as_str()), Some(7))
+        );
+        assert_eq!(findings[1].rule_id, "eval/pass_rate_below_minimum");
+        assert_eq!(
+            (findings[1].path.as_deref(), findings[1].line),
+            (Some(evals_file.as_str()), None)
+        );
+        assert_eq!(
+            format_github_annotations(&check.violations)[1],
+            format!("::error file={evals_file}::pass rate 0.5000 is below minimum 0.9000")
+        );
+
+        let rules: Vec<_> = ci_rules().into_iter().map(|rule| rule.id).collect();
+        assert_eq!(rules.len(), CiViolationKind::ALL.len());
+        for kind in CiViolationKind::ALL {
+            let serialized = serde_json::to_value(&kind).unwrap();
+            assert_eq!(serialized, kind.as_str());
+        }
+    }
+
     fn write_metrics_report(dir: &Path, metrics: ReportMetrics) {
         fs::create_dir_all(dir.join("runs/run-001/workspace")).unwrap();
         let pass_rate = metrics.pass_rate;
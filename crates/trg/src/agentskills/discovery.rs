@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+/// Skill directories found under a root, plus the entries the walk could not read.
+#[derive(Debug, Default)]
+pub struct Discovery {
+    pub skills: Vec<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+/// Find every directory under `root` holding a `SKILL.md` (or `skill.md`), sorted.
+///
+/// Paths matched by `.gitignore`, `.ignore` or `.git/info/exclude` are skipped, inside a git
+/// checkout or not, so vendored trees can be excluded the way they already are for git.
+/// Hidden directories are searched (`.claude/skills` is a common home for skills); `.git` is not.
+pub fn discover_skills(root: &Path) -> Discovery {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut skills = BTreeSet::new();
+    let mut errors = Vec::new();
+    for entry in walker {
+        match entry {
+            Ok(entry) => {
+                let is_skill_md = matches!(entry.file_name().to_str(), Some("SKILL.md" | "skill.md"));
+                if is_skill_md && entry.file_type().is_some_and(|t| t.is_file()) {
+                    if let Some(dir) = entry.path().parent() {
+                        skills.insert(dir.to_path_buf());
+                    }
+                }
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
+    Discovery {
+        skills: skills.into_iter().collect(),
+        errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn skill(root: &Path, rel: &str, file: &str) {
+        let dir = root.join(rel);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(file), "---\nname: x\ndescription: y\n---\n").unwrap();
+    }
+
+    #[test]
+    fn finds_skills_and_honours_ignore_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        skill(root, "skills/alpha", "SKILL.md");
+        skill(root, "skills/beta", "skill.md");
+        skill(root, ".claude/skills/gamma", "SKILL.md");
+        skill(root, "vendor/upstream/delta", "SKILL.md");
+        skill(root, "third_party/epsilon", "SKILL.md");
+        skill(root, ".git/zeta", "SKILL.md");
+        fs::write(root.join(".gitignore"), "vendor/\n").unwrap();
+        fs::write(root.join(".ignore"), "third_party\n").unwrap();
+
+        let found = discover_skills(root);
+        let rel: Vec<_> = found
+            .skills
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(rel, [".claude/skills/gamma", "skills/alpha", "skills/beta"]);
+        assert!(found.errors.is_empty());
+    }
+
+    #[test]
+    fn root_may_itself_be_a_skill() {
+        let tmp = tempfile::tempdir().unwrap();
+        skill(tmp.path(), "", "SKILL.md");
+        skill(tmp.path(), "", "skill.md");
+        assert_eq!(discover_skills(tmp.path()).skills, [tmp.path().to_path_buf()]);
+    }
+}
@@ -218,7 +218,7 @@ fn diff_eval_case_ids(current_ids: &BTreeSet<String>, previous_ids: &BTreeSet<St
 fn sha256_digest(content: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
-    format!("sha256:{}", super::hex_encode(hasher.finalize()))
+    format!("sha256:{}", crate::encoding::hex_encode(hasher.finalize()))
 }
 
 #[cfg(test)]
@@ -0,0 +1,615 @@
+//! Mechanical fixes for `SKILL.md` frontmatter, for `trg ai skills validate --fix`.
+//!
+//! Edits are made line by line on the frontmatter text rather than by re-serializing the
+//! YAML, so comments, key order, quoting of untouched fields and the Markdown body survive.
+
+use std::path::{Path, PathBuf};
+
+use unicode_normalization::UnicodeNormalization;
+
+use super::errors::Result;
+use super::parser;
+use super::validator::{ALLOWED_FRONTMATTER_FIELDS, MAX_DESC_LEN, MAX_NAME_LEN};
+use crate::fs::FileSystem;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixOptions {
+    /// Cut an over-long `description` down to the limit.
+    pub trim_description: bool,
+    /// Delete unexpected fields instead of moving them into `metadata`.
+    pub drop_unknown_fields: bool,
+    /// Resolve a name/directory mismatch by renaming the directory instead of the skill.
+    pub rename_dir: bool,
+}
+
+/// The changes `--fix` would make to one skill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixPlan {
+    pub skill_md: PathBuf,
+    pub original: String,
+    pub fixed: String,
+    /// Skill directory rename, `(from, to)`, applied after `SKILL.md` is written.
+    pub rename_dir: Option<(PathBuf, PathBuf)>,
+    /// One line per fix, for the summary.
+    pub fixes: Vec<String>,
+    /// Problems that have a mechanical fix that wasn't applied, and why.
+    pub skipped: Vec<String>,
+    /// Characters in `description` when it is over the limit and trimming wasn't requested.
+    pub long_description: Option<usize>,
+}
+
+impl FixPlan {
+    pub fn is_empty(&self) -> bool {
+        self.original == self.fixed && self.rename_dir.is_none()
+    }
+
+    /// A unified diff of `SKILL.md`, empty when only the directory changes.
+    pub fn diff(&self) -> String {
+        if self.original == self.fixed {
+            return String::new();
+        }
+        let path = self.skill_md.display().to_string();
+        similar::TextDiff::from_lines(&self.original, &self.fixed)
+            .unified_diff()
+            .context_radius(2)
+            .header(&format!("a/{path}"), &format!("b/{path}"))
+            .to_string()
+    }
+
+    /// Write `SKILL.md`, then rename the directory. Returns the skill directory afterwards.
+    pub fn apply(&self, fs: &impl FileSystem) -> std::io::Result<PathBuf> {
+        if self.original != self.fixed {
+            fs.write(&self.skill_md, &self.fixed)?;
+        }
+        match &self.rename_dir {
+            Some((from, to)) => {
+                std::fs::rename(from, to)?;
+                Ok(to.clone())
+            }
+            None => Ok(self.skill_md.parent().map(Path::to_path_buf).unwrap_or_default()),
+        }
+    }
+}
+
+/// Work out the fixes for the skill at `skill_path` without touching anything. Fails when
+/// `SKILL.md` is missing or its frontmatter doesn't parse; those need a human.
+pub fn plan_fixes(fs: &impl FileSystem, skill_path: &Path, options: FixOptions) -> Result<FixPlan> {
+    let skill_md = parser::find_skill_md(fs, skill_path)?;
+    let original = fs.read_to_string(&skill_md)?;
+    let (props, _) = parser::read_properties(fs, skill_path)?;
+
+    let mut doc = Frontmatter::parse(&original);
+    let mut plan = FixPlan {
+        skill_md,
+        original: original.clone(),
+        fixed: String::new(),
+        rename_dir: None,
+        fixes: Vec::new(),
+        skipped: Vec::new(),
+        long_description: None,
+    };
+
+    fix_name(&mut doc, &mut plan, &props.name, skill_path, options);
+    fix_allowed_tools(&mut doc, &mut plan);
+    fix_description(&mut doc, &mut plan, &props.description, options);
+    fix_unknown_fields(&mut doc, &mut plan, props.metadata.as_ref(), options);
+
+    // Nothing to fix: leave the file byte for byte as it was.
+    plan.fixed = if plan.fixes.is_empty() { original } else { doc.render() };
+    Ok(plan)
+}
+
+fn fix_name(doc: &mut Frontmatter, plan: &mut FixPlan, name: &str, skill_path: &Path, options: FixOptions) {
+    let Some(mut fixed_name) = normalize_name(name) else {
+        plan.skipped
+            .push(format!("name '{name}' has no letters or digits to keep"));
+        return;
+    };
+
+    let dir = skill_path.canonicalize().unwrap_or_else(|_| skill_path.to_path_buf());
+    let dir_name: String = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .nfkc()
+        .collect();
+    if dir_name != fixed_name {
+        if !options.rename_dir {
+            if normalize_name(&dir_name).as_deref() == Some(dir_name.as_str()) {
+                fixed_name = dir_name;
+            } else {
+                plan.skipped.push(format!(
+                    "directory name '{dir_name}' is not a valid skill name, so the skill can't take it (use --rename-dir)"
+                ));
+            }
+        } else if let Some(parent) = dir.parent() {
+            // Keep the path as the user spelled it unless it has no last component (`.`).
+            let (from, target) = match skill_path.file_name() {
+                Some(_) => (skill_path.to_path_buf(), skill_path.with_file_name(&fixed_name)),
+                None => (dir.clone(), parent.join(&fixed_name)),
+            };
+            if target.exists() {
+                plan.skipped.push(format!(
+                    "can't rename directory to '{fixed_name}': {} already exists",
+                    target.display()
+                ));
+            } else {
+                plan.fixes
+                    .push(format!("rename directory '{dir_name}' to '{fixed_name}'"));
+                plan.rename_dir = Some((from, target));
+            }
+        }
+    }
+
+    if fixed_name != name {
+        if doc.set_scalar("name", &fixed_name) {
+            plan.fixes.push(format!("name: '{name}' -> '{fixed_name}'"));
+        } else {
+            plan.skipped.push("name is not a single-line value".to_string());
+        }
+    }
+}
+
+fn fix_allowed_tools(doc: &mut Frontmatter, plan: &mut FixPlan) {
+    let Some(entry) = doc.entry("allowed-tools") else {
+        return;
+    };
+    let Some(value) = doc.single_line_scalar(&entry) else {
+        return;
+    };
+    let tools: Vec<&str> = value.split_whitespace().collect();
+    if tools.is_empty() {
+        return;
+    }
+    let mut lines = vec!["allowed-tools:".to_string()];
+    lines.extend(tools.iter().map(|tool| format!("  - {}", yaml_string(tool))));
+    doc.replace(&entry, lines);
+    plan.fixes.push(format!(
+        "allowed-tools: space-separated string -> list of {}",
+        tools.len()
+    ));
+}
+
+fn fix_description(doc: &mut Frontmatter, plan: &mut FixPlan, description: &str, options: FixOptions) {
+    let length = description.chars().count();
+    if length <= MAX_DESC_LEN {
+        return;
+    }
+    if !options.trim_description {
+        plan.long_description = Some(length);
+        return;
+    }
+    let Some(entry) = doc.entry("description") else {
+        return;
+    };
+    let trimmed = trim_to(description.trim(), MAX_DESC_LEN);
+    doc.replace(&entry, vec![format!("description: {}", yaml_string(&trimmed))]);
+    plan.fixes.push(format!(
+        "description: trimmed from {length} to {} characters",
+        trimmed.chars().count()
+    ));
+}
+
+fn fix_unknown_fields(
+    doc: &mut Frontmatter,
+    plan: &mut FixPlan,
+    metadata: Option<&std::collections::HashMap<String, String>>,
+    options: FixOptions,
+) {
+    let unknown: Vec<String> = doc
+        .entries()
+        .into_iter()
+        .map(|entry| entry.key)
+        .filter(|key| !ALLOWED_FRONTMATTER_FIELDS.contains(&key.as_str()))
+        .collect();
+
+    let metadata_is_inline = !doc.accepts_children("metadata");
+    let mut moved = Vec::new();
+    for key in unknown {
+        let Some(entry) = doc.entry(&key) else {
+            continue;
+        };
+        if options.drop_unknown_fields {
+            doc.replace(&entry, Vec::new());
+            plan.fixes.push(format!("removed field '{key}'"));
+            continue;
+        }
+        let Some(value) = doc.single_line_scalar(&entry) else {
+            plan.skipped.push(format!(
+                "'{key}' isn't a single-line value, so it can't move into metadata (use --drop-unknown-fields)"
+            ));
+            continue;
+        };
+        if metadata_is_inline {
+            plan.skipped.push(format!(
+                "metadata is written inline, so '{key}' can't move into it (use --drop-unknown-fields)"
+            ));
+            continue;
+        }
+        if metadata.is_some_and(|m| m.contains_key(&key)) || moved.iter().any(|(k, _)| k == &key) {
+            plan.skipped.push(format!("metadata already has '{key}'"));
+            continue;
+        }
+        doc.replace(&entry, Vec::new());
+        plan.fixes.push(format!("moved field '{key}' into metadata"));
+        moved.push((key, value));
+    }
+
+    if !moved.is_empty() {
+        let children: Vec<String> = moved
+            .iter()
+            .map(|(key, value)| format!("{}: {}", yaml_string(key), yaml_string(value)))
+            .collect();
+        doc.append_to_map("metadata", &children);
+    }
+}
+
+/// NFKC, lowercase, runs of anything but letters and digits collapsed to one hyphen, cut to
+/// the length limit. `None` when nothing is left.
+pub fn normalize_name(name: &str) -> Option<String> {
+    let mut normalized = String::new();
+    for c in name.trim().nfkc().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            normalized.push(c);
+        } else if !normalized.is_empty() && !normalized.ends_with('-') {
+            normalized.push('-');
+        }
+    }
+    let normalized: String = normalized.chars().take(MAX_NAME_LEN).collect();
+    let normalized = normalized.trim_end_matches('-');
+    (!normalized.is_empty()).then(|| normalized.to_string())
+}
+
+/// Cut `text` to at most `max` characters: at the last sentence end if that keeps at least
+/// half of it, otherwise at the last word boundary.
+fn trim_to(text: &str, max: usize) -> String {
+    let prefix: String = text.chars().take(max).collect();
+    if prefix.len() == text.len() {
+        return prefix;
+    }
+    let next_is_space = text[prefix.len()..].starts_with(char::is_whitespace);
+    let sentence_end = prefix
+        .char_indices()
+        .rev()
+        .find(|(i, c)| {
+            matches!(c, '.' | '!' | '?')
+                && (prefix[i + 1..].starts_with(char::is_whitespace) || (i + 1 == prefix.len() && next_is_space))
+        })
+        .map(|(i, _)| i + 1);
+    if let Some(end) = sentence_end.filter(|end| prefix[..*end].chars().count() >= max / 2) {
+        return prefix[..end].to_string();
+    }
+    let word_end = if next_is_space {
+        prefix.len()
+    } else {
+        prefix.rfind(char::is_whitespace).unwrap_or(prefix.len())
+    };
+    prefix[..word_end].trim_end_matches([' ', ',', ';', ':']).to_string()
+}
+
+/// `value` as a YAML scalar that reads back as the same string: plain when that is
+/// unambiguous, JSON-quoted (valid YAML) otherwise.
+fn yaml_string(value: &str) -> String {
+    let lower = value.to_ascii_lowercase();
+    let ambiguous = value.is_empty()
+        || value != value.trim()
+        || value.parse::<f64>().is_ok()
+        || matches!(
+            lower.as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~" | "y" | "n"
+        )
+        || value.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.contains(['\n', '\t']);
+    if ambiguous {
+        serde_json::to_string(value).expect("strings always serialize")
+    } else {
+        value.to_string()
+    }
+}
+
+/// The ` # comment` at the end of a value as written, with the whitespace before it.
+fn trailing_comment(raw: &str) -> &str {
+    let after_quotes = match raw.trim_start().starts_with(['"', '\'']) {
+        true => raw.rfind(['"', '\'']).map_or(0, |end| end + 1),
+        false => 0,
+    };
+    match raw[after_quotes..].find(" #") {
+        Some(c) => {
+            let start = raw[..after_quotes + c].trim_end().len();
+            &raw[start..]
+        }
+        None => "",
+    }
+}
+
+/// The value of a plain or quoted single-line scalar as written, without a trailing comment.
+fn unquote(raw: &str) -> Option<String> {
+    let raw = raw[..raw.len() - trailing_comment(raw).len()].trim();
+    if let Some(inner) = raw.strip_prefix('"') {
+        let end = inner.rfind('"')?;
+        return serde_json::from_str(&raw[..end + 2]).ok();
+    }
+    if let Some(inner) = raw.strip_prefix('\'') {
+        let end = inner.rfind('\'')?;
+        return Some(inner[..end].replace("''", "'"));
+    }
+    let value = raw;
+    let block_or_flow = value.starts_with(['|', '>', '[', '{', '&', '*', '!']);
+    (!value.is_empty() && !block_or_flow).then(|| value.to_string())
+}
+
+/// A top-level frontmatter key and the lines `start..end` (file line indexes) it spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    start: usize,
+    end: usize,
+}
+
+/// `SKILL.md` split into lines, with the frontmatter located.
+#[derive(Debug, Clone)]
+struct Frontmatter {
+    lines: Vec<String>,
+    /// `\r\n` when the first line ends that way, so rewritten files keep Windows line endings.
+    newline: &'static str,
+    trailing_newline: bool,
+}
+
+impl Frontmatter {
+    fn parse(content: &str) -> Self {
+        let crlf = content.find('\n').is_some_and(|end| content[..end].ends_with('\r'));
+        Self {
+            lines: content.lines().map(str::to_string).collect(),
+            newline: if crlf { "\r\n" } else { "\n" },
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = self.lines.join(self.newline);
+        if self.trailing_newline {
+            out.push_str(self.newline);
+        }
+        out
+    }
+
+    /// Index of the closing `---`; the opening one is line 0.
+    fn closing(&self) -> usize {
+        self.lines
+            .iter()
+            .skip(1)
+            .position(|line| line.trim_end() == "---")
+            .map_or(self.lines.len(), |i| i + 1)
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let closing = self.closing();
+        let mut entries: Vec<Entry> = Vec::new();
+        for index in 1..closing {
+            let line = &self.lines[index];
+            let top_level = !line.starts_with([' ', '\t', '#', '-']) && !line.trim().is_empty();
+            if top_level || line.starts_with('#') {
+                if let Some(last) = entries.last_mut() {
+                    if last.end == closing {
+                        last.end = index;
+                    }
+                }
+            }
+            if top_level {
+                if let Some((key, _)) = line.split_once(':') {
+                    entries.push(Entry {
+                        key: key.trim().to_string(),
+                        start: index,
+                        end: closing,
+                    });
+                }
+            }
+        }
+        entries
+    }
+
+    fn entry(&self, key: &str) -> Option<Entry> {
+        self.entries().into_iter().find(|entry| entry.key == key)
+    }
+
+    /// The value of an entry written as one plain or quoted scalar on its key line.
+    fn single_line_scalar(&self, entry: &Entry) -> Option<String> {
+        let continued = self.lines[entry.start + 1..entry.end]
+            .iter()
+            .any(|line| !line.trim().is_empty());
+        if continued {
+            return None;
+        }
+        let (_, raw) = self.lines[entry.start].split_once(':')?;
+        unquote(raw)
+    }
+
+    fn replace(&mut self, entry: &Entry, lines: Vec<String>) {
+        self.lines.splice(entry.start..entry.end, lines);
+    }
+
+    /// Replace a single-line scalar value, keeping a trailing comment.
+    fn set_scalar(&mut self, key: &str, value: &str) -> bool {
+        let Some(entry) = self.entry(key) else {
+            return false;
+        };
+        if self.single_line_scalar(&entry).is_none() {
+            return false;
+        }
+        let (_, raw) = self.lines[entry.start].split_once(':').unwrap_or_default();
+        let new_line = format!("{key}: {}{}", yaml_string(value), trailing_comment(raw));
+        self.lines[entry.start] = new_line;
+        true
+    }
+
+    /// The value written on `key`'s own line, without a trailing comment.
+    fn inline_value(&self, entry: &Entry) -> String {
+        let (_, raw) = self.lines[entry.start].split_once(':').unwrap_or_default();
+        raw[..raw.len() - trailing_comment(raw).len()].trim().to_string()
+    }
+
+    /// Whether [`Self::append_to_map`] can add to `key`: it is missing, a block map or `{}`.
+    fn accepts_children(&self, key: &str) -> bool {
+        self.entry(key)
+            .is_none_or(|entry| matches!(self.inline_value(&entry).as_str(), "" | "{}"))
+    }
+
+    /// Add `children` (`key: value` lines) to the block map under `key`, creating it at the end
+    /// of the frontmatter if it doesn't exist. Check [`Self::accepts_children`] first.
+    fn append_to_map(&mut self, key: &str, children: &[String]) {
+        match self.entry(key) {
+            Some(entry) => {
+                if self.inline_value(&entry) == "{}" {
+                    self.lines[entry.start] = format!("{key}:");
+                }
+                let body = &self.lines[entry.start + 1..entry.end];
+                let indent = body
+                    .iter()
+                    .find(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+                    .map_or("  ".to_string(), |line| {
+                        line[..line.len() - line.trim_start().len()].to_string()
+                    });
+                let last_content = (entry.start..entry.end)
+                    .rev()
+                    .find(|i| !self.lines[*i].trim().is_empty())
+                    .unwrap_or(entry.start);
+                let insert: Vec<String> = children.iter().map(|child| format!("{indent}{child}")).collect();
+                self.lines.splice(last_content + 1..last_content + 1, insert);
+            }
+            None => {
+                let closing = self.closing();
+                let mut insert = vec![format!("{key}:")];
+                insert.extend(children.iter().map(|child| format!("  {child}")));
+                self.lines.splice(closing..closing, insert);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentskills::validator::validate_skill;
+    use crate::fs::RealFS;
+    use std::fs;
+
+    fn skill(root: &Path, dir: &str, content: &str) -> PathBuf {
+        let path = root.join(dir);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("SKILL.md"), content).unwrap();
+        path
+    }
+
+    #[test]
+    fn fixes_frontmatter_and_keeps_comments_and_body() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = skill(
+            tmp.path(),
+            "pdf-tools",
+            "---\n# owned by docs team\nname: PDF_Tools  # display name\ndescription: Work with PDFs\nallowed-tools: Bash(git:*) Read\nversion: 1.2\nauthor: 'Ann'\nmetadata:\n  team: docs\n---\n# PDF tools\n\nBody `name: x` stays.\n",
+        );
+
+        let plan = plan_fixes(&RealFS, &path, FixOptions::default()).unwrap();
+        assert_eq!(
+            plan.fixed,
+            "---\n# owned by docs team\nname: pdf-tools  # display name\ndescription: Work with PDFs\nallowed-tools:\n  - Bash(git:*)\n  - Read\nmetadata:\n  team: docs\n  version: \"1.2\"\n  author: Ann\n---\n# PDF tools\n\nBody `name: x` stays.\n"
+        );
+        assert!(plan.rename_dir.is_none());
+        assert!(plan.skipped.is_empty(), "{:?}", plan.skipped);
+        assert!(plan
+            .diff()
+            .contains("-name: PDF_Tools  # display name\n+name: pdf-tools  # display name"));
+
+        plan.apply(&RealFS).unwrap();
+        let props = validate_skill(&RealFS, &path).unwrap();
+        assert_eq!(props.allowed_tools.unwrap(), ["Bash(git:*)", "Read"]);
+        assert_eq!(props.metadata.unwrap()["version"], "1.2");
+        assert!(plan_fixes(&RealFS, &path, FixOptions::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn renames_skill_or_directory_on_mismatch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = skill(tmp.path(), "keep-dir", "---\nname: other\ndescription: d\n---\n");
+        let plan = plan_fixes(&RealFS, &path, FixOptions::default()).unwrap();
+        assert!(plan.rename_dir.is_none());
+        assert_eq!(plan.fixed, "---\nname: keep-dir\ndescription: d\n---\n");
+
+        let path = skill(tmp.path(), "Not_Valid", "---\nname: other\ndescription: d\n---\n");
+        let plan = plan_fixes(&RealFS, &path, FixOptions::default()).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.skipped.len(), 1, "{:?}", plan.skipped);
+
+        let path = skill(tmp.path(), "old", "---\nname: new-name\ndescription: d\n---\n");
+        let options = FixOptions {
+            rename_dir: true,
+            ..FixOptions::default()
+        };
+        let plan = plan_fixes(&RealFS, &path, options).unwrap();
+        assert_eq!(plan.original, plan.fixed);
+        let renamed = plan.apply(&RealFS).unwrap();
+        assert_eq!(renamed, tmp.path().join("new-name"));
+        assert!(validate_skill(&RealFS, &renamed).is_ok());
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let clean = "---\r\nname: crlf\r\ndescription: d\r\n---\r\nBody\r\n";
+        let path = skill(tmp.path(), "crlf", clean);
+        assert!(plan_fixes(&RealFS, &path, FixOptions::default()).unwrap().is_empty());
+
+        fs::write(
+            path.join("SKILL.md"),
+            "---\r\nname: CRLF\r\ndescription: d\r\n---\r\nBody\r\n",
+        )
+        .unwrap();
+        let plan = plan_fixes(&RealFS, &path, FixOptions::default()).unwrap();
+        assert_eq!(plan.fixed, clean);
+    }
+
+    #[test]
+    fn trims_description_only_when_asked_and_drops_fields() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sentence = "Summarize quarterly reports for finance. ";
+        let long = sentence.repeat(30);
+        let path = skill(
+            tmp.path(),
+            "reports",
+            &format!("---\nname: reports\ndescription: >\n  {long}\nextra:\n  nested: true\n---\n"),
+        );
+
+        let plan = plan_fixes(&RealFS, &path, FixOptions::default()).unwrap();
+        assert!(plan.long_description.is_some_and(|n| n > MAX_DESC_LEN));
+        assert_eq!(plan.skipped.len(), 1, "nested `extra` can't move into metadata");
+
+        let options = FixOptions {
+            trim_description: true,
+            drop_unknown_fields: true,
+            ..FixOptions::default()
+        };
+        let plan = plan_fixes(&RealFS, &path, options).unwrap();
+        plan.apply(&RealFS).unwrap();
+        let props = validate_skill(&RealFS, &path).unwrap();
+        assert!(props.description.chars().count() <= MAX_DESC_LEN);
+        assert!(props.description.ends_with("finance."));
+    }
+
+    #[test]
+    fn normalizes_names_and_trims_text() {
+        assert_eq!(normalize_name("  Ｍy__Skill v2! ").as_deref(), Some("my-skill-v2"));
+        assert_eq!(normalize_name("---"), None);
+        assert_eq!(trim_to("one two three", 9), "one two");
+        assert_eq!(trim_to("Short. Then a much longer tail", 12), "Short.");
+        assert_eq!(trim_to("A. bb cc dd ee", 10), "A. bb cc");
+        assert_eq!(trim_to("First sentence. Second one", 20), "First sentence.");
+        assert_eq!(yaml_string("1.0"), "\"1.0\"");
+        assert_eq!(yaml_string("Bash(git:*)"), "Bash(git:*)");
+        assert_eq!(unquote(" \"a \\\"b\\\"\" # c").as_deref(), Some("a \"b\""));
+        assert_eq!(unquote(" 'it''s'").as_deref(), Some("it's"));
+        assert_eq!(unquote(" >"), None);
+    }
+}
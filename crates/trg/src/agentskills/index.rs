@@ -0,0 +1,453 @@
+//! A catalog of the skills under a directory tree, so `<available_skills>` can be built at agent
+//! start without walking and reparsing hundreds of `SKILL.md` files.
+//!
+//! Rebuilding reuses an entry whose skill directory hasn't been modified since the previous
+//! index, so only new and edited skills are reparsed and rehashed.
+//!
+//! Locations are stored relative to the directory holding the index file, so the index can be
+//! read from any working directory, and moved together with the skills.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::discovery::discover_skills;
+use super::models::SkillProperties;
+use super::pack::directory_digest;
+use super::parser::{find_skill_md, read_properties};
+use super::prompt::SkillWithLocation;
+use crate::fs::FileSystem;
+
+pub const INDEX_SCHEMA_VERSION: &str = "trg.skills.index.v1";
+
+#[derive(Debug, Error)]
+pub enum IndexError {
+    #[error("failed to read index {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("{path} is not a skills index: {source}")]
+    Malformed { path: PathBuf, source: serde_json::Error },
+    #[error("{path} has schema_version '{found}', expected '{INDEX_SCHEMA_VERSION}'")]
+    UnsupportedSchema { path: PathBuf, found: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillIndex {
+    pub schema_version: String,
+    /// The directory the index was built from, as given.
+    pub root: String,
+    /// Sorted by location.
+    pub skills: Vec<IndexEntry>,
+    /// Canonical directory of the index file, which locations are relative to.
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub description: String,
+    /// The skill's `SKILL.md`, relative to the directory holding the index file.
+    pub location: String,
+    /// `sha256:<hex>` over the per-file hashes `pack` would put in `MANIFEST.json`.
+    pub content_hash: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<String>,
+    /// Newest modification time of the skill directory and anything in it, RFC 3339.
+    pub last_modified: String,
+}
+
+/// The result of [`build_index`]: the new index and how it differs from the previous one.
+#[derive(Debug)]
+pub struct IndexUpdate {
+    pub index: SkillIndex,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: usize,
+    pub removed: Vec<String>,
+    /// Skills that couldn't be read; they are left out of the index.
+    pub errors: Vec<String>,
+}
+
+impl SkillIndex {
+    pub fn load(path: &Path) -> Result<Self, IndexError> {
+        let json = std::fs::read_to_string(path).map_err(|source| IndexError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let index: Self = serde_json::from_str(&json).map_err(|source| IndexError::Malformed {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if index.schema_version != INDEX_SCHEMA_VERSION {
+            return Err(IndexError::UnsupportedSchema {
+                path: path.to_path_buf(),
+                found: index.schema_version,
+            });
+        }
+        let dir = index_dir(path).map_err(|source| IndexError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self { dir, ..index })
+    }
+
+    /// Write the index to `path` through a temporary file in the same directory, renamed over
+    /// `path`, so a reader never sees a half-written index.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("index always serializes");
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("index.json");
+        let tmp = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+        let result = std::fs::write(&tmp, json + "\n").and_then(|()| std::fs::rename(&tmp, path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        result
+    }
+
+    /// The absolute path of `entry`'s `SKILL.md`.
+    pub fn skill_md(&self, entry: &IndexEntry) -> PathBuf {
+        let mut resolved = self.dir.clone();
+        for component in Path::new(&entry.location).components() {
+            match component {
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::CurDir => {}
+                other => resolved.push(other),
+            }
+        }
+        resolved
+    }
+}
+
+/// The canonical directory holding the index file at `path`, which need not exist yet.
+pub fn index_dir(path: &Path) -> std::io::Result<PathBuf> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize(),
+        _ => Path::new(".").canonicalize(),
+    }
+}
+
+/// `path` relative to `base`. Both must be absolute, without `.` or `..` components.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component<'_>> = path.components().collect();
+    let base: Vec<Component<'_>> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut relative: PathBuf = base[common..].iter().map(|_| Component::ParentDir).collect();
+    relative.extend(&path[common..]);
+    relative
+}
+
+impl IndexEntry {
+    pub fn to_skill(&self) -> SkillWithLocation {
+        SkillWithLocation {
+            properties: SkillProperties {
+                name: self.name.clone(),
+                description: self.description.clone(),
+                compatibility: self.compatibility.clone(),
+                license: self.license.clone(),
+                allowed_tools: (!self.allowed_tools.is_empty()).then(|| self.allowed_tools.clone()),
+                metadata: (!self.metadata.is_empty())
+                    .then(|| self.metadata.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+            },
+            location: Some(self.location.clone()),
+        }
+    }
+}
+
+/// Index every skill under `root` for an index file in `dir` (see [`index_dir`]). Entries of
+/// `previous` whose skill directory still has the same `last_modified` are reused as they are.
+pub fn build_index(fs: &impl FileSystem, root: &Path, dir: &Path, previous: Option<&SkillIndex>) -> IndexUpdate {
+    let mut previous: HashMap<&str, &IndexEntry> = previous
+        .map(|index| index.skills.iter().map(|e| (e.location.as_str(), e)).collect())
+        .unwrap_or_default();
+    let discovery = discover_skills(root);
+    let mut update = IndexUpdate {
+        index: SkillIndex {
+            schema_version: INDEX_SCHEMA_VERSION.to_string(),
+            root: root.display().to_string(),
+            skills: Vec::with_capacity(discovery.skills.len()),
+            dir: dir.to_path_buf(),
+        },
+        added: Vec::new(),
+        updated: Vec::new(),
+        unchanged: 0,
+        removed: Vec::new(),
+        errors: discovery.errors,
+    };
+
+    for skill_dir in &discovery.skills {
+        let location = match find_skill_md(fs, skill_dir).and_then(|path| Ok(path.canonicalize()?)) {
+            Ok(path) => relative_path(&path, dir).display().to_string(),
+            Err(e) => {
+                update.errors.push(format!("{}: {e}", skill_dir.display()));
+                continue;
+            }
+        };
+        let last_modified = match newest_modification(skill_dir) {
+            Ok(time) => DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Nanos, true),
+            Err(e) => {
+                update.errors.push(format!("{}: {e}", skill_dir.display()));
+                continue;
+            }
+        };
+        let known = previous.remove(location.as_str());
+        if let Some(entry) = known.filter(|entry| entry.last_modified == last_modified) {
+            update.index.skills.push(entry.clone());
+            update.unchanged += 1;
+            continue;
+        }
+        match index_entry(fs, skill_dir, location, last_modified) {
+            Ok(entry) => {
+                if known.is_some() {
+                    update.updated.push(entry.name.clone());
+                } else {
+                    update.added.push(entry.name.clone());
+                }
+                update.index.skills.push(entry);
+            }
+            Err(e) => update.errors.push(format!("{}: {e}", skill_dir.display())),
+        }
+    }
+
+    update.removed = previous.into_values().map(|entry| entry.name.clone()).collect();
+    update.removed.sort();
+    update.index.skills.sort_by(|a, b| a.location.cmp(&b.location));
+    update
+}
+
+fn index_entry(
+    fs: &impl FileSystem,
+    skill_dir: &Path,
+    location: String,
+    last_modified: String,
+) -> Result<IndexEntry, String> {
+    let (properties, _) = read_properties(fs, skill_dir).map_err(|e| e.to_string())?;
+    let digest = directory_digest(skill_dir).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    for (path, hash) in &digest {
+        hasher.update(format!("{path}\0{hash}\n"));
+    }
+    Ok(IndexEntry {
+        name: properties.name,
+        description: properties.description,
+        location,
+        content_hash: format!("sha256:{}", crate::encoding::hex_encode(hasher.finalize())),
+        metadata: properties.metadata.unwrap_or_default().into_iter().collect(),
+        allowed_tools: properties.allowed_tools.unwrap_or_default(),
+        license: properties.license,
+        compatibility: properties.compatibility,
+        last_modified,
+    })
+}
+
+/// The newest mtime of `dir` and everything under it. Directory mtimes catch added, removed
+/// and renamed files.
+fn newest_modification(dir: &Path) -> std::io::Result<SystemTime> {
+    let mut newest = std::fs::metadata(dir)?.modified()?;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let modified = if file_type.is_dir() {
+            newest_modification(&entry.path())?
+        } else {
+            entry.metadata()?.modified()?
+        };
+        newest = newest.max(modified);
+    }
+    Ok(newest)
+}
+
+/// Which skills `to-prompt` keeps. A skill must pass every kind of filter given; within a kind,
+/// any one match is enough.
+#[derive(Debug, Default)]
+pub struct SkillFilter {
+    metadata: Vec<(String, Option<String>)>,
+    tags: Vec<String>,
+    globs: Option<GlobSet>,
+}
+
+impl SkillFilter {
+    /// `metadata` entries are `KEY` (the key is present) or `KEY=VALUE`; `tags` are looked up in
+    /// the comma- or space-separated `tags` metadata value; `globs` match the skill name or its
+    /// directory.
+    pub fn new(metadata: &[String], tags: &[String], globs: &[String]) -> Result<Self, globset::Error> {
+        let globs = if globs.is_empty() {
+            None
+        } else {
+            let mut builder = GlobSetBuilder::new();
+            for glob in globs {
+                builder.add(Glob::new(glob)?);
+            }
+            Some(builder.build()?)
+        };
+        Ok(Self {
+            metadata: metadata
+                .iter()
+                .map(|filter| match filter.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (filter.clone(), None),
+                })
+                .collect(),
+            tags: tags.to_vec(),
+            globs,
+        })
+    }
+
+    pub fn matches(&self, skill: &SkillWithLocation) -> bool {
+        let metadata = skill.properties.metadata.as_ref();
+        let lookup = |key: &str| metadata.and_then(|m| m.get(key)).map(String::as_str);
+
+        let metadata_ok = self.metadata.is_empty()
+            || self.metadata.iter().any(|(key, value)| match (lookup(key), value) {
+                (Some(found), Some(value)) => found == value,
+                (found, None) => found.is_some(),
+                (None, Some(_)) => false,
+            });
+        let tags_ok = self.tags.is_empty() || {
+            let tags: Vec<&str> = lookup("tags")
+                .unwrap_or_default()
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            self.tags.iter().any(|tag| tags.contains(&tag.as_str()))
+        };
+        let globs_ok = self.globs.as_ref().is_none_or(|globs| {
+            let dir = skill.location.as_deref().map(Path::new).and_then(Path::parent);
+            globs.is_match(&skill.properties.name) || dir.is_some_and(|dir| globs.is_match(dir))
+        });
+        metadata_ok && tags_ok && globs_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::RealFS;
+
+    fn write_skill(root: &Path, name: &str, metadata: &str) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("SKILL.md"),
+            format!("---\nname: {name}\ndescription: Does {name} things.\n{metadata}---\n\n# {name}\n"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn rebuild_reuses_unmodified_skills() {
+        let root = tempfile::tempdir().unwrap();
+        write_skill(root.path(), "alpha", "");
+        write_skill(root.path(), "beta", "license: MIT\n");
+
+        let dir = root.path().canonicalize().unwrap();
+        let first = build_index(&RealFS, root.path(), &dir, None);
+        assert_eq!(first.added, ["alpha", "beta"]);
+        assert!(first.errors.is_empty());
+        assert_eq!(first.index.skills[1].license.as_deref(), Some("MIT"));
+        assert!(first.index.skills[0].content_hash.starts_with("sha256:"));
+        assert_eq!(first.index.skills[0].location, "alpha/SKILL.md");
+
+        std::fs::remove_dir_all(root.path().join("alpha")).unwrap();
+        write_skill(root.path(), "gamma", "");
+        std::fs::write(root.path().join("beta/notes.md"), "more").unwrap();
+        // Coarse filesystem timestamps could hide the edit to beta; force it to look stale.
+        let mut stale = first.index.clone();
+        stale.skills[1].last_modified = "1970-01-01T00:00:00Z".to_string();
+        let second = build_index(&RealFS, root.path(), &dir, Some(&stale));
+        assert_eq!(second.added, ["gamma"]);
+        assert_eq!(second.updated, ["beta"]);
+        assert_eq!(second.removed, ["alpha"]);
+        assert_ne!(second.index.skills[0].content_hash, first.index.skills[1].content_hash);
+
+        let third = build_index(&RealFS, root.path(), &dir, Some(&second.index));
+        assert_eq!(third.unchanged, 2);
+        assert!(third.added.is_empty() && third.updated.is_empty() && third.removed.is_empty());
+        assert_eq!(third.index, second.index);
+    }
+
+    #[test]
+    fn locations_are_relative_to_the_index_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_skill(&tmp.path().join("skills"), "alpha", "");
+        let out = tmp.path().join("out");
+        std::fs::create_dir(&out).unwrap();
+        let index_path = out.join("index.json");
+
+        let dir = index_dir(&index_path).unwrap();
+        let update = build_index(&RealFS, &tmp.path().join("skills"), &dir, None);
+        assert_eq!(update.index.skills[0].location, "../skills/alpha/SKILL.md");
+        update.index.save(&index_path).unwrap();
+        assert_eq!(std::fs::read_dir(&out).unwrap().count(), 1);
+
+        let loaded = SkillIndex::load(&index_path).unwrap();
+        assert_eq!(
+            loaded.skill_md(&loaded.skills[0]),
+            tmp.path().canonicalize().unwrap().join("skills/alpha/SKILL.md")
+        );
+    }
+
+    #[test]
+    fn filters_by_metadata_tag_and_glob() {
+        let skill = |name: &str, location: &str, metadata: &[(&str, &str)]| SkillWithLocation {
+            properties: SkillProperties {
+                name: name.to_string(),
+                description: String::new(),
+                compatibility: None,
+                license: None,
+                allowed_tools: None,
+                metadata: Some(metadata.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
+            },
+            location: Some(location.to_string()),
+        };
+        let csv = skill(
+            "csv",
+            "skills/data/csv/SKILL.md",
+            &[("team", "data"), ("tags", "csv, tables")],
+        );
+        let pdf = skill("pdf", "skills/docs/pdf/SKILL.md", &[("tags", "documents")]);
+        let matching = |filter: SkillFilter| -> Vec<String> {
+            [&csv, &pdf]
+                .into_iter()
+                .filter(|s| filter.matches(s))
+                .map(|s| s.properties.name.clone())
+                .collect()
+        };
+        let strings = |values: &[&str]| values.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(matching(SkillFilter::default()), ["csv", "pdf"]);
+        assert_eq!(
+            matching(SkillFilter::new(&strings(&["team"]), &[], &[]).unwrap()),
+            ["csv"]
+        );
+        assert_eq!(
+            matching(SkillFilter::new(&strings(&["team=docs"]), &[], &[]).unwrap()),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            matching(SkillFilter::new(&[], &strings(&["tables", "documents"]), &[]).unwrap()),
+            ["csv", "pdf"]
+        );
+        assert_eq!(
+            matching(SkillFilter::new(&[], &[], &strings(&["skills/docs/*"])).unwrap()),
+            ["pdf"]
+        );
+        assert_eq!(
+            matching(SkillFilter::new(&[], &strings(&["documents"]), &strings(&["c*"])).unwrap()),
+            Vec::<String>::new()
+        );
+    }
+}
@@ -0,0 +1,703 @@
+//! Checks on the Markdown body of `SKILL.md` and the files around it, complementing the
+//! frontmatter checks in `validator`.
+//!
+//! Rules can be turned off or have their severity changed per repository with a
+//! `.trg-lint.toml` in the skill directory or any ancestor:
+//!
+//! ```toml
+//! [rules]
+//! unreferenced-file = "off"
+//! script-not-executable = "error"
+//! ```
+//!
+//! The same file holds the token budgets of [`super::tokens`].
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::parser;
+use super::tokens::{TokenBudgets, Tokenizer, TokenizerKind};
+use super::tools::{check_allowed_tools, ToolProblem};
+
+pub const LINT_CONFIG_FILE: &str = ".trg-lint.toml";
+
+static LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"!?\[[^\]]*\]\(\s*<?([^)\s>]+)>?(?:\s+"[^"]*")?\s*\)"#).unwrap());
+static LINK_DEFINITION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s{0,3}\[[^\]]+\]:\s*<?([^\s>]+)>?").unwrap());
+/// Paths under the directories the Agent Skills layout reserves for bundled resources; bare
+/// mentions of them (`scripts/run.py`) count as references, as Markdown links do.
+static RESOURCE_PATH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\./)?(?:scripts|references|assets)/(?:[A-Za-z0-9_\-.]+/)*[A-Za-z0-9_\-.]*[A-Za-z0-9_\-/]").unwrap()
+});
+
+#[derive(Error, Debug)]
+pub enum LintError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid {}: {source}", path.display())]
+    Config {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    BrokenReference,
+    ScriptNotExecutable,
+    UnreferencedFile,
+    MalformedHeading,
+    DescriptionBudget,
+    UnknownTool,
+    InvalidToolPattern,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 7] = [
+        Self::BrokenReference,
+        Self::ScriptNotExecutable,
+        Self::UnreferencedFile,
+        Self::MalformedHeading,
+        Self::DescriptionBudget,
+        Self::UnknownTool,
+        Self::InvalidToolPattern,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BrokenReference => "broken-reference",
+            Self::ScriptNotExecutable => "script-not-executable",
+            Self::UnreferencedFile => "unreferenced-file",
+            Self::MalformedHeading => "malformed-heading",
+            Self::DescriptionBudget => "description-budget",
+            Self::UnknownTool => "unknown-tool",
+            Self::InvalidToolPattern => "invalid-tool-pattern",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::BrokenReference => "Files linked or mentioned in SKILL.md exist in the skill directory",
+            Self::ScriptNotExecutable => "Scripts referenced from SKILL.md are executable",
+            Self::UnreferencedFile => "Every bundled file is reachable from SKILL.md",
+            Self::MalformedHeading => "Headings have text, a space after the #s and no skipped levels",
+            Self::DescriptionBudget => "The description fits its token budget in <available_skills>",
+            Self::UnknownTool => "Every allowed-tools entry names a tool some runner knows",
+            Self::InvalidToolPattern => "allowed-tools entries use a specifier syntax the runner accepts",
+        }
+    }
+
+    fn default_severity(&self) -> Severity {
+        match self {
+            Self::BrokenReference | Self::DescriptionBudget => Severity::Error,
+            Self::ScriptNotExecutable
+            | Self::UnreferencedFile
+            | Self::MalformedHeading
+            | Self::UnknownTool
+            | Self::InvalidToolPattern => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+/// Severity of each rule and the token budgets, from the nearest `.trg-lint.toml` or the
+/// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    rules: RuleSeverities,
+    budgets: TokenBudgets,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintConfigFile {
+    #[serde(default)]
+    rules: RuleSeverities,
+    #[serde(default)]
+    budgets: TokenBudgets,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct RuleSeverities {
+    broken_reference: Option<Severity>,
+    script_not_executable: Option<Severity>,
+    unreferenced_file: Option<Severity>,
+    malformed_heading: Option<Severity>,
+    description_budget: Option<Severity>,
+    unknown_tool: Option<Severity>,
+    invalid_tool_pattern: Option<Severity>,
+}
+
+impl LintConfig {
+    /// The config that applies to `skill_dir`: the first `.trg-lint.toml` found walking up
+    /// from it, or the defaults when there is none.
+    pub fn load_for(skill_dir: &Path) -> Result<Self, LintError> {
+        let start = skill_dir.canonicalize().unwrap_or_else(|_| skill_dir.to_path_buf());
+        for dir in start.ancestors() {
+            let path = dir.join(LINT_CONFIG_FILE);
+            if path.is_file() {
+                return Self::parse(&std::fs::read_to_string(&path)?)
+                    .map_err(|source| LintError::Config { path, source });
+            }
+        }
+        Ok(Self::default())
+    }
+
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        let file: LintConfigFile = toml::from_str(content)?;
+        Ok(Self {
+            rules: file.rules,
+            budgets: file.budgets,
+        })
+    }
+
+    pub fn budgets(&self) -> &TokenBudgets {
+        &self.budgets
+    }
+
+    pub fn severity(&self, rule: LintRule) -> Severity {
+        let configured = match rule {
+            LintRule::BrokenReference => self.rules.broken_reference,
+            LintRule::ScriptNotExecutable => self.rules.script_not_executable,
+            LintRule::UnreferencedFile => self.rules.unreferenced_file,
+            LintRule::MalformedHeading => self.rules.malformed_heading,
+            LintRule::DescriptionBudget => self.rules.description_budget,
+            LintRule::UnknownTool => self.rules.unknown_tool,
+            LintRule::InvalidToolPattern => self.rules.invalid_tool_pattern,
+        };
+        configured.unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub severity: Severity,
+    /// `SKILL.md`, or the offending file for `unreferenced-file`.
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning | Severity::Off => "warning",
+        };
+        write!(f, "{severity}[{}] {}", self.rule.as_str(), self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// A path referenced from a Markdown file, relative to the skill directory.
+struct Reference {
+    line: u32,
+    /// As written, for messages.
+    text: String,
+    path: PathBuf,
+    /// A bare path inside a fenced code block.
+    fenced: bool,
+}
+
+/// Lint the body of the skill at `skill_dir` (a directory holding `SKILL.md`). Rules set to
+/// `off` are skipped; a skill without a readable `SKILL.md` has nothing to lint.
+pub fn lint_skill(skill_dir: &Path, skill_md: &Path, config: &LintConfig) -> Vec<LintFinding> {
+    let Ok(content) = std::fs::read_to_string(skill_md) else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+    let mut finding = |rule: LintRule, path: &Path, line: Option<u32>, message: String| {
+        let severity = config.severity(rule);
+        if severity != Severity::Off {
+            findings.push(LintFinding {
+                rule,
+                severity,
+                path: path.to_path_buf(),
+                line,
+                message,
+            });
+        }
+    };
+
+    let references = references_in(&content, Path::new(""));
+    let mut reported = HashSet::new();
+    for reference in &references {
+        let target = skill_dir.join(&reference.path);
+        // Code samples may name files the reader creates (`--out assets/report.pdf`); only
+        // prose has to point at files that exist. Existing ones still count as referenced.
+        if reference.fenced && !target.exists() {
+            continue;
+        }
+        if !reported.insert(reference.path.clone()) {
+            continue;
+        }
+        if !target.exists() {
+            finding(
+                LintRule::BrokenReference,
+                skill_md,
+                Some(reference.line),
+                format!("`{}` does not exist in the skill directory", reference.text),
+            );
+        } else if reference.path.starts_with("scripts") && target.is_file() && !is_executable(&target) {
+            finding(
+                LintRule::ScriptNotExecutable,
+                skill_md,
+                Some(reference.line),
+                format!("`{}` is not executable (chmod +x)", reference.text),
+            );
+        }
+    }
+
+    for (line, message) in heading_problems(&content) {
+        finding(LintRule::MalformedHeading, skill_md, Some(line), message);
+    }
+
+    if let Ok(properties) = parser::properties_from_content(&content) {
+        let key_lines = parser::frontmatter_key_lines(&content);
+        let key_line = |key: &str| key_lines.iter().find(|(found, _)| found == key).map(|(_, line)| *line);
+        if let Some(budget) = config.budgets().description() {
+            let tokens = TokenizerKind::default().count(&properties.description);
+            if tokens > budget {
+                finding(
+                    LintRule::DescriptionBudget,
+                    skill_md,
+                    key_line("description"),
+                    format!(
+                        "description is about {tokens} tokens, over the {budget}-token budget for <available_skills>"
+                    ),
+                );
+            }
+        }
+        let tools = properties.allowed_tools.unwrap_or_default();
+        for problem in check_allowed_tools(&tools).problems {
+            let (rule, tool) = match &problem {
+                ToolProblem::Unknown { tool, .. } => (LintRule::UnknownTool, tool),
+                ToolProblem::BadPattern { tool, .. } => (LintRule::InvalidToolPattern, tool),
+            };
+            let line = key_line("allowed-tools").map(|key| frontmatter_line_containing(&content, key, tool));
+            finding(rule, skill_md, line, problem.to_string());
+        }
+    }
+
+    if config.severity(LintRule::UnreferencedFile) != Severity::Off {
+        let reachable = reachable_paths(skill_dir, references);
+        for file in bundled_files(skill_dir) {
+            let referenced = file.ancestors().any(|ancestor| reachable.contains(ancestor));
+            if !referenced {
+                finding(
+                    LintRule::UnreferencedFile,
+                    &skill_dir.join(&file),
+                    None,
+                    format!("`{}` is not referenced from SKILL.md", file.display()),
+                );
+            }
+        }
+    }
+
+    findings
+}
+
+/// The first frontmatter line from `start` on that mentions `text`, or `start` itself, so an
+/// `allowed-tools` entry written as a YAML list points at its own line.
+fn frontmatter_line_containing(content: &str, start: u32, text: &str) -> u32 {
+    content
+        .lines()
+        .enumerate()
+        .skip(start as usize - 1)
+        .take_while(|(index, line)| *index + 1 == start as usize || line.trim_end() != "---")
+        .find(|(_, line)| line.contains(text))
+        .map_or(start, |(index, _)| index as u32 + 1)
+}
+
+/// Files that `content`, the `SKILL.md` of the skill at `skill_dir`, references directly or
+/// through referenced Markdown files, relative to `skill_dir`. Missing files and directories
+/// are left out.
+pub fn referenced_files(skill_dir: &Path, content: &str) -> BTreeSet<PathBuf> {
+    reachable_paths(skill_dir, references_in(content, Path::new("")))
+        .into_iter()
+        .filter(|path| skill_dir.join(path).is_file())
+        .collect()
+}
+
+/// Relative paths linked from or mentioned in a Markdown document located at `base` (relative
+/// to the skill directory). Links resolve against the document's directory; bare resource paths
+/// (`scripts/x.py`) always resolve against the skill directory. Frontmatter is skipped, and
+/// links are ignored inside fenced code blocks, where only bare resource paths count, marked
+/// as fenced.
+fn references_in(content: &str, base: &Path) -> Vec<Reference> {
+    let mut references = Vec::new();
+    let mut in_fence = false;
+    for (line_no, line) in body_lines(content) {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        let mut seen_on_line = HashSet::new();
+        if !in_fence {
+            let links = LINK_RE
+                .captures_iter(line)
+                .chain(LINK_DEFINITION_RE.captures_iter(line))
+                .filter_map(|captures| captures.get(1));
+            for target in links {
+                let Some(path) = link_path(target.as_str()) else {
+                    continue;
+                };
+                let Some(path) = normalize(&base.join(&path)) else {
+                    continue;
+                };
+                seen_on_line.insert(target.range());
+                references.push(Reference {
+                    line: line_no,
+                    text: target.as_str().to_string(),
+                    path,
+                    fenced: false,
+                });
+            }
+        }
+        for found in RESOURCE_PATH_RE.find_iter(line) {
+            let preceded_by_path = line[..found.start()]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || matches!(c, '/' | '.' | '-' | '_' | '$' | '{' | '<'));
+            let inside_link = seen_on_line
+                .iter()
+                .any(|range| range.start <= found.start() && found.end() <= range.end);
+            let placeholder = line[found.end()..].starts_with(['*', '{', '<', '$']);
+            if preceded_by_path || inside_link || placeholder {
+                continue;
+            }
+            let text = found.as_str();
+            if let Some(path) = normalize(Path::new(text)) {
+                references.push(Reference {
+                    line: line_no,
+                    text: text.to_string(),
+                    path,
+                    fenced: in_fence,
+                });
+            }
+        }
+    }
+    references
+}
+
+/// The local file a link target names, or `None` for URLs, anchors, absolute paths and
+/// templated targets.
+fn link_path(target: &str) -> Option<String> {
+    let has_scheme = target.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+    });
+    if has_scheme || target.starts_with(['#', '/']) || target.contains(['{', '<', '*', '$']) {
+        return None;
+    }
+    let path = target.split(['#', '?']).next().unwrap_or_default();
+    (!path.is_empty()).then(|| path.replace("%20", " "))
+}
+
+/// Lexically resolve `.` and `..`; `None` when the path climbs out of the skill directory.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!normalized.as_os_str().is_empty()).then_some(normalized)
+}
+
+/// Lines of `content` after the frontmatter block, with their 1-based line numbers in the file.
+pub(super) fn body_lines(content: &str) -> impl Iterator<Item = (u32, &str)> {
+    let mut lines = content.lines().enumerate().peekable();
+    if lines.peek().is_some_and(|(_, first)| first.trim_end() == "---") {
+        let closing = content.lines().skip(1).position(|line| line.trim_end() == "---");
+        if let Some(closing) = closing {
+            lines.nth(closing + 1);
+        }
+    }
+    lines.map(|(index, line)| (index as u32 + 1, line))
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+fn heading_problems(content: &str) -> Vec<(u32, String)> {
+    let mut problems = Vec::new();
+    let mut in_fence = false;
+    let mut previous_level = None;
+    for (line_no, line) in body_lines(content) {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || !line.starts_with('#') {
+            continue;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if level > 6 {
+            continue;
+        }
+        let rest = &line[level..];
+        if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+            problems.push((line_no, format!("missing space after `{}`", &line[..level])));
+            continue;
+        }
+        if rest.trim().trim_end_matches('#').trim().is_empty() {
+            problems.push((line_no, "heading has no text".to_string()));
+            continue;
+        }
+        if let Some(previous) = previous_level {
+            if level > previous + 1 {
+                problems.push((line_no, format!("heading level skips from h{previous} to h{level}")));
+            }
+        }
+        previous_level = Some(level);
+    }
+    problems
+}
+
+/// Everything reachable from `SKILL.md`: its references, plus references in the Markdown files
+/// it references, transitively.
+fn reachable_paths(skill_dir: &Path, references: Vec<Reference>) -> HashSet<PathBuf> {
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<PathBuf> = references.into_iter().map(|reference| reference.path).collect();
+    while let Some(path) = pending.pop() {
+        if !reachable.insert(path.clone()) {
+            continue;
+        }
+        if path.extension().is_some_and(|ext| ext == "md") {
+            if let Ok(content) = std::fs::read_to_string(skill_dir.join(&path)) {
+                let base = path.parent().unwrap_or(Path::new(""));
+                pending.extend(
+                    references_in(&content, base)
+                        .into_iter()
+                        .map(|reference| reference.path),
+                );
+            }
+        }
+    }
+    reachable
+}
+
+/// Files shipped with the skill, relative to `skill_dir`: everything except `SKILL.md`, license
+/// files, hidden entries and the `evals/` directory, which is tooling rather than skill content.
+fn bundled_files(skill_dir: &Path) -> BTreeSet<PathBuf> {
+    fn walk(dir: &Path, relative: &Path, files: &mut BTreeSet<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+            if name_str.starts_with('.') {
+                continue;
+            }
+            let relative = relative.join(&name);
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                walk(&entry.path(), &relative, files);
+            } else {
+                files.insert(relative);
+            }
+        }
+    }
+
+    let mut files = BTreeSet::new();
+    walk(skill_dir, Path::new(""), &mut files);
+    files.retain(|file| {
+        let top = file
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned());
+        let top = top.unwrap_or_default();
+        let is_skill_md = file.components().count() == 1 && matches!(top.as_str(), "SKILL.md" | "skill.md");
+        !(is_skill_md || top == "evals" || top.starts_with("LICENSE") || top.starts_with("LICENCE"))
+    });
+    files
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const SKILL_MD: &str = "---
+name: charts
+description: Make charts
+---
+# Charts
+
+Run `scripts/plot.py` on the data, then see [the guide](references/guide.md#usage).
+Details live in [missing](references/missing.md) and scripts/gone.sh.
+See https://example.com/scripts/remote.py and `scripts/<name>.py` for your own.
+
+```sh
+python scripts/plot.py --out assets/out/ --style references/yours.md
+# not a heading
+```
+
+###Setup
+###
+#### Too deep
+";
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn skill() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(root, "SKILL.md", SKILL_MD);
+        write(root, "scripts/plot.py", "print()");
+        write(root, "references/guide.md", "See [style](style.md).");
+        write(root, "references/style.md", "");
+        write(root, "assets/out/template.png", "");
+        write(root, "notes.txt", "");
+        write(root, "LICENSE.txt", "");
+        write(root, "evals/evals.json", "{}");
+        write(root, ".hidden", "");
+        tmp
+    }
+
+    fn summary(findings: &[LintFinding]) -> Vec<(LintRule, Option<u32>, String)> {
+        findings.iter().map(|f| (f.rule, f.line, f.message.clone())).collect()
+    }
+
+    #[test]
+    fn reports_each_rule_with_lines() {
+        let tmp = skill();
+        let root = tmp.path();
+        let findings = lint_skill(root, &root.join("SKILL.md"), &LintConfig::default());
+
+        let mut expected = vec![
+            (
+                LintRule::BrokenReference,
+                Some(8),
+                "`references/missing.md` does not exist in the skill directory".to_string(),
+            ),
+            (
+                LintRule::BrokenReference,
+                Some(8),
+                "`scripts/gone.sh` does not exist in the skill directory".to_string(),
+            ),
+        ];
+        if cfg!(unix) {
+            expected.insert(
+                0,
+                (
+                    LintRule::ScriptNotExecutable,
+                    Some(7),
+                    "`scripts/plot.py` is not executable (chmod +x)".to_string(),
+                ),
+            );
+        }
+        expected.extend([
+            (
+                LintRule::MalformedHeading,
+                Some(16),
+                "missing space after `###`".to_string(),
+            ),
+            (LintRule::MalformedHeading, Some(17), "heading has no text".to_string()),
+            (
+                LintRule::MalformedHeading,
+                Some(18),
+                "heading level skips from h1 to h4".to_string(),
+            ),
+            (
+                LintRule::UnreferencedFile,
+                None,
+                "`notes.txt` is not referenced from SKILL.md".to_string(),
+            ),
+        ]);
+        assert_eq!(summary(&findings), expected);
+        assert_eq!(findings[0].path, root.join("SKILL.md"));
+        assert_eq!(findings.last().unwrap().path, root.join("notes.txt"));
+    }
+
+    #[test]
+    fn config_changes_severity_and_turns_rules_off() {
+        let tmp = skill();
+        let root = tmp.path();
+        fs::write(
+            root.join(LINT_CONFIG_FILE),
+            "[rules]\nunreferenced-file = \"off\"\nmalformed-heading = \"error\"\n\n[budgets]\ndescription = 1\n",
+        )
+        .unwrap();
+        let nested = root.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+
+        let config = LintConfig::load_for(&nested).unwrap();
+        assert_eq!(config.severity(LintRule::UnreferencedFile), Severity::Off);
+        assert_eq!(config.severity(LintRule::MalformedHeading), Severity::Error);
+        assert_eq!(config.severity(LintRule::BrokenReference), Severity::Error);
+
+        let findings = lint_skill(root, &root.join("SKILL.md"), &config);
+        assert!(findings.iter().all(|f| f.rule != LintRule::UnreferencedFile));
+        assert!(findings
+            .iter()
+            .filter(|f| f.rule == LintRule::MalformedHeading)
+            .all(|f| f.severity == Severity::Error));
+        let over_budget: Vec<_> = findings
+            .iter()
+            .filter(|f| f.rule == LintRule::DescriptionBudget)
+            .map(|f| (f.line, f.message.as_str()))
+            .collect();
+        assert_eq!(
+            over_budget,
+            [(
+                Some(3),
+                "description is about 2 tokens, over the 1-token budget for <available_skills>"
+            )]
+        );
+
+        fs::write(root.join(LINT_CONFIG_FILE), "[rules]\nno-such-rule = \"off\"\n").unwrap();
+        assert!(matches!(LintConfig::load_for(root), Err(LintError::Config { .. })));
+    }
+}
@@ -0,0 +1,693 @@
+//! Reproducible skill archives: a gzipped tarball of the skill directory under a single
+//! `<name>/` root, with a `MANIFEST.json` of per-file SHA-256 hashes.
+//!
+//! Packing the same tree twice gives byte-identical archives: entries are sorted, and
+//! timestamps, owners and the gzip header are fixed. Only the execute bit of a file's mode
+//! is kept, and the manifest lists which files have it. Hidden files and symlinks are left
+//! out.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::{Compression, GzBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::errors::SkillError;
+use super::models::SkillProperties;
+use super::parser;
+use super::runner::{detect_tampering, SkillDigest};
+use super::validator::validate_skill;
+use crate::fs::RealFS;
+
+pub const MANIFEST_FILE: &str = "MANIFEST.json";
+/// Detached signature over `MANIFEST.json`, kept next to it in an unpacked skill.
+pub const SIGNATURE_FILE: &str = "MANIFEST.sig";
+pub const PACK_SCHEMA_VERSION: &str = "trg.skills.pack.v1";
+
+/// Largest file [`read_pack`] accepts, decompressed.
+pub const MAX_PACKED_FILE_SIZE: u64 = 16 * 1024 * 1024;
+/// Largest total of all files [`read_pack`] accepts, decompressed.
+pub const MAX_PACK_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum PackError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Skill(#[from] SkillError),
+
+    #[error("invalid {MANIFEST_FILE}: {0}")]
+    Manifest(#[from] serde_json::Error),
+
+    #[error("not a skill archive: {0}")]
+    Malformed(String),
+
+    #[error("unsupported {MANIFEST_FILE} schema_version '{0}' (expected {PACK_SCHEMA_VERSION})")]
+    UnsupportedSchema(String),
+
+    #[error("archive is too large: {0}")]
+    TooLarge(String),
+
+    #[error("archive does not match its {MANIFEST_FILE}: {}", .0.join("; "))]
+    Tampered(Vec<String>),
+
+    #[error("{} already exists (pass --force to overwrite)", .0.display())]
+    AlreadyExists(PathBuf),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub schema_version: String,
+    /// Version of the `trg` that built the archive.
+    pub trg_version: String,
+    pub skill: SkillProperties,
+    /// `sha256:<hex>` per file, keyed by path relative to the skill root, as
+    /// [`compute_skill_digest`](super::runner::compute_skill_digest) records them.
+    pub files: SkillDigest,
+    /// Paths in `files` with the execute bit set.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub executables: BTreeSet<String>,
+}
+
+/// A verified archive, held in memory.
+#[derive(Debug)]
+pub struct SkillPack {
+    pub manifest: PackManifest,
+    /// `MANIFEST.json` exactly as stored, which is what signatures cover.
+    manifest_json: Vec<u8>,
+    files: BTreeMap<String, PackedFile>,
+}
+
+/// A skill directory whose files match the `MANIFEST.json` it was unpacked with.
+#[derive(Debug)]
+pub struct UnpackedSkill {
+    pub manifest: PackManifest,
+    /// `sha256:<hex>` of `MANIFEST.json`.
+    pub manifest_digest: String,
+}
+
+#[derive(Debug)]
+struct PackedFile {
+    contents: Vec<u8>,
+    executable: bool,
+}
+
+/// Validate the skill at `skill_dir` and write its archive to `out`. Returns the manifest
+/// stored in the archive.
+pub fn pack_skill(skill_dir: &Path, out: impl Write) -> Result<PackManifest, PackError> {
+    let skill = validate_skill(&RealFS, skill_dir)?;
+    let mut files = BTreeMap::new();
+    collect_files(skill_dir, Path::new(""), &mut files)?;
+
+    let manifest = PackManifest {
+        schema_version: PACK_SCHEMA_VERSION.to_string(),
+        trg_version: env!("CARGO_PKG_VERSION").to_string(),
+        skill,
+        files: files
+            .iter()
+            .map(|(path, file)| (path.clone(), sha256(&file.contents)))
+            .collect(),
+        executables: executables(&files),
+    };
+    let mut manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    manifest_json.push(b'\n');
+
+    let gzip = GzBuilder::new().mtime(0).write(out, Compression::default());
+    let mut tar = tar::Builder::new(gzip);
+    let root = &manifest.skill.name;
+    append(&mut tar, &format!("{root}/{MANIFEST_FILE}"), &manifest_json, false)?;
+    for (path, file) in &files {
+        append(&mut tar, &format!("{root}/{path}"), &file.contents, file.executable)?;
+    }
+    tar.into_inner()?.finish()?.flush()?;
+    Ok(manifest)
+}
+
+/// Read an archive and check it against its manifest: every listed file present with the
+/// listed hash and execute bit, nothing unlisted, and a `SKILL.md` whose frontmatter matches
+/// the manifest. Files over [`MAX_PACKED_FILE_SIZE`], or [`MAX_PACK_SIZE`] in total, are
+/// refused before they are read into memory.
+pub fn read_pack(archive: impl Read) -> Result<SkillPack, PackError> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    let mut root: Option<String> = None;
+    let mut manifest_json = None;
+    let mut files = BTreeMap::new();
+    let mut total_size = 0;
+
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        let raw_path = entry.path()?.into_owned();
+        let components = safe_components(&raw_path)
+            .ok_or_else(|| PackError::Malformed(format!("unsafe path '{}'", raw_path.display())))?;
+        if entry_type.is_dir() {
+            continue;
+        }
+        if !entry_type.is_file() {
+            return Err(PackError::Malformed(format!(
+                "'{}' is not a regular file",
+                raw_path.display()
+            )));
+        }
+        let Some((top, rest)) = components.split_first().filter(|(_, rest)| !rest.is_empty()) else {
+            return Err(PackError::Malformed(format!(
+                "'{}' is outside the skill directory",
+                raw_path.display()
+            )));
+        };
+        match &root {
+            Some(root) if root != top => {
+                return Err(PackError::Malformed(format!(
+                    "entries under both '{root}/' and '{top}/'"
+                )));
+            }
+            Some(_) => {}
+            None => root = Some(top.clone()),
+        }
+
+        let relative = rest.join("/");
+        let size = entry.size();
+        if size > MAX_PACKED_FILE_SIZE {
+            return Err(PackError::TooLarge(format!(
+                "'{relative}' is {size} bytes, over the {MAX_PACKED_FILE_SIZE}-byte limit per file"
+            )));
+        }
+        total_size += size;
+        if total_size > MAX_PACK_SIZE {
+            return Err(PackError::TooLarge(format!(
+                "files add up to more than {MAX_PACK_SIZE} bytes"
+            )));
+        }
+
+        let executable = entry.header().mode().is_ok_and(|mode| mode & 0o111 != 0);
+        let mut contents = Vec::with_capacity(size as usize);
+        entry.read_to_end(&mut contents)?;
+        if relative == MANIFEST_FILE {
+            manifest_json = Some(contents);
+        } else if files
+            .insert(relative.clone(), PackedFile { contents, executable })
+            .is_some()
+        {
+            return Err(PackError::Malformed(format!("'{relative}' appears twice")));
+        }
+    }
+
+    let manifest_json = manifest_json.ok_or_else(|| PackError::Malformed(format!("no {MANIFEST_FILE}")))?;
+    let manifest: PackManifest = serde_json::from_slice(&manifest_json)?;
+    if manifest.schema_version != PACK_SCHEMA_VERSION {
+        return Err(PackError::UnsupportedSchema(manifest.schema_version));
+    }
+
+    let actual: SkillDigest = files
+        .iter()
+        .map(|(path, file)| (path.clone(), sha256(&file.contents)))
+        .collect();
+    let mut problems = digest_problems(&manifest.files, &actual);
+    problems.extend(mode_problems(&manifest, &files));
+    if root.as_deref() != Some(manifest.skill.name.as_str()) {
+        problems.push(format!(
+            "root directory '{}' is not the skill name '{}'",
+            root.unwrap_or_default(),
+            manifest.skill.name
+        ));
+    }
+    problems.extend(skill_md_problem(&files, &manifest.skill));
+    if !problems.is_empty() {
+        return Err(PackError::Tampered(problems));
+    }
+
+    Ok(SkillPack {
+        manifest,
+        manifest_json,
+        files,
+    })
+}
+
+/// Check an unpacked skill directory against the `MANIFEST.json` inside it. Every entry
+/// counts, hidden ones included: anything the manifest doesn't list, and any symlink or other
+/// non-regular file, is a problem. `MANIFEST.sig` is not part of the digest.
+pub fn verify_unpacked(skill_dir: &Path) -> Result<UnpackedSkill, PackError> {
+    let manifest_path = skill_dir.join(MANIFEST_FILE);
+    let manifest_json = match std::fs::read(&manifest_path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(PackError::Malformed(format!(
+                "no {MANIFEST_FILE} in {}",
+                skill_dir.display()
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let manifest: PackManifest = serde_json::from_slice(&manifest_json)?;
+    if manifest.schema_version != PACK_SCHEMA_VERSION {
+        return Err(PackError::UnsupportedSchema(manifest.schema_version));
+    }
+    let mut files = BTreeMap::new();
+    let mut problems = Vec::new();
+    walk_unpacked(skill_dir, Path::new(""), &mut files, &mut problems)?;
+    let actual: SkillDigest = files
+        .iter()
+        .map(|(path, file)| (path.clone(), sha256(&file.contents)))
+        .collect();
+    problems.extend(digest_problems(&manifest.files, &actual));
+    // Elsewhere there is no execute bit to compare.
+    if cfg!(unix) {
+        problems.extend(mode_problems(&manifest, &files));
+    }
+    problems.extend(skill_md_problem(&files, &manifest.skill));
+    if !problems.is_empty() {
+        return Err(PackError::Tampered(problems));
+    }
+    Ok(UnpackedSkill {
+        manifest,
+        manifest_digest: sha256(&manifest_json),
+    })
+}
+
+/// Why the `SKILL.md` among `files` does not declare exactly the manifest's `skill`, if it doesn't.
+fn skill_md_problem(files: &BTreeMap<String, PackedFile>, skill: &SkillProperties) -> Option<String> {
+    let Some(file) = files.get("SKILL.md").or_else(|| files.get("skill.md")) else {
+        return Some("no SKILL.md".to_string());
+    };
+    match parser::properties_from_content(&String::from_utf8_lossy(&file.contents)) {
+        Ok(parsed) if parsed == *skill => None,
+        Ok(_) => Some("SKILL.md frontmatter does not match the manifest".to_string()),
+        Err(e) => Some(format!("SKILL.md: {e}")),
+    }
+}
+
+/// `sha256:<hex>` per file of the skill at `skill_dir`, as [`pack_skill`] would list them.
+pub fn directory_digest(skill_dir: &Path) -> std::io::Result<SkillDigest> {
+    let mut files = BTreeMap::new();
+    collect_files(skill_dir, Path::new(""), &mut files)?;
+    Ok(files
+        .into_iter()
+        .map(|(path, file)| (path, sha256(&file.contents)))
+        .collect())
+}
+
+fn digest_problems(expected: &SkillDigest, actual: &SkillDigest) -> Vec<String> {
+    detect_tampering(expected, actual)
+        .into_iter()
+        .map(
+            |path| match (expected.contains_key(&path), actual.contains_key(&path)) {
+                (true, true) => format!("'{path}' has a different hash"),
+                (true, false) => format!("'{path}' is missing"),
+                _ => format!("'{path}' is not in the manifest"),
+            },
+        )
+        .collect()
+}
+
+/// Files present in both the manifest and `files` whose execute bit differs from the manifest.
+fn mode_problems(manifest: &PackManifest, files: &BTreeMap<String, PackedFile>) -> Vec<String> {
+    files
+        .iter()
+        .filter(|(path, _)| manifest.files.contains_key(*path))
+        .filter(|(path, file)| file.executable != manifest.executables.contains(*path))
+        .map(|(path, file)| match file.executable {
+            true => format!("'{path}' is executable but the manifest says it isn't"),
+            false => format!("'{path}' is not executable but the manifest says it is"),
+        })
+        .collect()
+}
+
+fn executables(files: &BTreeMap<String, PackedFile>) -> BTreeSet<String> {
+    files
+        .iter()
+        .filter(|(_, file)| file.executable)
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+impl SkillPack {
+    /// `sha256:<hex>` of `MANIFEST.json`, the message a skill signature covers.
+    pub fn manifest_digest(&self) -> String {
+        sha256(&self.manifest_json)
+    }
+
+    /// Write the skill to `dest/<name>`, including `MANIFEST.json`, and return that directory.
+    /// An existing directory is replaced only with `force`.
+    pub fn unpack(&self, dest: &Path, force: bool) -> Result<PathBuf, PackError> {
+        let skill_dir = dest.join(&self.manifest.skill.name);
+        if skill_dir.exists() {
+            if !force {
+                return Err(PackError::AlreadyExists(skill_dir));
+            }
+            std::fs::remove_dir_all(&skill_dir)?;
+        }
+        for (path, file) in &self.files {
+            let target = skill_dir.join(path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, &file.contents)?;
+            if file.executable {
+                set_executable(&target)?;
+            }
+        }
+        std::fs::write(skill_dir.join(MANIFEST_FILE), &self.manifest_json)?;
+        Ok(skill_dir)
+    }
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("sha256:{}", crate::encoding::hex_encode(Sha256::digest(bytes)))
+}
+
+/// Regular files under `dir`, keyed by `/`-separated path relative to the skill root.
+/// `MANIFEST.json` and `MANIFEST.sig` at the root are skipped; the archive gets a fresh
+/// manifest, and signatures travel beside the archive.
+fn collect_files(dir: &Path, relative: &Path, files: &mut BTreeMap<String, PackedFile>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let relative = relative.join(&name);
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &relative, files)?;
+        } else if file_type.is_file() {
+            let key = archive_path(&relative);
+            if key == MANIFEST_FILE || key == SIGNATURE_FILE {
+                continue;
+            }
+            files.insert(
+                key,
+                PackedFile {
+                    contents: std::fs::read(entry.path())?,
+                    executable: is_executable(&entry.path()),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Like [`collect_files`], but for checking an unpacked skill: hidden files are kept so they
+/// show up as unlisted, and symlinks and other non-regular files are reported in `problems`.
+fn walk_unpacked(
+    dir: &Path,
+    relative: &Path,
+    files: &mut BTreeMap<String, PackedFile>,
+    problems: &mut Vec<String>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let relative = relative.join(entry.file_name());
+        let key = archive_path(&relative);
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            walk_unpacked(&entry.path(), &relative, files, problems)?;
+        } else if file_type.is_symlink() {
+            problems.push(format!("'{key}' is a symlink"));
+        } else if !file_type.is_file() {
+            problems.push(format!("'{key}' is not a regular file"));
+        } else if key != MANIFEST_FILE && key != SIGNATURE_FILE {
+            files.insert(
+                key,
+                PackedFile {
+                    contents: std::fs::read(entry.path())?,
+                    executable: is_executable(&entry.path()),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `relative` as a `/`-separated archive path.
+fn archive_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, path: &str, contents: &[u8], executable: bool) -> std::io::Result<()> {
+    let mut header = tar::Header::new_ustar();
+    header.set_size(contents.len() as u64);
+    header.set_mode(if executable { 0o755 } else { 0o644 });
+    header.set_mtime(0);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_entry_type(tar::EntryType::Regular);
+    tar.append_data(&mut header, path, contents)
+}
+
+/// Normal components of an archive path, or `None` when it is absolute or climbs with `..`.
+fn safe_components(path: &Path) -> Option<Vec<String>> {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn skill(root: &Path) -> PathBuf {
+        let dir = root.join("charts");
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::write(
+            dir.join("SKILL.md"),
+            "---\nname: charts\ndescription: Make charts\n---\n# Charts\n",
+        )
+        .unwrap();
+        fs::write(dir.join("scripts/plot.py"), "print()\n").unwrap();
+        fs::write(dir.join(".DS_Store"), "junk").unwrap();
+        dir
+    }
+
+    #[test]
+    fn packs_reproducibly_and_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = skill(tmp.path());
+
+        let mut first = Vec::new();
+        let manifest = pack_skill(&dir, &mut first).unwrap();
+        let mut second = Vec::new();
+        pack_skill(&dir, &mut second).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(
+            manifest.files.keys().collect::<Vec<_>>(),
+            ["SKILL.md", "scripts/plot.py"]
+        );
+        assert_eq!(manifest.trg_version, env!("CARGO_PKG_VERSION"));
+
+        let pack = read_pack(first.as_slice()).unwrap();
+        let out = tmp.path().join("out");
+        let unpacked = pack.unpack(&out, false).unwrap();
+        assert_eq!(unpacked, out.join("charts"));
+        assert_eq!(
+            fs::read_to_string(unpacked.join("scripts/plot.py")).unwrap(),
+            "print()\n"
+        );
+        assert!(unpacked.join(MANIFEST_FILE).is_file());
+        assert!(matches!(pack.unpack(&out, false), Err(PackError::AlreadyExists(_))));
+        pack.unpack(&out, true).unwrap();
+
+        // Repacking the unpacked skill ignores the old manifest and gives the same archive.
+        let mut repacked = Vec::new();
+        pack_skill(&unpacked, &mut repacked).unwrap();
+        assert_eq!(read_pack(repacked.as_slice()).unwrap().manifest.files, manifest.files);
+    }
+
+    #[test]
+    fn rejects_archives_that_differ_from_their_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = skill(tmp.path());
+        let mut archive = Vec::new();
+        let manifest = pack_skill(&dir, &mut archive).unwrap();
+
+        let mut tampered = Vec::new();
+        {
+            let gzip = GzBuilder::new().write(&mut tampered, Compression::default());
+            let mut tar = tar::Builder::new(gzip);
+            let manifest_json = serde_json::to_vec(&manifest).unwrap();
+            append(&mut tar, "charts/MANIFEST.json", &manifest_json, false).unwrap();
+            append(
+                &mut tar,
+                "charts/SKILL.md",
+                b"---\nname: charts\ndescription: Make charts\n---\n# Charts\n",
+                false,
+            )
+            .unwrap();
+            append(&mut tar, "charts/scripts/plot.py", b"import os\n", false).unwrap();
+            append(&mut tar, "charts/extra.sh", b"curl evil\n", true).unwrap();
+            tar.into_inner().unwrap().finish().unwrap();
+        }
+
+        match read_pack(tampered.as_slice()) {
+            Err(PackError::Tampered(problems)) => assert_eq!(
+                problems,
+                [
+                    "'extra.sh' is not in the manifest",
+                    "'scripts/plot.py' has a different hash",
+                ]
+            ),
+            other => panic!("expected Tampered, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn manifest_properties_must_match_skill_md() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = skill(tmp.path());
+        let skill_md =
+            "---\nname: charts\ndescription: Make charts\nallowed-tools: Read\nlicense: MIT\n---\n# Charts\n";
+        fs::write(dir.join("SKILL.md"), skill_md).unwrap();
+        let mut archive = Vec::new();
+        let mut manifest = pack_skill(&dir, &mut archive).unwrap();
+        read_pack(archive.as_slice()).unwrap();
+
+        manifest.skill.allowed_tools = Some(vec!["Read".to_string(), "Bash".to_string()]);
+        let mut tampered = Vec::new();
+        {
+            let gzip = GzBuilder::new().write(&mut tampered, Compression::default());
+            let mut tar = tar::Builder::new(gzip);
+            let manifest_json = serde_json::to_vec(&manifest).unwrap();
+            append(&mut tar, "charts/MANIFEST.json", &manifest_json, false).unwrap();
+            append(&mut tar, "charts/SKILL.md", skill_md.as_bytes(), false).unwrap();
+            append(&mut tar, "charts/scripts/plot.py", b"print()\n", false).unwrap();
+            tar.into_inner().unwrap().finish().unwrap();
+        }
+        match read_pack(tampered.as_slice()) {
+            Err(PackError::Tampered(problems)) => {
+                assert_eq!(problems, ["SKILL.md frontmatter does not match the manifest"])
+            }
+            other => panic!("expected Tampered, got {other:?}"),
+        }
+
+        let unpacked = read_pack(archive.as_slice())
+            .unwrap()
+            .unpack(&tmp.path().join("out"), false)
+            .unwrap();
+        verify_unpacked(&unpacked).unwrap();
+        manifest.skill.allowed_tools = Some(vec!["Read".to_string()]);
+        manifest.skill.license = None;
+        fs::write(unpacked.join(MANIFEST_FILE), serde_json::to_vec(&manifest).unwrap()).unwrap();
+        assert!(matches!(verify_unpacked(&unpacked), Err(PackError::Tampered(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpacked_skills_reject_hidden_files_and_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut archive = Vec::new();
+        pack_skill(&skill(tmp.path()), &mut archive).unwrap();
+        let unpacked = read_pack(archive.as_slice())
+            .unwrap()
+            .unpack(&tmp.path().join("out"), false)
+            .unwrap();
+        fs::write(unpacked.join(SIGNATURE_FILE), "sig").unwrap();
+        verify_unpacked(&unpacked).unwrap();
+
+        fs::create_dir(unpacked.join(".hidden")).unwrap();
+        fs::write(unpacked.join(".hidden/run.sh"), "curl evil\n").unwrap();
+        std::os::unix::fs::symlink("/etc/passwd", unpacked.join("extra.txt")).unwrap();
+        match verify_unpacked(&unpacked) {
+            Err(PackError::Tampered(mut problems)) => {
+                problems.sort();
+                assert_eq!(
+                    problems,
+                    ["'.hidden/run.sh' is not in the manifest", "'extra.txt' is a symlink"]
+                );
+            }
+            other => panic!("expected Tampered, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn manifest_pins_the_execute_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = skill(tmp.path());
+        fs::set_permissions(dir.join("scripts/plot.py"), fs::Permissions::from_mode(0o755)).unwrap();
+        let mut archive = Vec::new();
+        let manifest = pack_skill(&dir, &mut archive).unwrap();
+        assert_eq!(manifest.executables.iter().collect::<Vec<_>>(), ["scripts/plot.py"]);
+
+        let mut flipped = Vec::new();
+        {
+            let gzip = GzBuilder::new().write(&mut flipped, Compression::default());
+            let mut tar = tar::Builder::new(gzip);
+            let manifest_json = serde_json::to_vec(&manifest).unwrap();
+            append(&mut tar, "charts/MANIFEST.json", &manifest_json, false).unwrap();
+            let skill_md = fs::read(dir.join("SKILL.md")).unwrap();
+            append(&mut tar, "charts/SKILL.md", &skill_md, true).unwrap();
+            append(&mut tar, "charts/scripts/plot.py", b"print()\n", true).unwrap();
+            tar.into_inner().unwrap().finish().unwrap();
+        }
+        match read_pack(flipped.as_slice()) {
+            Err(PackError::Tampered(problems)) => {
+                assert_eq!(problems, ["'SKILL.md' is executable but the manifest says it isn't"])
+            }
+            other => panic!("expected Tampered, got {other:?}"),
+        }
+
+        let unpacked = read_pack(archive.as_slice())
+            .unwrap()
+            .unpack(&tmp.path().join("out"), false)
+            .unwrap();
+        verify_unpacked(&unpacked).unwrap();
+        let script = unpacked.join("scripts/plot.py");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(matches!(verify_unpacked(&unpacked), Err(PackError::Tampered(_))));
+    }
+
+    #[test]
+    fn refuses_oversized_entries_before_reading_them() {
+        let mut archive = Vec::new();
+        {
+            let gzip = GzBuilder::new().write(&mut archive, Compression::default());
+            let mut tar = tar::Builder::new(gzip);
+            let big = vec![0; MAX_PACKED_FILE_SIZE as usize + 1];
+            append(&mut tar, "charts/big.bin", &big, false).unwrap();
+            tar.into_inner().unwrap().finish().unwrap();
+        }
+        match read_pack(archive.as_slice()) {
+            Err(PackError::TooLarge(message)) => assert!(message.starts_with("'big.bin' is 16777217 bytes")),
+            other => panic!("expected TooLarge, got {other:?}"),
+        }
+    }
+}
@@ -46,13 +46,41 @@ fn ensure_required_frontmatter_field(value: &str, field: &'static str) -> Result
     }
 }
 
+/// Top-level frontmatter keys of a `SKILL.md` with their 1-based line numbers, for pointing
+/// diagnostics at the offending line. Empty when the file has no `---` frontmatter block.
+pub fn frontmatter_key_lines(content: &str) -> Vec<(String, u32)> {
+    let mut lines = content.lines().enumerate();
+    if !matches!(lines.next(), Some((_, first)) if first.trim_end() == "---") {
+        return Vec::new();
+    }
+    let mut keys = Vec::new();
+    for (index, line) in lines {
+        if line.trim_end() == "---" {
+            break;
+        }
+        if line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        if let Some((key, _)) = line.split_once(':') {
+            keys.push((key.trim().to_string(), index as u32 + 1));
+        }
+    }
+    keys
+}
+
 /// Frontmatter fields embedded in eval runner prompts (full body lives in the symlinked skill).
 pub fn skill_summary_from_content(content: &str) -> Result<(String, String)> {
+    let props = properties_from_content(content)?;
+    Ok((props.name, props.description))
+}
+
+/// The frontmatter of a `SKILL.md` already read into memory.
+pub fn properties_from_content(content: &str) -> Result<SkillProperties> {
     let data = parse_frontmatter(content)?;
     let props: SkillProperties = data.deserialize()?;
     ensure_required_frontmatter_field(&props.name, "name")?;
     ensure_required_frontmatter_field(&props.description, "description")?;
-    Ok((props.name, props.description))
+    Ok(props)
 }
 
 #[cfg(test)]
@@ -237,4 +265,18 @@ mod tests {
         assert_eq!(props1.name, "skill1");
         assert_eq!(props2.name, "skill2");
     }
+
+    #[test]
+    fn test_frontmatter_key_lines() {
+        let content = "---\nname: a\nmetadata:\n  author: x\n# note\ndescription: b\n---\nbody: not a key\n";
+        assert_eq!(
+            frontmatter_key_lines(content),
+            [
+                ("name".to_string(), 2),
+                ("metadata".to_string(), 3),
+                ("description".to_string(), 6)
+            ]
+        );
+        assert!(frontmatter_key_lines("no frontmatter: here").is_empty());
+    }
 }
@@ -94,6 +94,7 @@ fn html_escape(s: &str) -> String {
         .replace('\'', "&#39;")
 }
 
+#[derive(Debug, Clone)]
 pub struct SkillWithLocation {
     pub properties: SkillProperties,
     pub location: Option<String>,
@@ -114,26 +115,30 @@ pub fn to_prompt(skills: &[SkillProperties]) -> String {
 
 pub fn to_prompt_with_location(skills: &[SkillWithLocation]) -> String {
     let mut lines = vec!["<available_skills>".to_string()];
+    lines.extend(skills.iter().map(skill_entry));
+    lines.push("</available_skills>".to_string());
+    lines.join("\n")
+}
 
-    for skill in skills {
-        lines.push("<skill>".to_string());
-        lines.push("<name>".to_string());
-        lines.push(html_escape(&skill.properties.name));
-        lines.push("</name>".to_string());
-        lines.push("<description>".to_string());
-        lines.push(html_escape(&skill.properties.description));
-        lines.push("</description>".to_string());
-
-        if let Some(ref location) = skill.location {
-            lines.push("<location>".to_string());
-            lines.push(html_escape(location));
-            lines.push("</location>".to_string());
-        }
-
-        lines.push("</skill>".to_string());
+/// The `<skill>` element `to_prompt_with_location` renders for one skill.
+pub fn skill_entry(skill: &SkillWithLocation) -> String {
+    let mut lines = vec![
+        "<skill>".to_string(),
+        "<name>".to_string(),
+        html_escape(&skill.properties.name),
+        "</name>".to_string(),
+        "<description>".to_string(),
+        html_escape(&skill.properties.description),
+        "</description>".to_string(),
+    ];
+
+    if let Some(ref location) = skill.location {
+        lines.push("<location>".to_string());
+        lines.push(html_escape(location));
+        lines.push("</location>".to_string());
     }
 
-    lines.push("</available_skills>".to_string());
+    lines.push("</skill>".to_string());
     lines.join("\n")
 }
 
@@ -30,11 +30,23 @@ impl RedactedCommandLine {
 }
 
 pub fn redact_secrets(text: &str) -> String {
+    redact_secrets_counting(text, &[]).0
+}
+
+/// [`redact_secrets`] followed by caller-supplied `extra` patterns; also returns how many
+/// matches were replaced.
+pub fn redact_secrets_counting(text: &str, extra: &[Regex]) -> (String, usize) {
+    let builtin = [&*BEARER_RE, &*AWS_ACCESS_KEY_RE, &*GITHUB_TOKEN_RE, &*JWT_RE];
     let mut out = text.to_string();
-    for pattern in [&*BEARER_RE, &*AWS_ACCESS_KEY_RE, &*GITHUB_TOKEN_RE, &*JWT_RE] {
-        out = pattern.replace_all(&out, REDACTED).into_owned();
+    let mut count = 0;
+    for pattern in builtin.into_iter().chain(extra) {
+        let matches = pattern.find_iter(&out).count();
+        if matches > 0 {
+            count += matches;
+            out = pattern.replace_all(&out, REDACTED).into_owned();
+        }
     }
-    out
+    (out, count)
 }
 
 pub fn redact_transcript_bytes(raw: &[u8]) -> RedactedTranscript {
@@ -147,6 +159,19 @@ mod tests {
         assert!(out.contains(REDACTED));
     }
 
+    #[test]
+    fn redact_secrets_counting_applies_extra_patterns_after_builtins() {
+        let extra = [Regex::new(r"sk_live_[0-9a-z]{8}").unwrap()];
+        let input = "stripe sk_live_abcd1234 and Bearer abcdefghijklmnop, sk_live_zzzz9999";
+        let (out, count) = redact_secrets_counting(input, &extra);
+        assert_eq!(out, "stripe <redacted> and <redacted>, <redacted>");
+        assert_eq!(count, 3);
+        assert_eq!(
+            redact_secrets_counting("nothing here", &extra),
+            ("nothing here".to_string(), 0)
+        );
+    }
+
     #[test]
     fn redact_secrets_leaves_benign_text() {
         let input = "The report includes a summary of sales data.";
@@ -0,0 +1,197 @@
+//! Minimal SARIF 2.1.0 output, enough for GitHub code scanning, GitLab and IDE viewers, and the
+//! same findings as GitHub Actions workflow commands.
+//!
+//! Skill validation (`validator::report_findings`) and eval CI checks (`ci::violation_findings`)
+//! both produce [`Finding`]s, so every consumer sees one set of rule ids and locations.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A check that findings refer to by `id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub id: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+/// One result: a rule violated at a file (and optionally a line).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule_id: String,
+    pub level: Level,
+    pub message: String,
+    /// Relative paths are resolved against the scanned checkout (`%SRCROOT%`).
+    pub path: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// A SARIF log with a single run of `trg`.
+pub fn to_sarif(rules: &[Rule], findings: &[Finding]) -> Value {
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|rule| json!({ "id": rule.id, "shortDescription": { "text": rule.description } }))
+        .collect();
+    let results: Vec<Value> = findings.iter().map(result).collect();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "trg",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// A GitHub Actions workflow command (`::error file=…,line=…::message`) for a finding.
+pub fn github_annotation(finding: &Finding) -> String {
+    let command = match finding.level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note => "notice",
+    };
+    let mut properties = Vec::new();
+    if let Some(path) = &finding.path {
+        properties.push(format!("file={}", escape_github_property(&relative_path(path))));
+        if let Some(line) = finding.line {
+            properties.push(format!("line={line}"));
+        }
+    }
+    let properties = if properties.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", properties.join(","))
+    };
+    format!("::{command}{properties}::{}", escape_github_message(&finding.message))
+}
+
+/// Print [`github_annotation`]s, only when running under GitHub Actions.
+pub fn emit_github_annotations(findings: &[Finding]) {
+    if std::env::var("GITHUB_ACTIONS").ok().as_deref() != Some("true") {
+        return;
+    }
+    for finding in findings {
+        println!("{}", github_annotation(finding));
+    }
+}
+
+fn result(finding: &Finding) -> Value {
+    let mut result = json!({
+        "ruleId": finding.rule_id,
+        "level": finding.level,
+        "message": { "text": finding.message },
+    });
+    if let Some(path) = &finding.path {
+        let path = relative_path(path);
+        let artifact = if path.starts_with('/') {
+            json!({ "uri": format!("file://{path}") })
+        } else {
+            json!({ "uri": path, "uriBaseId": "%SRCROOT%" })
+        };
+        let mut physical = json!({ "artifactLocation": artifact });
+        if let Some(line) = finding.line {
+            physical["region"] = json!({ "startLine": line });
+        }
+        result["locations"] = json!([{ "physicalLocation": physical }]);
+    }
+    result
+}
+
+/// Forward slashes and no leading `./`, the form both SARIF viewers and GitHub match against.
+fn relative_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut relative = path.as_str();
+    while let Some(rest) = relative.strip_prefix("./") {
+        relative = rest;
+    }
+    relative.to_string()
+}
+
+fn escape_github_message(message: &str) -> String {
+    message.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_github_property(value: &str) -> String {
+    escape_github_message(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emits_rules_results_and_locations() {
+        let rules = [Rule {
+            id: "skill/name".into(),
+            description: "Skill name".into(),
+        }];
+        let findings = [
+            Finding {
+                rule_id: "skill/name".into(),
+                level: Level::Error,
+                message: "name: must be lowercase".into(),
+                path: Some("./skills/a/SKILL.md".into()),
+                line: Some(2),
+            },
+            Finding {
+                rule_id: "skill/name".into(),
+                level: Level::Warning,
+                message: "elsewhere".into(),
+                path: Some("/abs/SKILL.md".into()),
+                line: None,
+            },
+        ];
+        let log = to_sarif(&rules, &findings);
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "skill/name");
+        let first = &run["results"][0];
+        assert_eq!(first["level"], "error");
+        let location = &first["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "skills/a/SKILL.md");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(location["region"]["startLine"], 2);
+        let second = &run["results"][1]["locations"][0]["physicalLocation"];
+        assert_eq!(second["artifactLocation"]["uri"], "file:///abs/SKILL.md");
+        assert!(second.get("region").is_none());
+    }
+
+    #[test]
+    fn github_annotations_map_levels_and_locations() {
+        let finding = |level, path: Option<&str>, line| Finding {
+            rule_id: "eval/failed_assertion".into(),
+            level,
+            message: "50% pass\nrate".into(),
+            path: path.map(str::to_string),
+            line,
+        };
+        assert_eq!(
+            github_annotation(&finding(Level::Error, Some("./evals/evals.json"), Some(7))),
+            "::error file=evals/evals.json,line=7::50%25 pass%0Arate"
+        );
+        assert_eq!(
+            github_annotation(&finding(Level::Warning, Some("a,b.md"), None)),
+            "::warning file=a%2Cb.md::50%25 pass%0Arate"
+        );
+        assert_eq!(
+            github_annotation(&finding(Level::Note, None, Some(3))),
+            "::notice::50%25 pass%0Arate"
+        );
+    }
+}
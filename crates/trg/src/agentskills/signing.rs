@@ -0,0 +1,390 @@
+//! Ed25519 signatures over packed skills.
+//!
+//! A signature covers the SHA-256 of the archive's `MANIFEST.json`, which in turn lists the
+//! SHA-256 of every file, so one signature pins the whole skill. It is stored detached: as
+//! `<archive>.sig` next to an archive, and as `MANIFEST.sig` inside an unpacked skill.
+//!
+//! Keys are one-line text files: `ed25519:<hex>` for a public key (`.pub`) and
+//! `ed25519-secret:<hex>` for a secret key (`.key`, written `0600`). A directory of `.pub`
+//! files is a set of trusted keys.
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::pack::{read_pack, verify_unpacked, PackError, SkillPack, MANIFEST_FILE, SIGNATURE_FILE};
+use crate::encoding::{hex_decode, hex_encode};
+
+pub const SIGNATURE_SCHEMA_VERSION: &str = "trg.skills.signature.v1";
+pub const ALGORITHM: &str = "ed25519";
+const PUBLIC_KEY_PREFIX: &str = "ed25519:";
+const SECRET_KEY_PREFIX: &str = "ed25519-secret:";
+
+#[derive(Error, Debug)]
+pub enum SigningError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Pack(#[from] PackError),
+
+    #[error("invalid signature file: {0}")]
+    SignatureFile(#[from] serde_json::Error),
+
+    #[error("invalid key file {}: {reason}", path.display())]
+    Key { path: PathBuf, reason: String },
+
+    #[error("no trusted keys (*.pub) in {}", .0.display())]
+    NoTrustedKeys(PathBuf),
+
+    #[error("not signed: {} not found", .0.display())]
+    Unsigned(PathBuf),
+
+    #[error("signed by untrusted key {0}")]
+    UntrustedKey(String),
+
+    #[error("signature is for a different {MANIFEST_FILE}")]
+    ManifestMismatch,
+
+    #[error("signature does not verify")]
+    BadSignature,
+
+    #[error("unsupported signature: {0}")]
+    Unsupported(String),
+}
+
+/// Detached signature, stored as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillSignature {
+    pub schema_version: String,
+    pub algorithm: String,
+    /// [`key_id`] of the signing key.
+    pub key_id: String,
+    /// `sha256:<hex>` of the signed `MANIFEST.json`.
+    pub manifest_sha256: String,
+    /// Hex-encoded Ed25519 signature.
+    pub signature: String,
+}
+
+/// A skill whose signature checked out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verified {
+    pub skill: String,
+    pub key_id: String,
+    /// File stem of the trusted `.pub` file that matched.
+    pub key_name: String,
+}
+
+/// First 16 hex digits of the SHA-256 of the public key.
+pub fn key_id(key: &VerifyingKey) -> String {
+    hex_encode(Sha256::digest(key.as_bytes()))[..16].to_string()
+}
+
+pub fn generate_key() -> SigningKey {
+    SigningKey::generate(&mut OsRng)
+}
+
+/// Write `<prefix>.key` (mode `0600`) and `<prefix>.pub`; refuses to overwrite either.
+pub fn write_key_pair(key: &SigningKey, prefix: &Path) -> Result<(PathBuf, PathBuf), SigningError> {
+    let secret_path = with_suffix(prefix, ".key");
+    let public_path = with_suffix(prefix, ".pub");
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut secret = options.open(&secret_path)?;
+    writeln!(secret, "{SECRET_KEY_PREFIX}{}", hex_encode(key.to_bytes()))?;
+    let mut public = OpenOptions::new().write(true).create_new(true).open(&public_path)?;
+    writeln!(
+        public,
+        "{PUBLIC_KEY_PREFIX}{}",
+        hex_encode(key.verifying_key().as_bytes())
+    )?;
+    Ok((secret_path, public_path))
+}
+
+pub fn read_signing_key(path: &Path) -> Result<SigningKey, SigningError> {
+    let bytes = read_key_bytes(path, SECRET_KEY_PREFIX)?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+pub fn read_public_key(path: &Path) -> Result<VerifyingKey, SigningError> {
+    let bytes = read_key_bytes(path, PUBLIC_KEY_PREFIX)?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| SigningError::Key {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })
+}
+
+fn read_key_bytes(path: &Path, prefix: &str) -> Result<[u8; 32], SigningError> {
+    let content = std::fs::read_to_string(path)?;
+    let invalid = |reason: String| SigningError::Key {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or_default();
+    let hex = line
+        .strip_prefix(prefix)
+        .ok_or_else(|| invalid(format!("expected a line starting with '{prefix}'")))?;
+    hex_decode(hex)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| invalid("expected 64 hex digits".to_string()))
+}
+
+/// The `.pub` files of a directory, by [`key_id`].
+#[derive(Debug, Clone)]
+pub struct TrustedKeys {
+    keys: BTreeMap<String, (String, VerifyingKey)>,
+}
+
+impl TrustedKeys {
+    pub fn load(dir: &Path) -> Result<Self, SigningError> {
+        let mut keys = BTreeMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "pub") {
+                let key = read_public_key(&path)?;
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                keys.insert(key_id(&key), (name, key));
+            }
+        }
+        if keys.is_empty() {
+            return Err(SigningError::NoTrustedKeys(dir.to_path_buf()));
+        }
+        Ok(Self { keys })
+    }
+
+    /// Check that `signature` covers `manifest_sha256` and was made by a trusted key; returns
+    /// the key's id and name.
+    fn verify(&self, signature: &SkillSignature, manifest_sha256: &str) -> Result<(String, String), SigningError> {
+        if signature.schema_version != SIGNATURE_SCHEMA_VERSION || signature.algorithm != ALGORITHM {
+            return Err(SigningError::Unsupported(format!(
+                "{} {}",
+                signature.schema_version, signature.algorithm
+            )));
+        }
+        if signature.manifest_sha256 != manifest_sha256 {
+            return Err(SigningError::ManifestMismatch);
+        }
+        let (name, key) = self
+            .keys
+            .get(&signature.key_id)
+            .ok_or_else(|| SigningError::UntrustedKey(signature.key_id.clone()))?;
+        let bytes = hex_decode(&signature.signature)
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .ok_or(SigningError::BadSignature)?;
+        key.verify_strict(&signed_message(manifest_sha256), &Signature::from_bytes(&bytes))
+            .map_err(|_| SigningError::BadSignature)?;
+        Ok((signature.key_id.clone(), name.clone()))
+    }
+}
+
+/// Domain-separated, so a skill signature can't be replayed as a signature over anything else.
+fn signed_message(manifest_sha256: &str) -> Vec<u8> {
+    format!("{SIGNATURE_SCHEMA_VERSION}\n{manifest_sha256}").into_bytes()
+}
+
+pub fn sign_manifest(manifest_sha256: &str, key: &SigningKey) -> SkillSignature {
+    SkillSignature {
+        schema_version: SIGNATURE_SCHEMA_VERSION.to_string(),
+        algorithm: ALGORITHM.to_string(),
+        key_id: key_id(&key.verifying_key()),
+        manifest_sha256: manifest_sha256.to_string(),
+        signature: hex_encode(key.sign(&signed_message(manifest_sha256)).to_bytes()),
+    }
+}
+
+/// Where the detached signature of `archive` lives: `<archive>.sig`.
+pub fn signature_path(archive: &Path) -> PathBuf {
+    with_suffix(archive, ".sig")
+}
+
+/// Verify `archive` against its manifest, then sign the manifest.
+pub fn sign_archive(archive: &Path, key: &SigningKey) -> Result<SkillSignature, SigningError> {
+    let pack = read_pack(std::fs::File::open(archive)?)?;
+    Ok(sign_manifest(&pack.manifest_digest(), key))
+}
+
+pub fn write_signature(path: &Path, signature: &SkillSignature) -> Result<(), SigningError> {
+    let mut json = serde_json::to_vec_pretty(signature)?;
+    json.push(b'\n');
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+pub fn read_signature(path: &Path) -> Result<SkillSignature, SigningError> {
+    Ok(serde_json::from_slice(&read_signature_bytes(path)?)?)
+}
+
+/// The signature file at `path` as stored; [`SigningError::Unsigned`] when there is none.
+pub fn read_signature_bytes(path: &Path) -> Result<Vec<u8>, SigningError> {
+    match std::fs::read(path) {
+        Ok(json) => Ok(json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(SigningError::Unsigned(path.to_path_buf())),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Check an archive's integrity and its detached signature.
+pub fn verify_archive(archive: &Path, signature: &Path, trusted: &TrustedKeys) -> Result<Verified, SigningError> {
+    let signature = read_signature_bytes(signature)?;
+    let pack = read_pack(std::fs::File::open(archive)?)?;
+    verify_pack(&pack, &signature, trusted)
+}
+
+/// Check a pack already read into memory against `signature`, the bytes of its detached
+/// signature file, so what was verified is exactly what gets unpacked.
+pub fn verify_pack(pack: &SkillPack, signature: &[u8], trusted: &TrustedKeys) -> Result<Verified, SigningError> {
+    let signature: SkillSignature = serde_json::from_slice(signature)?;
+    let (key_id, key_name) = trusted.verify(&signature, &pack.manifest_digest())?;
+    Ok(Verified {
+        skill: pack.manifest.skill.name.clone(),
+        key_id,
+        key_name,
+    })
+}
+
+/// Check an unpacked skill: its files match `MANIFEST.json`, and `MANIFEST.sig` is a trusted
+/// signature over that manifest. A skill that was never packed is unsigned.
+pub fn verify_skill_dir(skill_dir: &Path, trusted: &TrustedKeys) -> Result<Verified, SigningError> {
+    let manifest = skill_dir.join(MANIFEST_FILE);
+    if !manifest.is_file() {
+        return Err(SigningError::Unsigned(manifest));
+    }
+    let signature = read_signature(&skill_dir.join(SIGNATURE_FILE))?;
+    let unpacked = verify_unpacked(skill_dir)?;
+    let (key_id, key_name) = trusted.verify(&signature, &unpacked.manifest_digest)?;
+    Ok(Verified {
+        skill: unpacked.manifest.skill.name,
+        key_id,
+        key_name,
+    })
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentskills::pack::pack_skill;
+    use std::fs;
+
+    fn packed_skill(root: &Path) -> PathBuf {
+        let dir = root.join("charts");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("SKILL.md"),
+            "---\nname: charts\ndescription: Make charts\n---\n# Charts\n",
+        )
+        .unwrap();
+        let archive = root.join("charts.tgz");
+        pack_skill(&dir, fs::File::create(&archive).unwrap()).unwrap();
+        archive
+    }
+
+    fn trust(root: &Path, name: &str, key: &SigningKey) -> PathBuf {
+        let dir = root.join("trusted");
+        fs::create_dir_all(&dir).unwrap();
+        let (_, public) = write_key_pair(key, &root.join(name)).unwrap();
+        fs::copy(&public, dir.join(format!("{name}.pub"))).unwrap();
+        dir
+    }
+
+    #[test]
+    fn signs_and_verifies_archives_and_unpacked_skills() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let archive = packed_skill(root);
+        let key = generate_key();
+        let trusted_dir = trust(root, "release", &key);
+        assert_eq!(
+            read_signing_key(&root.join("release.key")).unwrap().to_bytes(),
+            key.to_bytes()
+        );
+
+        let signature = sign_archive(&archive, &key).unwrap();
+        write_signature(&signature_path(&archive), &signature).unwrap();
+        let trusted = TrustedKeys::load(&trusted_dir).unwrap();
+        let verified = verify_archive(&archive, &signature_path(&archive), &trusted).unwrap();
+        assert_eq!(verified.skill, "charts");
+        assert_eq!(verified.key_name, "release");
+        assert_eq!(verified.key_id, key_id(&key.verifying_key()));
+
+        let pack = read_pack(fs::File::open(&archive).unwrap()).unwrap();
+        let signature_bytes = read_signature_bytes(&signature_path(&archive)).unwrap();
+        assert_eq!(verify_pack(&pack, &signature_bytes, &trusted).unwrap(), verified);
+
+        let out = root.join("out");
+        let skill_dir = pack.unpack(&out, false).unwrap();
+        assert!(matches!(
+            verify_skill_dir(&skill_dir, &trusted),
+            Err(SigningError::Unsigned(_))
+        ));
+        fs::copy(signature_path(&archive), skill_dir.join(SIGNATURE_FILE)).unwrap();
+        assert_eq!(verify_skill_dir(&skill_dir, &trusted).unwrap(), verified);
+
+        fs::write(skill_dir.join("notes.md"), "added later").unwrap();
+        assert!(matches!(
+            verify_skill_dir(&skill_dir, &trusted),
+            Err(SigningError::Pack(PackError::Tampered(_)))
+        ));
+    }
+
+    #[test]
+    fn rejects_untrusted_keys_and_forged_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let archive = packed_skill(root);
+        let trusted = TrustedKeys::load(&trust(root, "release", &generate_key())).unwrap();
+
+        let stranger = generate_key();
+        let signature = sign_archive(&archive, &stranger).unwrap();
+        write_signature(&signature_path(&archive), &signature).unwrap();
+        assert!(matches!(
+            verify_archive(&archive, &signature_path(&archive), &trusted),
+            Err(SigningError::UntrustedKey(id)) if id == key_id(&stranger.verifying_key())
+        ));
+
+        let trusted_id = trusted.keys.keys().next().unwrap().clone();
+        let forged = SkillSignature {
+            key_id: trusted_id,
+            ..signature.clone()
+        };
+        write_signature(&signature_path(&archive), &forged).unwrap();
+        assert!(matches!(
+            verify_archive(&archive, &signature_path(&archive), &trusted),
+            Err(SigningError::BadSignature)
+        ));
+
+        let other_manifest = SkillSignature {
+            manifest_sha256: format!("sha256:{}", "0".repeat(64)),
+            ..signature
+        };
+        write_signature(&signature_path(&archive), &other_manifest).unwrap();
+        assert!(matches!(
+            verify_archive(&archive, &signature_path(&archive), &trusted),
+            Err(SigningError::ManifestMismatch)
+        ));
+    }
+}
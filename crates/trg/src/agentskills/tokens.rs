@@ -0,0 +1,339 @@
+//! Token counts for the parts of a skill an agent loads: the `<available_skills>` entry
+//! (`to-prompt` output, always in context), the `SKILL.md` body (loaded when the skill
+//! activates) and the files it references (loaded on demand).
+//!
+//! Counts come from a [`Tokenizer`]. The default, [`ApproxBpe`], estimates what a BPE
+//! tokenizer would produce without shipping a vocabulary; it is meant for comparing skills
+//! and catching growth, not for exact accounting against a particular model.
+//!
+//! Budgets are set in the `[budgets]` table of `.trg-lint.toml`, next to the lint rules:
+//!
+//! ```toml
+//! [budgets]
+//! description = 200
+//! body = 4000
+//! file = 2000
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::errors::Result;
+use super::lint::{body_lines, referenced_files};
+use super::parser;
+use super::prompt::{skill_entry, SkillWithLocation};
+use crate::fs::FileSystem;
+
+/// Default budget for a description, in tokens. The 1024-character limit is about 256 tokens
+/// of English; text that tokenizes worse than that crowds out other skills in the catalog.
+pub const DEFAULT_DESCRIPTION_BUDGET: usize = 256;
+/// Default budget for the `SKILL.md` body, in tokens.
+pub const DEFAULT_BODY_BUDGET: usize = 5000;
+
+/// Pre-tokenization in the style of GPT-family BPE: a word with its leading space, a run of up
+/// to three digits, a run of punctuation, or a run of whitespace.
+static PIECE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"'(?:s|t|re|ve|m|ll|d)| ?\p{L}+| ?\p{N}{1,3}| ?[^\s\p{L}\p{N}]+|\s+").unwrap());
+
+pub trait Tokenizer {
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Splits text the way BPE pre-tokenizers do and prices each piece: common-length ASCII words
+/// are one token, longer ones one more per six letters, every non-ASCII letter (CJK, emoji)
+/// one token, punctuation one per two characters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproxBpe;
+
+impl Tokenizer for ApproxBpe {
+    fn count(&self, text: &str) -> usize {
+        PIECE_RE.find_iter(text).map(|piece| piece_tokens(piece.as_str())).sum()
+    }
+}
+
+fn piece_tokens(piece: &str) -> usize {
+    let body = piece.strip_prefix(' ').filter(|rest| !rest.is_empty()).unwrap_or(piece);
+    let first = body.chars().next().unwrap_or(' ');
+    if first.is_whitespace() {
+        1
+    } else if first.is_alphabetic() {
+        let ascii = body.chars().filter(char::is_ascii).count();
+        let other = body.chars().count() - ascii;
+        let ascii_tokens = if ascii == 0 { 0 } else { 1 + (ascii - 1) / 6 };
+        ascii_tokens + other
+    } else if first.is_numeric() {
+        1
+    } else {
+        body.chars().count().div_ceil(2)
+    }
+}
+
+/// One token per `N` characters, the usual back-of-the-envelope rule.
+#[derive(Debug, Clone, Copy)]
+pub struct CharsPerToken(pub usize);
+
+impl Tokenizer for CharsPerToken {
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(self.0.max(1))
+    }
+}
+
+/// The tokenizers selectable from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenizerKind {
+    /// BPE-style estimate (see [`ApproxBpe`])
+    #[default]
+    ApproxBpe,
+    /// One token per four characters
+    Chars,
+}
+
+impl Tokenizer for TokenizerKind {
+    fn count(&self, text: &str) -> usize {
+        match self {
+            Self::ApproxBpe => ApproxBpe.count(text),
+            Self::Chars => CharsPerToken(4).count(text),
+        }
+    }
+}
+
+/// Token limits per part of a skill. `None` uses the default (none for `frontmatter`, `file`
+/// and `total`); `0` turns a limit off.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenBudgets {
+    /// The `description` frontmatter field alone.
+    pub description: Option<usize>,
+    /// The skill's whole `<available_skills>` entry.
+    pub frontmatter: Option<usize>,
+    /// The `SKILL.md` body.
+    pub body: Option<usize>,
+    /// Each referenced file.
+    pub file: Option<usize>,
+    /// Entry, body and referenced files together.
+    pub total: Option<usize>,
+}
+
+impl TokenBudgets {
+    pub fn description(&self) -> Option<usize> {
+        limit(self.description, Some(DEFAULT_DESCRIPTION_BUDGET))
+    }
+
+    pub fn frontmatter(&self) -> Option<usize> {
+        limit(self.frontmatter, None)
+    }
+
+    pub fn body(&self) -> Option<usize> {
+        limit(self.body, Some(DEFAULT_BODY_BUDGET))
+    }
+
+    pub fn file(&self) -> Option<usize> {
+        limit(self.file, None)
+    }
+
+    pub fn total(&self) -> Option<usize> {
+        limit(self.total, None)
+    }
+}
+
+fn limit(configured: Option<usize>, default: Option<usize>) -> Option<usize> {
+    match configured {
+        Some(0) => None,
+        Some(budget) => Some(budget),
+        None => default,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileTokens {
+    /// Relative to the skill directory.
+    pub path: PathBuf,
+    /// `None` for files that aren't UTF-8 text (images, archives), which agents don't read
+    /// as tokens.
+    pub tokens: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkillTokens {
+    pub path: PathBuf,
+    pub name: String,
+    /// The skill's `<available_skills>` entry, as `to-prompt` renders it.
+    pub frontmatter: usize,
+    pub description: usize,
+    pub body: usize,
+    pub files: Vec<FileTokens>,
+    pub total: usize,
+}
+
+/// A part of a skill over its budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverBudget {
+    /// `description`, `frontmatter`, `body`, `total`, or the referenced file's path.
+    pub part: String,
+    pub tokens: usize,
+    pub budget: usize,
+}
+
+impl fmt::Display for OverBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {} tokens, over the {}-token budget",
+            self.part, self.tokens, self.budget
+        )
+    }
+}
+
+impl SkillTokens {
+    pub fn over_budget(&self, budgets: &TokenBudgets) -> Vec<OverBudget> {
+        let mut over = Vec::new();
+        let mut check = |part: String, tokens: usize, budget: Option<usize>| {
+            if let Some(budget) = budget.filter(|budget| tokens > *budget) {
+                over.push(OverBudget { part, tokens, budget });
+            }
+        };
+        check("description".to_string(), self.description, budgets.description());
+        check("frontmatter".to_string(), self.frontmatter, budgets.frontmatter());
+        check("body".to_string(), self.body, budgets.body());
+        for file in &self.files {
+            if let Some(tokens) = file.tokens {
+                check(file.path.display().to_string(), tokens, budgets.file());
+            }
+        }
+        check("total".to_string(), self.total, budgets.total());
+        over
+    }
+}
+
+/// Count the tokens of the skill at `skill_path`. Referenced files are those `SKILL.md`
+/// reaches through links and resource paths, directly or through referenced Markdown.
+pub fn skill_tokens(fs: &impl FileSystem, skill_path: &Path, tokenizer: &impl Tokenizer) -> Result<SkillTokens> {
+    let (properties, _) = parser::read_properties(fs, skill_path)?;
+    let skill_md = parser::find_skill_md(fs, skill_path)?;
+    let content = fs.read_to_string(&skill_md)?;
+
+    let description = tokenizer.count(&properties.description);
+    let name = properties.name.clone();
+    let frontmatter = tokenizer.count(&skill_entry(&SkillWithLocation {
+        properties,
+        location: Some(skill_md.to_string_lossy().to_string()),
+    }));
+    let body = tokenizer.count(
+        &body_lines(&content)
+            .map(|(_, line)| line)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+
+    let files: Vec<FileTokens> = referenced_files(skill_path, &content)
+        .into_iter()
+        .map(|path| {
+            let tokens = fs
+                .read_bytes(&skill_path.join(&path))
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .map(|text| tokenizer.count(&text));
+            FileTokens { path, tokens }
+        })
+        .collect();
+
+    let total = frontmatter + body + files.iter().filter_map(|file| file.tokens).sum::<usize>();
+    Ok(SkillTokens {
+        path: skill_path.to_path_buf(),
+        name,
+        frontmatter,
+        description,
+        body,
+        files,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn approx_bpe_prices_words_numbers_and_symbols() {
+        let tokenizer = ApproxBpe;
+        assert_eq!(tokenizer.count(""), 0);
+        assert_eq!(tokenizer.count("Analyze the data"), 4);
+        assert_eq!(tokenizer.count(" internationalization"), 4);
+        assert_eq!(tokenizer.count("1234567"), 3);
+        assert_eq!(tokenizer.count("a --> b"), 4);
+        assert_eq!(tokenizer.count("データ"), 3);
+        assert_eq!(CharsPerToken(4).count("abcdefghi"), 3);
+    }
+
+    #[test]
+    fn counts_each_part_and_flags_budgets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("charts");
+        fs::create_dir_all(root.join("references")).unwrap();
+        fs::write(
+            root.join("SKILL.md"),
+            "---\nname: charts\ndescription: Make charts from tables\n---\n# Charts\n\nRead [the guide](references/guide.md).\n",
+        )
+        .unwrap();
+        fs::write(root.join("references/guide.md"), "Use bars for counts.").unwrap();
+        fs::write(root.join("references/unused.md"), "Never loaded.").unwrap();
+
+        let stats = skill_tokens(&crate::fs::RealFS, &root, &ApproxBpe).unwrap();
+        assert_eq!(stats.name, "charts");
+        assert_eq!(stats.description, 4);
+        assert_eq!(
+            stats.body,
+            ApproxBpe.count("# Charts\n\nRead [the guide](references/guide.md).")
+        );
+        assert_eq!(
+            stats.files,
+            vec![FileTokens {
+                path: PathBuf::from("references/guide.md"),
+                tokens: Some(5),
+            }]
+        );
+        assert_eq!(stats.total, stats.frontmatter + stats.body + 5);
+        assert!(stats.over_budget(&TokenBudgets::default()).is_empty());
+
+        let cjk = "表".repeat(1000);
+        fs::write(
+            root.join("SKILL.md"),
+            format!("---\nname: charts\ndescription: {cjk}\n---\n# Charts\n"),
+        )
+        .unwrap();
+        let cjk_stats = skill_tokens(&crate::fs::RealFS, &root, &ApproxBpe).unwrap();
+        assert_eq!(cjk_stats.description, 1000);
+        let over = cjk_stats.over_budget(&TokenBudgets::default());
+        assert_eq!(over.len(), 1);
+        assert_eq!(
+            (over[0].part.as_str(), over[0].budget),
+            ("description", DEFAULT_DESCRIPTION_BUDGET)
+        );
+        let no_description_budget = TokenBudgets {
+            description: Some(0),
+            ..TokenBudgets::default()
+        };
+        assert!(cjk_stats.over_budget(&no_description_budget).is_empty());
+
+        let budgets = TokenBudgets {
+            description: Some(3),
+            body: Some(0),
+            file: Some(4),
+            ..TokenBudgets::default()
+        };
+        let over: Vec<String> = stats.over_budget(&budgets).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            over,
+            [
+                "description is 4 tokens, over the 3-token budget",
+                "references/guide.md is 5 tokens, over the 4-token budget",
+            ]
+        );
+    }
+}
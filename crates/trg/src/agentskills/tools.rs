@@ -0,0 +1,327 @@
+//! Known tool names for `allowed-tools`, per runner, so a typo (`bash`, `Bash(git*:)`) is
+//! reported instead of silently granting nothing.
+//!
+//! Entries are either a bare name (`Read`) or a name with a specifier in parentheses
+//! (`Bash(git diff:*)`, `Shell(git)`); Claude Code MCP tools are written `mcp__server__tool`.
+
+use std::fmt;
+
+use super::runner::Runner;
+
+/// What may go between the parentheses after a tool name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Specifier {
+    /// Bare name only.
+    Forbidden,
+    /// Optional, any non-empty text (a path glob, a command, an agent name).
+    Any,
+    /// Required, any non-empty text.
+    Required,
+    /// Claude Code `Bash`: a command, where the `:*` prefix wildcard may only come last.
+    BashCommand,
+    /// Claude Code `WebFetch`: `domain:<host>`.
+    Domain,
+    /// Cursor `Mcp`: `<server>:<tool>`, required.
+    McpTool,
+}
+
+struct Vocabulary {
+    runner: Runner,
+    tools: &'static [(&'static str, Specifier)],
+    /// Whether `mcp__<server>[__<tool>]` names are understood.
+    mcp_names: bool,
+}
+
+const VOCABULARIES: &[Vocabulary] = &[
+    Vocabulary {
+        runner: Runner::ClaudeCode,
+        tools: &[
+            ("Bash", Specifier::BashCommand),
+            ("BashOutput", Specifier::Forbidden),
+            ("Edit", Specifier::Any),
+            ("ExitPlanMode", Specifier::Forbidden),
+            ("Glob", Specifier::Any),
+            ("Grep", Specifier::Any),
+            ("KillShell", Specifier::Forbidden),
+            ("LS", Specifier::Any),
+            ("MultiEdit", Specifier::Any),
+            ("NotebookEdit", Specifier::Any),
+            ("NotebookRead", Specifier::Any),
+            ("Read", Specifier::Any),
+            ("Skill", Specifier::Any),
+            ("SlashCommand", Specifier::Any),
+            ("Task", Specifier::Any),
+            ("TodoWrite", Specifier::Forbidden),
+            ("WebFetch", Specifier::Domain),
+            ("WebSearch", Specifier::Forbidden),
+            ("Write", Specifier::Any),
+        ],
+        mcp_names: true,
+    },
+    Vocabulary {
+        runner: Runner::CursorAgent,
+        tools: &[
+            ("Mcp", Specifier::McpTool),
+            ("Read", Specifier::Required),
+            ("Shell", Specifier::Required),
+            ("Write", Specifier::Required),
+        ],
+        mcp_names: false,
+    },
+    Vocabulary {
+        runner: Runner::Codex,
+        tools: &[
+            ("apply_patch", Specifier::Forbidden),
+            ("shell", Specifier::Forbidden),
+            ("update_plan", Specifier::Forbidden),
+            ("view_image", Specifier::Forbidden),
+            ("web_search", Specifier::Forbidden),
+        ],
+        mcp_names: false,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolProblem {
+    /// No runner knows the name.
+    Unknown { tool: String, suggestion: Option<String> },
+    /// The name is known, but no runner accepts it written this way.
+    BadPattern { tool: String, reason: String },
+}
+
+impl fmt::Display for ToolProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown { tool, suggestion } => {
+                write!(f, "`{tool}` is not a tool any supported runner knows")?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, "; did you mean `{suggestion}`?")?;
+                }
+                Ok(())
+            }
+            Self::BadPattern { tool, reason } => write!(f, "`{tool}`: {reason}"),
+        }
+    }
+}
+
+/// Which declared tools a runner would honor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerSupport {
+    pub runner: Runner,
+    pub honored: Vec<String>,
+    pub ignored: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ToolCheck {
+    pub problems: Vec<ToolProblem>,
+    /// One entry per runner, in a fixed order: claude-code, cursor-agent, codex.
+    pub support: Vec<RunnerSupport>,
+}
+
+/// Check each `allowed-tools` entry against every runner's vocabulary.
+pub fn check_allowed_tools(tools: &[String]) -> ToolCheck {
+    let mut support: Vec<RunnerSupport> = VOCABULARIES
+        .iter()
+        .map(|vocabulary| RunnerSupport {
+            runner: vocabulary.runner,
+            honored: Vec::new(),
+            ignored: Vec::new(),
+        })
+        .collect();
+    let mut problems = Vec::new();
+
+    for tool in tools {
+        let verdicts: Vec<Option<Result<(), String>>> =
+            VOCABULARIES.iter().map(|vocabulary| vocabulary.check(tool)).collect();
+        for (runner, verdict) in support.iter_mut().zip(&verdicts) {
+            if matches!(verdict, Some(Ok(()))) {
+                runner.honored.push(tool.clone());
+            } else {
+                runner.ignored.push(tool.clone());
+            }
+        }
+
+        if verdicts.iter().any(|verdict| matches!(verdict, Some(Ok(())))) {
+            continue;
+        }
+        let reason = verdicts.into_iter().flatten().find_map(Result::err);
+        problems.push(match reason {
+            Some(reason) => ToolProblem::BadPattern {
+                tool: tool.clone(),
+                reason,
+            },
+            None if tool.contains(['(', ')']) && parse(tool).is_none() => ToolProblem::BadPattern {
+                tool: tool.clone(),
+                reason: "expected `Tool` or `Tool(specifier)` with balanced parentheses".to_string(),
+            },
+            None => ToolProblem::Unknown {
+                tool: tool.clone(),
+                suggestion: suggest(parse(tool).map_or(tool.as_str(), |(name, _)| name)),
+            },
+        });
+    }
+
+    ToolCheck { problems, support }
+}
+
+impl Vocabulary {
+    /// `None` when this runner doesn't know the tool's name; otherwise whether it accepts
+    /// the entry as written.
+    fn check(&self, tool: &str) -> Option<Result<(), String>> {
+        if let Some(rest) = tool.strip_prefix("mcp__") {
+            if !self.mcp_names {
+                return None;
+            }
+            let valid = rest
+                .split("__")
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || "_-*".contains(c)));
+            return Some(if valid && rest.split("__").count() <= 2 {
+                Ok(())
+            } else {
+                Err("MCP tools are written `mcp__<server>` or `mcp__<server>__<tool>`".to_string())
+            });
+        }
+        let (name, specifier) = parse(tool)?;
+        let (_, kind) = self.tools.iter().find(|(known, _)| *known == name)?;
+        Some(check_specifier(name, *kind, specifier))
+    }
+}
+
+fn check_specifier(name: &str, kind: Specifier, specifier: Option<&str>) -> Result<(), String> {
+    let Some(specifier) = specifier else {
+        return match kind {
+            Specifier::Required => Err(format!("`{name}` needs a specifier, e.g. `{name}(…)`")),
+            Specifier::McpTool => Err(format!("`{name}` needs a specifier, e.g. `{name}(server:tool)`")),
+            _ => Ok(()),
+        };
+    };
+    if specifier.trim().is_empty() {
+        return Err(format!("`{name}()` has an empty specifier"));
+    }
+    match kind {
+        Specifier::Forbidden => Err(format!("`{name}` doesn't take a specifier")),
+        Specifier::Any | Specifier::Required => Ok(()),
+        Specifier::BashCommand => match specifier.find(":*") {
+            Some(at) if at + 2 != specifier.len() => {
+                Err("`:*` only works at the end of a command prefix, e.g. `Bash(git diff:*)`".to_string())
+            }
+            _ => Ok(()),
+        },
+        Specifier::Domain => match specifier.strip_prefix("domain:") {
+            Some(host) if !host.is_empty() => Ok(()),
+            _ => Err(format!(
+                "`{name}` takes `domain:<host>`, e.g. `{name}(domain:example.com)`"
+            )),
+        },
+        Specifier::McpTool => match specifier.split_once(':') {
+            Some((server, tool)) if !server.is_empty() && !tool.is_empty() => Ok(()),
+            _ => Err(format!(
+                "`{name}` takes `server:tool`, e.g. `{name}(github:create_issue)`"
+            )),
+        },
+    }
+}
+
+/// Split `Name(specifier)` into its parts; `None` when it isn't shaped that way.
+fn parse(tool: &str) -> Option<(&str, Option<&str>)> {
+    let (name, specifier) = match tool.split_once('(') {
+        Some((name, rest)) => (name, Some(rest.strip_suffix(')')?)),
+        None => (tool, None),
+    };
+    let valid_name = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid_name.then_some((name, specifier))
+}
+
+/// The known name closest to `name`: the same letters in another case, or at most two edits
+/// away.
+fn suggest(name: &str) -> Option<String> {
+    let known = VOCABULARIES
+        .iter()
+        .flat_map(|vocabulary| vocabulary.tools.iter().map(|(known, _)| *known));
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in known {
+        let distance = if candidate.eq_ignore_ascii_case(name) {
+            0
+        } else {
+            edit_distance(&name.to_ascii_lowercase(), &candidate.to_ascii_lowercase()) + 1
+        };
+        if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.filter(|(distance, _)| *distance <= 2.min(name.len() / 2) + 1)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn reports_unknown_tools_and_bad_patterns() {
+        let check = check_allowed_tools(&tools(&[
+            "Bash(git:*)",
+            "Read",
+            "bash",
+            "Grpe",
+            "python",
+            "Bash(git:* --force)",
+            "WebFetch(example.com)",
+            "TodoWrite(all)",
+            "Shell(git",
+            "mcp__github__create_issue",
+        ]));
+        let problems: Vec<String> = check.problems.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            problems,
+            [
+                "`bash` is not a tool any supported runner knows; did you mean `Bash`?",
+                "`Grpe` is not a tool any supported runner knows; did you mean `Grep`?",
+                "`python` is not a tool any supported runner knows",
+                "`Bash(git:* --force)`: `:*` only works at the end of a command prefix, e.g. `Bash(git diff:*)`",
+                "`WebFetch(example.com)`: `WebFetch` takes `domain:<host>`, e.g. `WebFetch(domain:example.com)`",
+                "`TodoWrite(all)`: `TodoWrite` doesn't take a specifier",
+                "`Shell(git`: expected `Tool` or `Tool(specifier)` with balanced parentheses",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_which_runners_honor_the_tools() {
+        let check = check_allowed_tools(&tools(&["Bash(git:*)", "Read", "Shell(git)", "Read(src/**)", "shell"]));
+        let support: Vec<(&str, Vec<&str>)> = check
+            .support
+            .iter()
+            .map(|s| (s.runner.display_name(), s.honored.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            support,
+            [
+                ("claude-code", vec!["Bash(git:*)", "Read", "Read(src/**)"]),
+                ("cursor-agent", vec!["Shell(git)", "Read(src/**)"]),
+                ("codex", vec!["shell"]),
+            ]
+        );
+        assert!(check.problems.is_empty());
+    }
+}
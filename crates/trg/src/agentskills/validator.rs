@@ -1,16 +1,21 @@
 use super::errors::{Result, SkillError};
+use super::lint::{lint_skill, LintConfig, LintError, LintFinding, LintRule, Severity};
 use super::models::SkillProperties;
 use super::parser;
+use super::sarif::{Finding, Level, Rule};
+use super::tools::{check_allowed_tools, RunnerSupport};
 use super::validation::{ValidationError, ValidationErrors};
 use crate::fs::FileSystem;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use unicode_normalization::UnicodeNormalization;
 
-const MAX_NAME_LEN: usize = 64;
-const MAX_DESC_LEN: usize = 1024;
+pub(crate) const MAX_NAME_LEN: usize = 64;
+pub(crate) const MAX_DESC_LEN: usize = 1024;
 const MAX_COMPAT_LEN: usize = 500;
 
-const ALLOWED_FRONTMATTER_FIELDS: &[&str] = &[
+pub(crate) const ALLOWED_FRONTMATTER_FIELDS: &[&str] = &[
     "name",
     "description",
     "license",
@@ -75,6 +80,180 @@ pub fn validate_skill(fs: &impl FileSystem, skill_path: &Path) -> Result<SkillPr
     Ok(props)
 }
 
+/// Outcome of validating one skill directory; `errors` is empty when it is valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillReport {
+    pub path: PathBuf,
+    /// The frontmatter `name`, when the skill is valid.
+    pub name: Option<String>,
+    pub errors: ValidationErrors,
+    /// Findings of the `SKILL.md` body lint; empty until [`lint_reports`] runs.
+    pub lint: Vec<LintFinding>,
+    /// The frontmatter `allowed-tools`, when the skill is valid.
+    pub allowed_tools: Vec<String>,
+}
+
+impl SkillReport {
+    /// No validation errors and no lint findings at `error` severity.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty() && !self.lint.iter().any(|f| f.severity == Severity::Error)
+    }
+
+    /// Which runners honor the declared `allowed-tools`; empty when the skill declares none.
+    pub fn runner_support(&self) -> Vec<RunnerSupport> {
+        if self.allowed_tools.is_empty() {
+            return Vec::new();
+        }
+        check_allowed_tools(&self.allowed_tools).support
+    }
+}
+
+/// Lint the body of each skill that has a `SKILL.md`, with the `.trg-lint.toml` that applies
+/// to it. Fails only when a lint config can't be read or parsed.
+pub fn lint_reports(fs: &impl FileSystem, reports: &mut [SkillReport]) -> std::result::Result<(), LintError> {
+    for report in reports {
+        let Ok(skill_md) = parser::find_skill_md(fs, &report.path) else {
+            continue;
+        };
+        let config = LintConfig::load_for(&report.path)?;
+        report.lint = lint_skill(&report.path, &skill_md, &config);
+    }
+    Ok(())
+}
+
+/// Validate each skill directory, spread over the available cores. Reports keep the order of
+/// `skill_paths`. Errors that stop validation early (no `SKILL.md`, unparseable YAML) are
+/// reported as a single error without a field.
+pub fn validate_skills<F: FileSystem + Sync>(fs: &F, skill_paths: &[PathBuf]) -> Vec<SkillReport> {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(skill_paths.len());
+    let next = AtomicUsize::new(0);
+    let reports: Mutex<Vec<Option<SkillReport>>> = Mutex::new(vec![None; skill_paths.len()]);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = skill_paths.get(index) else {
+                    break;
+                };
+                let report = validate_one(fs, path);
+                reports.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(report);
+            });
+        }
+    });
+
+    reports
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn validate_one(fs: &impl FileSystem, path: &Path) -> SkillReport {
+    let (props, errors) = match validate_skill(fs, path) {
+        Ok(props) => (Some(props), ValidationErrors::new()),
+        Err(SkillError::Validation(errors)) => (None, errors),
+        Err(SkillError::EmptyField(field)) => (
+            None,
+            ValidationError::for_field(field, "must be a non-empty string").into(),
+        ),
+        Err(other) => (None, ValidationError::new(other.to_string()).into()),
+    };
+    let (name, allowed_tools) = match props {
+        Some(props) => (Some(props.name), props.allowed_tools.unwrap_or_default()),
+        None => (None, Vec::new()),
+    };
+    SkillReport {
+        path: path.to_path_buf(),
+        name,
+        errors,
+        lint: Vec::new(),
+        allowed_tools,
+    }
+}
+
+/// SARIF rule id for a validation error: one per frontmatter field, `skill/structure` for errors
+/// that stopped validation before the fields were checked (missing file, invalid YAML).
+pub fn validation_rule_id(field: Option<&str>) -> String {
+    format!("skill/{}", field.unwrap_or("structure"))
+}
+
+pub fn validation_rules() -> Vec<Rule> {
+    [
+        ("structure", "SKILL.md exists and has parseable YAML frontmatter"),
+        ("frontmatter", "Frontmatter uses only the allowed fields"),
+        ("name", "Skill name is valid and matches its directory"),
+        (
+            "description",
+            "Skill description is present and within the length limit",
+        ),
+        ("compatibility", "Compatibility note is within the length limit"),
+    ]
+    .into_iter()
+    .map(|(field, description)| Rule {
+        id: validation_rule_id(Some(field)),
+        description: description.to_string(),
+    })
+    .chain(LintRule::ALL.iter().map(|rule| Rule {
+        id: lint_rule_id(*rule),
+        description: rule.description().to_string(),
+    }))
+    .collect()
+}
+
+/// SARIF rule id for a body lint rule, e.g. `skill/broken-reference`.
+pub fn lint_rule_id(rule: LintRule) -> String {
+    format!("skill/{}", rule.as_str())
+}
+
+/// One finding per validation error, located at the offending frontmatter line of `SKILL.md`,
+/// then one per lint finding. Unexpected-field errors point at the first field that isn't
+/// allowed.
+pub fn report_findings(fs: &impl FileSystem, reports: &[SkillReport]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for report in reports {
+        let skill_md = parser::find_skill_md(fs, &report.path).unwrap_or_else(|_| report.path.join("SKILL.md"));
+        let key_lines = fs
+            .read_to_string(&skill_md)
+            .map(|content| parser::frontmatter_key_lines(&content))
+            .unwrap_or_default();
+        let line_of = |field: &str| {
+            key_lines
+                .iter()
+                .find(|(key, _)| match field {
+                    "frontmatter" => !ALLOWED_FRONTMATTER_FIELDS.contains(&key.as_str()),
+                    _ => key == field,
+                })
+                .map(|(_, line)| *line)
+        };
+        for error in &report.errors {
+            findings.push(Finding {
+                rule_id: validation_rule_id(error.field()),
+                level: Level::Error,
+                message: error.to_string(),
+                path: Some(skill_md.display().to_string()),
+                line: error.field().and_then(line_of),
+            });
+        }
+        for lint in &report.lint {
+            findings.push(Finding {
+                rule_id: lint_rule_id(lint.rule),
+                level: match lint.severity {
+                    Severity::Error => Level::Error,
+                    Severity::Warning | Severity::Off => Level::Warning,
+                },
+                message: lint.message.clone(),
+                path: Some(lint.path.display().to_string()),
+                line: lint.line,
+            });
+        }
+    }
+    findings
+}
+
 fn validate_name(name: &str, skill_path: &Path) -> Result<()> {
     let mut errors = ValidationErrors::new();
 
@@ -358,4 +537,65 @@ mod tests {
         let result = validate_skill(&fs, Path::new("/Test-Skill"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_validate_skills_keeps_order_and_collects_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |dir: &str, content: &str| {
+            let path = tmp.path().join(dir);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("SKILL.md"), content).unwrap();
+            path
+        };
+        let mut paths = vec![
+            write("good-skill", "---\nname: good-skill\ndescription: Fine\n---"),
+            write("bad-skill", "---\nname: Bad--Skill\ndescription: Fine\nextra: 1\n---"),
+            tmp.path().join("missing"),
+        ];
+        for i in 0..20 {
+            paths.push(write(
+                &format!("skill-{i}"),
+                &format!("---\nname: skill-{i}\ndescription: Fine\n---"),
+            ));
+        }
+
+        let reports = validate_skills(&crate::fs::RealFS, &paths);
+        assert_eq!(reports.len(), paths.len());
+        assert!(reports.iter().zip(&paths).all(|(r, p)| &r.path == p));
+
+        assert!(reports[0].is_valid());
+        assert_eq!(reports[0].name.as_deref(), Some("good-skill"));
+
+        let fields: Vec<_> = reports[1].errors.iter().map(|e| e.field()).collect();
+        assert!(fields.contains(&Some("frontmatter")));
+        assert!(fields.contains(&Some("name")));
+
+        assert_eq!(reports[2].errors.len(), 1);
+        assert_eq!(reports[2].errors.iter().next().unwrap().field(), None);
+        assert!(reports[3..].iter().all(SkillReport::is_valid));
+    }
+
+    #[test]
+    fn test_report_findings_point_at_frontmatter_lines() {
+        let fs = MemFS::new();
+        fs.insert(
+            Path::new("/skills/my-skill/SKILL.md"),
+            "---\nname: Other\ndescription: Fine\nversion: 2\n---\n",
+        );
+        let reports = vec![
+            validate_one(&fs, Path::new("/skills/my-skill")),
+            validate_one(&fs, Path::new("/skills/gone")),
+        ];
+
+        let findings = report_findings(&fs, &reports);
+        let located: Vec<_> = findings
+            .iter()
+            .map(|f| (f.rule_id.as_str(), f.path.as_deref(), f.line))
+            .collect();
+        assert!(located.contains(&("skill/frontmatter", Some("/skills/my-skill/SKILL.md"), Some(4))));
+        assert!(located.contains(&("skill/name", Some("/skills/my-skill/SKILL.md"), Some(2))));
+        assert!(located.contains(&("skill/structure", Some("/skills/gone/SKILL.md"), None)));
+        let rules: Vec<_> = validation_rules().into_iter().map(|r| r.id).collect();
+        assert!(findings.iter().all(|f| rules.contains(&f.rule_id)));
+    }
 }
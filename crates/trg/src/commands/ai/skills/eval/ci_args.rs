@@ -54,6 +54,13 @@ pub struct EvalCiArgs {
         help = "Baseline report directory for regression comparisons"
     )]
     pub baseline: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Also write the CI violations as a SARIF 2.1.0 log, located in evals.json where possible"
+    )]
+    pub sarif: Option<PathBuf>,
 }
 
 impl EvalCiArgs {
@@ -16,10 +16,11 @@ use std::path::Path;
 
 use crate::agentskills::ci::{
     collect_failed_assertions, collect_missing_grading_workspaces, collect_report_metrics, emit_github_annotations,
-    print_human_summary, run_ci_checks, EvalCommandJsonOutput,
+    print_human_summary, run_ci_checks, write_sarif, EvalCommandJsonOutput, EvalsSource,
 };
 use crate::agentskills::evals::WorkspaceCheckReport;
 use crate::fs::FileSystem;
+use ci_args::EvalCiArgs;
 use clap::{Args, Subcommand};
 
 pub use benchmark::BenchmarkArgs;
@@ -79,8 +80,7 @@ impl EvalArgs {
 pub(crate) fn finish_eval_output(
     report_dir: &Path,
     json: bool,
-    policy: crate::agentskills::ci::CiPolicy,
-    thresholds: &crate::agentskills::ci::ThresholdConfig,
+    ci: &EvalCiArgs,
     workspace: Option<WorkspaceCheckReport>,
 ) -> i32 {
     let metrics = match collect_report_metrics(report_dir) {
@@ -93,8 +93,23 @@ pub(crate) fn finish_eval_output(
 
     let failed_assertions = collect_failed_assertions(report_dir).unwrap_or_default();
     let missing_grading = collect_missing_grading_workspaces(report_dir).unwrap_or_default();
-    let check = run_ci_checks(&metrics, policy, thresholds, &failed_assertions, &missing_grading);
+    let mut check = run_ci_checks(
+        &metrics,
+        ci.policy(),
+        &ci.thresholds(),
+        &failed_assertions,
+        &missing_grading,
+    );
+    if let Some(evals) = EvalsSource::load(report_dir) {
+        evals.locate_suite_violations(&mut check.violations);
+    }
     emit_github_annotations(&check.violations);
+    if let Some(path) = &ci.sarif {
+        if let Err(error) = write_sarif(path, &check.violations) {
+            eprintln!("Failed to write SARIF to {}: {error}", path.display());
+            return 1;
+        }
+    }
 
     let exit_code = if check.passed { 0 } else { 1 };
     if json {
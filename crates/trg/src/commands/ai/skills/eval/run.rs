@@ -11,14 +11,16 @@ use crate::agentskills::evals::{
 use crate::agentskills::layout::detect_next_iteration;
 use crate::agentskills::outputs::index_output_artifacts;
 use crate::agentskills::report::{
-    build_report_bundle, write_report_bundle, BuildReportOptions, ReportBundle, ScenarioKind, SkillIntegrityReport,
-    SkillStaging, WriteReportOptions,
+    build_report_bundle, write_report_bundle, BuildReportOptions, ReportBundle, RunRecord, ScenarioKind,
+    SkillIntegrityReport, SkillStaging, WriteReportOptions,
 };
 use crate::agentskills::runner::{
     availability, compute_skill_digest, detect_tampering, EvalRunOutcome, EvalRunRequest, Runner, RunnerError,
 };
+use crate::commands::ai::skills::skills_are_trusted;
 use crate::fs::FileSystem;
 use clap::Args;
+use tracing::{field::Empty, info_span, Span};
 
 use super::benchmark::benchmark_report_dir_with_document;
 use super::ci_args::EvalCiArgs;
@@ -168,12 +170,28 @@ pub struct RunArgs {
     )]
     pub skill_staging: SkillStaging,
 
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Refuse to run unless the skill (and --old-skill-dir) is an unpacked archive signed by a public key (*.pub) in DIR"
+    )]
+    pub trusted_keys: Option<PathBuf>,
+
     #[command(flatten)]
     pub ci: EvalCiArgs,
 }
 
 impl RunArgs {
     pub fn handle(self, fs: &impl FileSystem) -> i32 {
+        if let Some(trusted_keys) = &self.trusted_keys {
+            let skill_dirs: Vec<&Path> = std::iter::once(self.skill_dir.as_path())
+                .chain(self.old_skill_dir.as_deref())
+                .collect();
+            if !skills_are_trusted(trusted_keys, &skill_dirs) {
+                return 1;
+            }
+        }
+
         let props = match crate::agentskills::validator::validate_skill(fs, &self.skill_dir) {
             Ok(props) => props,
             Err(e) => {
@@ -220,6 +238,14 @@ impl RunArgs {
         let iteration = self
             .iteration
             .unwrap_or_else(|| detect_next_iteration(&self.out_dir, &props.name));
+        // Parent of the per-run, grading and benchmark spans (exported with the `otel` feature).
+        let _pipeline = info_span!(
+            "eval.pipeline",
+            skill = %props.name,
+            iteration,
+            runner = self.runner.map(Runner::display_name),
+        )
+        .entered();
 
         if self.scenario.contains(&ScenarioKind::OldSkill) && self.old_skill_dir.is_none() {
             eprintln!("--old-skill-dir is required when --scenario old_skill is included");
@@ -335,7 +361,9 @@ impl RunArgs {
         };
 
         if self.grade {
-            let (code, grade_report) = grade_report_dir_with_report(&report_dir, grade_options);
+            let span = info_span!("eval.grade", exit_code = Empty);
+            let (code, grade_report) = span.in_scope(|| grade_report_dir_with_report(&report_dir, grade_options));
+            span.record("exit_code", code);
             if code != 0 {
                 if self.json && !self.benchmark {
                     emit_chained_grade_json(&report_dir, code, grade_report.as_ref());
@@ -349,7 +377,10 @@ impl RunArgs {
         }
 
         if self.benchmark {
-            let (code, benchmark_doc) = benchmark_report_dir_with_document(&report_dir, BenchmarkOptions::default());
+            let span = info_span!("eval.benchmark", exit_code = Empty);
+            let (code, benchmark_doc) =
+                span.in_scope(|| benchmark_report_dir_with_document(&report_dir, BenchmarkOptions::default()));
+            span.record("exit_code", code);
             if self.json {
                 if let Some(document) = benchmark_doc {
                     emit_chained_benchmark_json(&report_dir, code, &document);
@@ -358,7 +389,7 @@ impl RunArgs {
             return code;
         }
 
-        finish_eval_output(&report_dir, self.json, self.ci.policy(), &self.ci.thresholds(), None)
+        finish_eval_output(&report_dir, self.json, &self.ci, None)
     }
 }
 
@@ -458,6 +489,21 @@ fn execute_runs(
     let runner_kind = runner_kind_label(runner).to_string();
 
     for run in bundle.document.runs.iter_mut() {
+        let run_span = info_span!(
+            "eval.run",
+            run_id = %run.id,
+            eval_case_id = %run.eval_case_id,
+            scenario = run.scenario_id.as_str(),
+            model_config = %run.model_config_id,
+            attempt = run.attempt,
+            cache_hit = Empty,
+            status = Empty,
+            duration_ms = Empty,
+            total_tokens = Empty,
+            input_tokens = Empty,
+            output_tokens = Empty,
+        );
+        let _run_entered = run_span.enter();
         let case = match case_index.get(&run.eval_case_id) {
             Some(case) => *case,
             None => {
@@ -475,6 +521,7 @@ fn execute_runs(
                     None => {
                         eprintln!("Run {}: old_skill scenario requires --old-skill-dir", run.id);
                         run.status = "failed".to_string();
+                        record_run(&run_span, run);
                         continue;
                     }
                 };
@@ -483,6 +530,7 @@ fn execute_runs(
                     None => {
                         eprintln!("Run {}: old skill SKILL.md is unavailable", run.id);
                         run.status = "failed".to_string();
+                        record_run(&run_span, run);
                         continue;
                     }
                 };
@@ -501,6 +549,7 @@ fn execute_runs(
             Err(e) => {
                 eprintln!("Run {}: failed to hash fixtures: {}", run.id, e);
                 run.status = "failed".to_string();
+                record_run(&run_span, run);
                 continue;
             }
         };
@@ -536,7 +585,11 @@ fn execute_runs(
 
         if let Some(pointer) = try_resolve_cache(out_dir, cache_options, &key_input, &reuse_input) {
             match apply_cache_hit(run, &cache_key, &pointer, report_dir) {
-                Ok(()) => continue,
+                Ok(()) => {
+                    run_span.record("cache_hit", true);
+                    record_run(&run_span, run);
+                    continue;
+                }
                 Err(e) => {
                     eprintln!("Run {}: cache reuse failed, re-executing: {}", run.id, e);
                 }
@@ -572,7 +625,26 @@ fn execute_runs(
 
         for _ in 0..max_attempts {
             invocations += 1;
-            match invoke_runner(runner, &request) {
+            let invocation = info_span!(
+                "eval.runner",
+                runner = %runner_kind,
+                invocation = invocations,
+                status = Empty,
+                exit_code = Empty,
+                total_tokens = Empty,
+            );
+            let result = invocation.in_scope(|| invoke_runner(runner, &request));
+            match &result {
+                Ok(outcome) => {
+                    invocation.record("status", outcome.status.as_str());
+                    invocation.record("exit_code", outcome.exit_code);
+                    invocation.record("total_tokens", outcome.total_tokens);
+                }
+                Err(_) => {
+                    invocation.record("status", "error");
+                }
+            }
+            match result {
                 Ok(outcome) => {
                     if !outcome.is_transient_failure() || invocations >= max_attempts {
                         apply_outcome(
@@ -634,6 +706,7 @@ fn execute_runs(
                 }
             }
         }
+        record_run(&run_span, run);
     }
 
     rebuild_summaries(&mut bundle);
@@ -714,6 +787,15 @@ mod fake_runner {
     }
 }
 
+/// Put a run's final status and metrics on its `eval.run` span.
+fn record_run(span: &Span, run: &RunRecord) {
+    span.record("status", run.status.as_str());
+    span.record("duration_ms", run.metrics.duration_ms);
+    span.record("total_tokens", run.metrics.total_tokens);
+    span.record("input_tokens", run.metrics.input_tokens);
+    span.record("output_tokens", run.metrics.output_tokens);
+}
+
 fn invoke_runner(runner: Runner, request: &EvalRunRequest) -> Result<EvalRunOutcome, RunnerError> {
     #[cfg(test)]
     if fake_runner::enabled() {
@@ -900,6 +982,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
 
             ci: EvalCiArgs {
                 strict_ci: false,
@@ -915,6 +998,7 @@ mod tests {
                 max_output_tokens: None,
                 max_duration_ms: None,
                 baseline: None,
+                sarif: None,
             },
         }
         .handle(&crate::fs::RealFS);
@@ -1034,6 +1118,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         }
         .handle(&crate::fs::RealFS);
@@ -1072,6 +1157,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         }
         .handle(&crate::fs::RealFS);
@@ -1126,6 +1212,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         }
         .handle(&crate::fs::RealFS);
@@ -1225,6 +1312,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         };
 
@@ -1271,6 +1359,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         };
 
@@ -1317,6 +1406,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         };
 
@@ -1355,6 +1445,7 @@ mod tests {
             no_cache,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         };
 
@@ -1392,6 +1483,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         });
 
@@ -1417,6 +1509,7 @@ mod tests {
             no_cache: false,
             reuse_completed: true,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         });
 
@@ -1489,6 +1582,7 @@ mod tests {
             no_cache: true,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         });
 
@@ -1552,6 +1646,7 @@ mod tests {
             no_cache: true,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         });
 
@@ -1638,6 +1733,7 @@ mod tests {
             no_cache: true,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         });
 
@@ -1675,6 +1771,7 @@ mod tests {
                 no_cache: false,
                 reuse_completed: false,
                 skill_staging: SkillStaging::Symlink,
+                trusted_keys: None,
                 ci: EvalCiArgs::default(),
             }
             .handle(&crate::fs::RealFS);
@@ -1735,6 +1832,7 @@ mod tests {
             no_cache: false,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         }
         .handle(&crate::fs::RealFS);
@@ -1777,6 +1875,7 @@ mod tests {
             no_cache: true,
             reuse_completed: false,
             skill_staging: SkillStaging::Symlink,
+            trusted_keys: None,
             ci: EvalCiArgs::default(),
         });
 
@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 
 use crate::agentskills::ci::{
     collect_failed_assertions_in_workspace, collect_workspace_metrics, emit_github_annotations, find_report_dir,
-    print_human_summary, run_ci_checks, EvalCommandJsonOutput,
+    print_human_summary, run_ci_checks, run_id_for_workspace, write_sarif, EvalCommandJsonOutput, EvalsSource,
 };
 use crate::agentskills::evals::{
     check_eval_suite, check_workspace, lint_eval_suite_fixtures, print_eval_lint_warnings, EvalCheckOptions,
@@ -118,16 +118,20 @@ impl VerifyArgs {
             }
         };
 
+        let evals = EvalsSource::load(&report_dir);
         let mut failed_assertions = Vec::new();
         if let Err(error) = collect_failed_assertions_in_workspace(
             &workspace,
-            None,
+            run_id_for_workspace(&report_dir, &workspace),
             workspace.display().to_string(),
             &mut failed_assertions,
         ) {
             eprintln!("Failed to collect failed assertions: {error}");
             return 1;
         }
+        if let Some(evals) = &evals {
+            evals.locate_failed_assertions(&mut failed_assertions);
+        }
 
         let mut policy = self.ci.policy();
         if matches!(self.mode, VerifyMode::Strict) {
@@ -141,14 +145,23 @@ impl VerifyArgs {
             Vec::new()
         };
 
-        let check = run_ci_checks(
+        let mut check = run_ci_checks(
             &metrics,
             policy,
             &self.ci.thresholds(),
             &failed_assertions,
             &missing_grading,
         );
+        if let Some(evals) = &evals {
+            evals.locate_suite_violations(&mut check.violations);
+        }
         emit_github_annotations(&check.violations);
+        if let Some(path) = &self.ci.sarif {
+            if let Err(error) = write_sarif(path, &check.violations) {
+                eprintln!("Failed to write SARIF to {}: {error}", path.display());
+                return 1;
+            }
+        }
 
         let exit_code = if check.passed { 0 } else { 1 };
         if self.json {
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use crate::agentskills::index::{build_index, index_dir, SkillIndex};
+use crate::fs::FileSystem;
+use clap::Args;
+
+const AFTER_HELP: &str = "Examples:
+  # Catalog every skill under ./skills; rerun to pick up only what changed
+  $ trg ai skills index ./skills -o index.json
+
+  # Render <available_skills> from the catalog instead of rereading every SKILL.md
+  $ trg ai skills to-prompt --index index.json --tag data";
+
+#[derive(Args)]
+#[command(after_help = AFTER_HELP)]
+pub struct IndexArgs {
+    #[arg(help = "Directory to search for skills, skipping paths matched by .gitignore or .ignore")]
+    pub root: PathBuf,
+
+    #[arg(long, short, default_value = "index.json", help = "Index file to write")]
+    pub output: PathBuf,
+
+    #[arg(
+        long,
+        help = "Reparse every skill instead of reusing unmodified entries from an existing OUTPUT"
+    )]
+    pub full: bool,
+}
+
+impl IndexArgs {
+    pub fn handle(self, fs: &impl FileSystem) -> i32 {
+        let previous = if self.full || !self.output.exists() {
+            None
+        } else {
+            match SkillIndex::load(&self.output) {
+                Ok(index) => Some(index),
+                Err(e) => {
+                    eprintln!("warning: rebuilding from scratch: {e}");
+                    None
+                }
+            }
+        };
+
+        let dir = match index_dir(&self.output) {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("✗ Failed to write {}: {e}", self.output.display());
+                return 1;
+            }
+        };
+        let update = build_index(fs, &self.root, &dir, previous.as_ref());
+        for error in &update.errors {
+            eprintln!("✗ {error}");
+        }
+        if let Err(e) = update.index.save(&self.output) {
+            eprintln!("✗ Failed to write {}: {e}", self.output.display());
+            return 1;
+        }
+
+        eprintln!(
+            "✓ Indexed {} skill(s) into {} ({} added, {} updated, {} removed, {} unchanged)",
+            update.index.skills.len(),
+            self.output.display(),
+            update.added.len(),
+            update.updated.len(),
+            update.removed.len(),
+            update.unchanged
+        );
+        println!("{}", self.output.display());
+        if update.errors.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use crate::agentskills::signing::{generate_key, key_id, write_key_pair};
+use clap::Args;
+
+#[derive(Args)]
+pub struct KeygenArgs {
+    #[arg(
+        long,
+        short,
+        value_name = "PREFIX",
+        help = "Write the secret key to PREFIX.key and the public key to PREFIX.pub"
+    )]
+    pub output: PathBuf,
+}
+
+impl KeygenArgs {
+    pub fn handle(self) -> i32 {
+        let key = generate_key();
+        match write_key_pair(&key, &self.output) {
+            Ok((secret, public)) => {
+                eprintln!("✓ Wrote secret key {} (keep it private)", secret.display());
+                eprintln!("✓ Wrote public key {}", public.display());
+                println!("{}", key_id(&key.verifying_key()));
+                0
+            }
+            Err(e) => {
+                eprintln!("✗ Failed to write key pair: {e}");
+                1
+            }
+        }
+    }
+}
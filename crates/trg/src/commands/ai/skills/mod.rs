@@ -1,17 +1,36 @@
 mod eval;
+mod index;
+mod keygen;
+mod pack;
 mod read_properties;
+mod sign;
+mod stats;
 mod to_prompt;
+mod unpack;
 mod validate;
+mod verify;
+mod verify_pack;
 
 use std::path::{Path, PathBuf};
 
+use crate::agentskills::discovery::discover_skills;
+use crate::agentskills::signing::{verify_skill_dir, TrustedKeys};
 use crate::fs::FileSystem;
 use clap::Subcommand;
+use serde::Serialize;
 
 pub use eval::EvalArgs;
+pub use index::IndexArgs;
+pub use keygen::KeygenArgs;
+pub use pack::PackArgs;
 pub use read_properties::ReadPropertiesArgs;
+pub use sign::SignArgs;
+pub use stats::StatsArgs;
 pub use to_prompt::ToPromptArgs;
+pub use unpack::UnpackArgs;
 pub use validate::ValidateArgs;
+pub use verify::VerifyArgs;
+pub use verify_pack::VerifyPackArgs;
 
 #[derive(Subcommand)]
 #[allow(clippy::large_enum_variant)]
@@ -24,19 +43,89 @@ pub enum SkillsCommands {
     ReadProperties(ReadPropertiesArgs),
     /// Generate <available_skills> XML for agent prompts
     ToPrompt(ToPromptArgs),
+    /// Build or update a JSON catalog of the skills under a directory
+    Index(IndexArgs),
+    /// Estimate token counts for skills and check them against budgets
+    Stats(StatsArgs),
+    /// Pack a skill into a reproducible archive with a MANIFEST.json of file hashes
+    Pack(PackArgs),
+    /// Verify a skill archive and unpack it
+    Unpack(UnpackArgs),
+    /// Check a skill archive against its MANIFEST.json without unpacking it
+    VerifyPack(VerifyPackArgs),
+    /// Generate an Ed25519 key pair for signing skill archives
+    Keygen(KeygenArgs),
+    /// Sign a skill archive with a detached Ed25519 signature
+    Sign(SignArgs),
+    /// Check that a skill archive or unpacked skill is signed by a trusted key
+    Verify(VerifyArgs),
 }
 
 impl SkillsCommands {
-    pub fn handle(self, fs: &impl FileSystem) -> i32 {
+    pub fn handle(self, fs: &(impl FileSystem + Sync)) -> i32 {
         match self {
             Self::Validate(args) => args.handle(fs),
             Self::Eval(args) => args.handle(fs),
             Self::ReadProperties(args) => args.handle(fs),
             Self::ToPrompt(args) => args.handle(fs),
+            Self::Index(args) => args.handle(fs),
+            Self::Stats(args) => args.handle(fs),
+            Self::Pack(args) => args.handle(),
+            Self::Unpack(args) => args.handle(),
+            Self::VerifyPack(args) => args.handle(),
+            Self::Keygen(args) => args.handle(),
+            Self::Sign(args) => args.handle(),
+            Self::Verify(args) => args.handle(),
         }
     }
 }
 
+/// Enforcement for `--trusted-keys`: every skill directory must be an unpacked archive whose
+/// `MANIFEST.sig` verifies against a key in `trusted_keys`. Prints each refusal; `false` when
+/// any skill is refused.
+pub fn skills_are_trusted(trusted_keys: &Path, skill_dirs: &[&Path]) -> bool {
+    let trusted = match TrustedKeys::load(trusted_keys) {
+        Ok(trusted) => trusted,
+        Err(e) => {
+            eprintln!("✗ {e}");
+            return false;
+        }
+    };
+    let mut all_trusted = true;
+    for skill_dir in skill_dirs {
+        if let Err(e) = verify_skill_dir(skill_dir, &trusted) {
+            eprintln!("✗ Refusing untrusted skill {}: {e}", skill_dir.display());
+            all_trusted = false;
+        }
+    }
+    all_trusted
+}
+
+/// The skills a command works on: every skill under `path` with `--recursive`, otherwise the
+/// one at `path`. Prints discovery problems; `None` when a recursive search finds nothing.
+pub fn skill_paths(path: &Path, recursive: bool) -> Option<Vec<PathBuf>> {
+    if !recursive {
+        return Some(vec![resolve_skill_path(path)]);
+    }
+    let discovery = discover_skills(path);
+    for error in &discovery.errors {
+        eprintln!("warning: {error}");
+    }
+    if discovery.skills.is_empty() {
+        eprintln!("✗ No SKILL.md found under {}", path.display());
+        return None;
+    }
+    Some(discovery.skills)
+}
+
+/// Print a report document as pretty JSON on stdout.
+pub fn print_pretty_json(document: &impl Serialize) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(document).expect("JSON values always serialize")
+    );
+}
+
 pub fn resolve_skill_path(path: &Path) -> PathBuf {
     let is_skill_md = matches!(path.file_name().and_then(|n| n.to_str()), Some("SKILL.md" | "skill.md"));
 
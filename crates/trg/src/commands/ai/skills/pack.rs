@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use crate::agentskills::pack::pack_skill;
+use clap::Args;
+
+use super::resolve_skill_path;
+
+#[derive(Args)]
+pub struct PackArgs {
+    #[arg(help = "Path to skill directory or SKILL.md file")]
+    pub path: PathBuf,
+
+    #[arg(long, short, help = "Archive to write [default: <name>.tgz]")]
+    pub output: Option<PathBuf>,
+}
+
+impl PackArgs {
+    pub fn handle(self) -> i32 {
+        let skill_path = resolve_skill_path(&self.path);
+        let mut archive = Vec::new();
+        let manifest = match pack_skill(&skill_path, &mut archive) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                eprintln!("✗ Failed to pack {}: {e}", skill_path.display());
+                return 1;
+            }
+        };
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("{}.tgz", manifest.skill.name)));
+        if let Err(e) = std::fs::write(&output, &archive) {
+            eprintln!("✗ Failed to write {}: {e}", output.display());
+            return 1;
+        }
+        eprintln!(
+            "✓ Packed {} ({} file(s)) into {}",
+            manifest.skill.name,
+            manifest.files.len(),
+            output.display()
+        );
+        println!("{}", output.display());
+        0
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use crate::agentskills::signing::{read_signing_key, sign_archive, signature_path, write_signature};
+use clap::Args;
+
+#[derive(Args)]
+pub struct SignArgs {
+    #[arg(help = "Skill archive written by `trg ai skills pack`")]
+    pub archive: PathBuf,
+
+    #[arg(long, value_name = "FILE", help = "Secret key written by `trg ai skills keygen`")]
+    pub key: PathBuf,
+
+    #[arg(
+        long,
+        short,
+        value_name = "FILE",
+        help = "Signature to write [default: <ARCHIVE>.sig]"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+impl SignArgs {
+    pub fn handle(self) -> i32 {
+        let key = match read_signing_key(&self.key) {
+            Ok(key) => key,
+            Err(e) => {
+                eprintln!("✗ {e}");
+                return 1;
+            }
+        };
+        let signature = match sign_archive(&self.archive, &key) {
+            Ok(signature) => signature,
+            Err(e) => {
+                eprintln!("✗ Failed to sign {}: {e}", self.archive.display());
+                return 1;
+            }
+        };
+        let output = self.output.unwrap_or_else(|| signature_path(&self.archive));
+        if let Err(e) = write_signature(&output, &signature) {
+            eprintln!("✗ Failed to write {}: {e}", output.display());
+            return 1;
+        }
+        eprintln!("✓ Signed {} with key {}", self.archive.display(), signature.key_id);
+        println!("{}", output.display());
+        0
+    }
+}
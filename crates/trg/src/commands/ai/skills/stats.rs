@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use crate::agentskills::lint::LintConfig;
+use crate::agentskills::parser::{find_skill_md, read_properties};
+use crate::agentskills::prompt::{to_prompt_with_location, SkillWithLocation};
+use crate::agentskills::tokens::{skill_tokens, OverBudget, SkillTokens, Tokenizer, TokenizerKind};
+use crate::fs::FileSystem;
+use clap::{Args, ValueEnum};
+use serde_json::json;
+
+use super::{print_pretty_json, skill_paths};
+
+const AFTER_HELP: &str = "Examples:
+  # Token counts for one skill
+  $ trg ai skills stats ./my-skill
+
+  # Every skill under a tree, plus the size of the whole <available_skills> catalog
+  $ trg ai skills stats --recursive . --format json
+
+Budgets come from the [budgets] table of the .trg-lint.toml next to the skill or in any
+parent directory. Only body has a default, 5000; 0 turns a budget off.
+
+  [budgets]
+  description = 200   # the description field alone
+  frontmatter = 300   # the whole <available_skills> entry
+  body = 4000         # SKILL.md after the frontmatter
+  file = 2000         # each referenced file
+  total = 12000       # entry, body and referenced files together";
+
+#[derive(Args)]
+#[command(after_help = AFTER_HELP)]
+pub struct StatsArgs {
+    #[arg(help = "Path to skill directory or SKILL.md file; with --recursive, the directory to search")]
+    pub path: PathBuf,
+
+    #[arg(
+        long,
+        short,
+        help = "Measure every skill (directory with a SKILL.md) under PATH, skipping paths matched by .gitignore or .ignore"
+    )]
+    pub recursive: bool,
+
+    #[arg(long, value_enum, default_value_t = TokenizerKind::default(), help = "How to count tokens")]
+    pub tokenizer: TokenizerKind,
+
+    #[arg(long, value_enum, default_value_t = StatsFormat::Human, help = "Output format")]
+    pub format: StatsFormat,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    Human,
+    Json,
+}
+
+struct Measured {
+    tokens: SkillTokens,
+    over_budget: Vec<OverBudget>,
+}
+
+impl StatsArgs {
+    pub fn handle(self, fs: &impl FileSystem) -> i32 {
+        let Some(skill_paths) = skill_paths(&self.path, self.recursive) else {
+            return 1;
+        };
+
+        let mut measured = Vec::with_capacity(skill_paths.len());
+        let mut catalog = Vec::with_capacity(skill_paths.len());
+        let mut had_error = false;
+        for path in &skill_paths {
+            let config = match LintConfig::load_for(path) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("✗ {e}");
+                    return 1;
+                }
+            };
+            match skill_tokens(fs, path, &self.tokenizer) {
+                Ok(tokens) => {
+                    let over_budget = tokens.over_budget(config.budgets());
+                    measured.push(Measured { tokens, over_budget });
+                }
+                Err(e) => {
+                    eprintln!("✗ Failed to read skill from {}: {e}", path.display());
+                    had_error = true;
+                    continue;
+                }
+            }
+            if let Ok((properties, _)) = read_properties(fs, path) {
+                catalog.push(SkillWithLocation {
+                    properties,
+                    location: find_skill_md(fs, path).ok().map(|p| p.to_string_lossy().to_string()),
+                });
+            }
+        }
+        let catalog_tokens = self.tokenizer.count(&to_prompt_with_location(&catalog));
+
+        match self.format {
+            StatsFormat::Human => print_human(&measured, catalog_tokens),
+            StatsFormat::Json => print_json(self.tokenizer, &measured, catalog_tokens),
+        }
+        if had_error || measured.iter().any(|m| !m.over_budget.is_empty()) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+fn print_human(measured: &[Measured], catalog_tokens: usize) {
+    for Measured { tokens, over_budget } in measured {
+        println!("{} ({})", tokens.name, tokens.path.display());
+        let mut rows = vec![
+            ("frontmatter".to_string(), tokens.frontmatter.to_string()),
+            ("  description".to_string(), tokens.description.to_string()),
+            ("body".to_string(), tokens.body.to_string()),
+        ];
+        rows.extend(tokens.files.iter().map(|file| {
+            let count = file.tokens.map_or_else(|| "binary".to_string(), |t| t.to_string());
+            (file.path.display().to_string(), count)
+        }));
+        rows.push(("total".to_string(), tokens.total.to_string()));
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let count_width = rows.iter().map(|(_, count)| count.len()).max().unwrap_or(0);
+        for (label, count) in rows {
+            println!("  {label:<label_width$}  {count:>count_width$}");
+        }
+        for over in over_budget {
+            println!("  ✗ {over}");
+        }
+    }
+    if measured.len() > 1 {
+        println!();
+        println!(
+            "<available_skills> catalog: {catalog_tokens} tokens for {} skill(s)",
+            measured.len()
+        );
+    }
+}
+
+fn print_json(tokenizer: TokenizerKind, measured: &[Measured], catalog_tokens: usize) {
+    let skills: Vec<_> = measured
+        .iter()
+        .map(|Measured { tokens, over_budget }| {
+            json!({
+                "path": tokens.path.display().to_string(),
+                "name": tokens.name,
+                "frontmatter": tokens.frontmatter,
+                "description": tokens.description,
+                "body": tokens.body,
+                "files": tokens.files,
+                "total": tokens.total,
+                "over_budget": over_budget,
+            })
+        })
+        .collect();
+    let document = json!({
+        "tokenizer": tokenizer,
+        "skills": skills,
+        "catalog": catalog_tokens,
+    });
+    print_pretty_json(&document);
+}
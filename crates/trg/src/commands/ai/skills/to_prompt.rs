@@ -1,23 +1,105 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::agentskills::catalog::{fit_to_budget, render_catalog, CatalogFormat};
+use crate::agentskills::index::{SkillFilter, SkillIndex};
 use crate::agentskills::parser::{find_skill_md, read_properties};
-use crate::agentskills::prompt::{to_prompt_with_location, SkillWithLocation};
+use crate::agentskills::prompt::SkillWithLocation;
+use crate::agentskills::tokens::TokenizerKind;
 use crate::fs::FileSystem;
 use clap::Args;
 
-use super::resolve_skill_path;
+use super::{resolve_skill_path, skills_are_trusted};
 
 #[derive(Args)]
 pub struct ToPromptArgs {
-    #[arg(help = "Paths to skill directories or SKILL.md files")]
+    #[arg(help = "Paths to skill directories or SKILL.md files", conflicts_with = "index")]
     pub paths: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Read skills from a catalog written by `trg ai skills index`"
+    )]
+    pub index: Option<PathBuf>,
+
+    #[arg(
+        long = "metadata",
+        value_name = "KEY[=VALUE]",
+        help = "Keep skills whose metadata has KEY (set to VALUE); repeat to accept any of several"
+    )]
+    pub metadata: Vec<String>,
+
+    #[arg(
+        long = "tag",
+        value_name = "TAG",
+        help = "Keep skills whose `tags` metadata lists TAG; repeat to accept any of several"
+    )]
+    pub tags: Vec<String>,
+
+    #[arg(
+        long = "glob",
+        value_name = "PATTERN",
+        help = "Keep skills whose name or directory matches PATTERN; repeat to accept any of several"
+    )]
+    pub globs: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Refuse skills that aren't unpacked archives signed by a public key (*.pub) in DIR; prints nothing if any is refused"
+    )]
+    pub trusted_keys: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = CatalogFormat::default(), help = "Output format")]
+    pub format: CatalogFormat,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Keep the catalog within N tokens, ranking skills by `priority` metadata, then shorter description; truncates or omits the rest"
+    )]
+    pub max_tokens: Option<usize>,
+
+    #[arg(long, value_enum, default_value_t = TokenizerKind::default(), help = "How to count tokens for --max-tokens")]
+    pub tokenizer: TokenizerKind,
 }
 
 impl ToPromptArgs {
     pub fn handle(self, fs: &impl FileSystem) -> i32 {
+        let filter = match SkillFilter::new(&self.metadata, &self.tags, &self.globs) {
+            Ok(filter) => filter,
+            Err(e) => {
+                eprintln!("✗ Invalid --glob: {e}");
+                return 1;
+            }
+        };
+
         let mut skills = Vec::new();
         let mut had_error = false;
 
+        if let Some(index) = &self.index {
+            match SkillIndex::load(index) {
+                Ok(index) => {
+                    let cwd = std::env::current_dir().and_then(|cwd| cwd.canonicalize()).ok();
+                    skills.extend(index.skills.iter().map(|entry| {
+                        let skill_md = index.skill_md(entry);
+                        // Relative to where the agent starts when the skill is under it.
+                        let shown = cwd
+                            .as_deref()
+                            .and_then(|cwd| skill_md.strip_prefix(cwd).ok())
+                            .unwrap_or(&skill_md);
+                        let mut skill = entry.to_skill();
+                        skill.location = Some(shown.display().to_string());
+                        (skill_md.parent().unwrap_or(Path::new("")).to_path_buf(), skill)
+                    }));
+                }
+                Err(e) => {
+                    eprintln!("✗ {e}");
+                    return 1;
+                }
+            }
+        }
+
         for path in &self.paths {
             let skill_path = resolve_skill_path(path);
             match read_properties(fs, &skill_path) {
@@ -25,10 +107,13 @@ impl ToPromptArgs {
                     let location = find_skill_md(fs, &skill_path)
                         .ok()
                         .map(|p| p.to_string_lossy().to_string());
-                    skills.push(SkillWithLocation {
-                        properties: props,
-                        location,
-                    });
+                    skills.push((
+                        skill_path,
+                        SkillWithLocation {
+                            properties: props,
+                            location,
+                        },
+                    ));
                 }
                 Err(e) => {
                     eprintln!("✗ Failed to read skill from {:?}: {}", path, e);
@@ -40,8 +125,55 @@ impl ToPromptArgs {
         if had_error && skills.is_empty() {
             return 1;
         }
+        skills.retain(|(_, skill)| filter.matches(skill));
+
+        if let Some(trusted_keys) = &self.trusted_keys {
+            let skill_dirs: Vec<&Path> = skills.iter().map(|(dir, _)| dir.as_path()).collect();
+            if !skills_are_trusted(trusted_keys, &skill_dirs) {
+                return 1;
+            }
+            if self.index.is_some() {
+                // The index isn't signed; describe each skill from its verified SKILL.md.
+                for (dir, skill) in &mut skills {
+                    match read_properties(fs, dir) {
+                        Ok((properties, _)) => skill.properties = properties,
+                        Err(e) => {
+                            eprintln!("✗ Failed to read skill from {}: {e}", dir.display());
+                            return 1;
+                        }
+                    }
+                }
+                skills.retain(|(_, skill)| filter.matches(skill));
+            }
+        }
+
+        let mut skills: Vec<SkillWithLocation> = skills.into_iter().map(|(_, skill)| skill).collect();
+        if let Some(max_tokens) = self.max_tokens {
+            let selection = match fit_to_budget(skills, self.format, &self.tokenizer, max_tokens) {
+                Ok(selection) => selection,
+                Err(e) => {
+                    eprintln!("✗ --max-tokens is too small: {e}");
+                    return 1;
+                }
+            };
+            if !selection.truncated.is_empty() {
+                eprintln!(
+                    "warning: truncated the description of {} skill(s) to fit --max-tokens {max_tokens}: {}",
+                    selection.truncated.len(),
+                    selection.truncated.join(", ")
+                );
+            }
+            if !selection.omitted.is_empty() {
+                eprintln!(
+                    "warning: omitted {} skill(s) to fit --max-tokens {max_tokens}: {}",
+                    selection.omitted.len(),
+                    selection.omitted.join(", ")
+                );
+            }
+            skills = selection.skills;
+        }
 
-        println!("{}", to_prompt_with_location(&skills));
+        println!("{}", render_catalog(self.format, &skills));
         0
     }
 }
@@ -0,0 +1,85 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use crate::agentskills::pack::{read_pack, SIGNATURE_FILE};
+use crate::agentskills::signing::{read_signature_bytes, signature_path, verify_pack, SigningError, TrustedKeys};
+use clap::Args;
+
+#[derive(Args)]
+pub struct UnpackArgs {
+    #[arg(help = "Skill archive written by `trg ai skills pack`")]
+    pub archive: PathBuf,
+
+    #[arg(
+        long,
+        short,
+        default_value = ".",
+        help = "Directory to unpack into; the skill lands in <DEST>/<name>"
+    )]
+    pub dest: PathBuf,
+
+    #[arg(long, help = "Replace an existing <DEST>/<name> directory")]
+    pub force: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Refuse the archive unless <ARCHIVE>.sig is signed by a public key (*.pub) in DIR"
+    )]
+    pub trusted_keys: Option<PathBuf>,
+}
+
+impl UnpackArgs {
+    pub fn handle(self) -> i32 {
+        // The archive and its signature are each read once: what is verified is what gets
+        // unpacked and kept, even if the files on disk change meanwhile.
+        let pack = match File::open(&self.archive).map_err(Into::into).and_then(read_pack) {
+            Ok(pack) => pack,
+            Err(e) => {
+                eprintln!("✗ {}: {e}", self.archive.display());
+                return 1;
+            }
+        };
+        let signature = match read_signature_bytes(&signature_path(&self.archive)) {
+            Ok(signature) => Some(signature),
+            Err(SigningError::Unsigned(_)) if self.trusted_keys.is_none() => None,
+            Err(e) => {
+                eprintln!("✗ {}: {e}", self.archive.display());
+                return 1;
+            }
+        };
+        if let (Some(dir), Some(signature)) = (&self.trusted_keys, &signature) {
+            let verified = TrustedKeys::load(dir).and_then(|trusted| verify_pack(&pack, signature, &trusted));
+            match verified {
+                Ok(verified) => eprintln!("✓ Signed by trusted key {} ({})", verified.key_name, verified.key_id),
+                Err(e) => {
+                    eprintln!("✗ {}: {e}", self.archive.display());
+                    return 1;
+                }
+            }
+        }
+
+        let unpacked = pack.unpack(&self.dest, self.force).and_then(|skill_dir| {
+            // Keep the signature with the skill so it can be verified where it is used.
+            if let Some(signature) = &signature {
+                std::fs::write(skill_dir.join(SIGNATURE_FILE), signature)?;
+            }
+            Ok(skill_dir)
+        });
+        match unpacked {
+            Ok(skill_dir) => {
+                eprintln!(
+                    "✓ Verified and unpacked {} ({} file(s))",
+                    pack.manifest.skill.name,
+                    pack.manifest.files.len()
+                );
+                println!("{}", skill_dir.display());
+                0
+            }
+            Err(e) => {
+                eprintln!("✗ Failed to unpack {}: {e}", self.archive.display());
+                1
+            }
+        }
+    }
+}
@@ -1,28 +1,350 @@
-use std::path::PathBuf;
+use std::io::{stderr, stdin, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
+use crate::agentskills::fix::{plan_fixes, FixOptions};
+use crate::agentskills::sarif::{emit_github_annotations, to_sarif};
+use crate::agentskills::validator::{
+    lint_reports, report_findings, validate_skills, validation_rules, SkillReport, MAX_DESC_LEN,
+};
 use crate::fs::FileSystem;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use serde_json::json;
 
-use super::resolve_skill_path;
+use super::{print_pretty_json, skill_paths};
+
+const AFTER_HELP: &str = "Examples:
+  # Validate one skill
+  $ trg ai skills validate ./my-skill
+
+  # Validate every skill under a tree (honours .gitignore and .ignore), as SARIF for code scanning
+  $ trg ai skills validate --recursive . --format sarif > skills.sarif
+
+  # Preview, then apply, mechanical fixes to frontmatter
+  $ trg ai skills validate --recursive . --fix --dry-run
+  $ trg ai skills validate --recursive . --fix
+
+The SKILL.md body is linted too: broken references, non-executable scripts, unreferenced
+files and malformed headings, plus a description over its token budget and allowed-tools
+entries no runner understands. Tune or disable rules in a .trg-lint.toml next to the skill
+or in any parent directory:
+
+  [rules]
+  unreferenced-file = \"off\"
+
+The description budget defaults to 256 tokens; set it in the same file, or to 0 to turn it
+off:
+
+  [budgets]
+  description = 200";
 
 #[derive(Args)]
+#[command(after_help = AFTER_HELP)]
 pub struct ValidateArgs {
-    #[arg(help = "Path to skill directory or SKILL.md file")]
+    #[arg(help = "Path to skill directory or SKILL.md file; with --recursive, the directory to search")]
     pub path: PathBuf,
+
+    #[arg(
+        long,
+        short,
+        help = "Validate every skill (directory with a SKILL.md) under PATH, skipping paths matched by .gitignore or .ignore"
+    )]
+    pub recursive: bool,
+
+    #[arg(long, value_enum, default_value_t = ValidateFormat::Human, help = "Output format")]
+    pub format: ValidateFormat,
+
+    #[arg(
+        long,
+        help = "Rewrite SKILL.md frontmatter to fix mechanical problems (name spelling, name/directory mismatch, allowed-tools string, unexpected fields), then validate"
+    )]
+    pub fix: bool,
+
+    #[arg(
+        long,
+        requires = "fix",
+        help = "With --fix, print the changes as a diff instead of making them"
+    )]
+    pub dry_run: bool,
+
+    #[arg(
+        long,
+        short,
+        requires = "fix",
+        help = "With --fix, trim over-long descriptions without asking"
+    )]
+    pub yes: bool,
+
+    #[arg(
+        long,
+        requires = "fix",
+        help = "With --fix, delete unexpected frontmatter fields instead of moving them into metadata"
+    )]
+    pub drop_unknown_fields: bool,
+
+    #[arg(
+        long,
+        requires = "fix",
+        help = "With --fix, rename the directory to match the skill instead of renaming the skill"
+    )]
+    pub rename_dir: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ValidateFormat {
+    Human,
+    Json,
+    Sarif,
 }
 
 impl ValidateArgs {
-    pub fn handle(self, fs: &impl FileSystem) -> i32 {
-        let skill_path = resolve_skill_path(&self.path);
-        match crate::agentskills::validator::validate_skill(fs, &skill_path) {
-            Ok(_) => {
-                println!("✓ Skill is valid");
-                0
+    pub fn handle(self, fs: &(impl FileSystem + Sync)) -> i32 {
+        let Some(skill_paths) = skill_paths(&self.path, self.recursive) else {
+            return 1;
+        };
+
+        let skill_paths = if self.fix {
+            match self.run_fixes(fs, skill_paths) {
+                Ok(paths) => paths,
+                Err(code) => return code,
             }
-            Err(e) => {
-                eprintln!("✗ Validation failed: {}", e);
-                1
+        } else {
+            skill_paths
+        };
+
+        if !self.recursive && self.format == ValidateFormat::Human {
+            return handle_single(fs, &skill_paths[0]);
+        }
+
+        let mut reports = validate_skills(fs, &skill_paths);
+        if let Err(e) = lint_reports(fs, &mut reports) {
+            eprintln!("✗ {e}");
+            return 1;
+        }
+        match self.format {
+            ValidateFormat::Human => {
+                emit_github_annotations(&report_findings(fs, &reports));
+                print_human(&reports);
             }
+            ValidateFormat::Json => print_json(&reports),
+            ValidateFormat::Sarif => print_sarif(fs, &reports),
+        }
+        if reports.iter().all(SkillReport::is_valid) {
+            0
+        } else {
+            1
         }
     }
+
+    /// Fix each skill, or with `--dry-run` print what would change. Returns the skill
+    /// directories to validate afterwards (some may have been renamed), or the exit code for
+    /// a dry run: 1 when anything would change.
+    fn run_fixes(&self, fs: &impl FileSystem, skill_paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, i32> {
+        let options = FixOptions {
+            trim_description: self.yes,
+            drop_unknown_fields: self.drop_unknown_fields,
+            rename_dir: self.rename_dir,
+        };
+        let mut fixed_paths = Vec::with_capacity(skill_paths.len());
+        let mut pending = false;
+        for path in skill_paths {
+            let mut plan = match plan_fixes(fs, &path, options) {
+                Ok(plan) => plan,
+                Err(e) => {
+                    eprintln!("{}: can't fix: {e}", path.display());
+                    fixed_paths.push(path);
+                    continue;
+                }
+            };
+            if let Some(length) = plan.long_description {
+                let question = format!(
+                    "{}: description is {length} characters, over the {MAX_DESC_LEN} limit. Trim it?",
+                    plan.skill_md.display()
+                );
+                if !self.dry_run && confirm(&question) {
+                    let options = FixOptions {
+                        trim_description: true,
+                        ..options
+                    };
+                    match plan_fixes(fs, &path, options) {
+                        Ok(trimmed) => plan = trimmed,
+                        Err(e) => eprintln!("{}: can't fix: {e}", path.display()),
+                    }
+                } else {
+                    plan.skipped.push(format!(
+                        "description is {length} characters; rerun with --fix --yes to trim it"
+                    ));
+                }
+            }
+            for skipped in &plan.skipped {
+                eprintln!("{}: not fixed: {skipped}", path.display());
+            }
+            if plan.is_empty() {
+                fixed_paths.push(path);
+                continue;
+            }
+
+            if self.dry_run {
+                pending = true;
+                print!("{}", plan.diff());
+                if let Some((from, to)) = &plan.rename_dir {
+                    println!("rename {} -> {}", from.display(), to.display());
+                }
+                continue;
+            }
+            match plan.apply(fs) {
+                Ok(new_path) => {
+                    eprintln!("fixed {}:", plan.skill_md.display());
+                    for fix in &plan.fixes {
+                        eprintln!("  - {fix}");
+                    }
+                    fixed_paths.push(new_path);
+                }
+                Err(e) => {
+                    eprintln!("{}: failed to write fixes: {e}", path.display());
+                    return Err(1);
+                }
+            }
+        }
+        if self.dry_run {
+            Err(i32::from(pending))
+        } else {
+            Ok(fixed_paths)
+        }
+    }
+}
+
+/// Ask a yes/no question on the terminal; `false` when there is no terminal to ask on.
+fn confirm(question: &str) -> bool {
+    if !(stdin().is_terminal() && stderr().is_terminal()) {
+        return false;
+    }
+    eprint!("{question} [y/N] ");
+    let _ = stderr().flush();
+    let mut answer = String::new();
+    if stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+fn handle_single(fs: &(impl FileSystem + Sync), skill_path: &Path) -> i32 {
+    let mut reports = validate_skills(fs, &[skill_path.to_path_buf()]);
+    if let Err(e) = lint_reports(fs, &mut reports) {
+        eprintln!("✗ {e}");
+        return 1;
+    }
+    emit_github_annotations(&report_findings(fs, &reports));
+
+    let report = &reports[0];
+    for finding in &report.lint {
+        eprintln!("{finding}");
+    }
+    if !report.errors.is_empty() {
+        eprintln!("✗ Validation failed: {}", report.errors);
+        1
+    } else if !report.is_valid() {
+        eprintln!("✗ Skill has lint errors");
+        1
+    } else {
+        println!("✓ Skill is valid");
+        if let Some(line) = runner_support_line(report) {
+            println!("  {line}");
+        }
+        0
+    }
+}
+
+fn print_human(reports: &[SkillReport]) {
+    for report in reports {
+        if report.is_valid() {
+            println!("✓ {}", report.path.display());
+        } else {
+            println!("✗ {}", report.path.display());
+        }
+        for error in &report.errors {
+            println!("    {error}");
+        }
+        for finding in &report.lint {
+            println!("    {finding}");
+        }
+        if let Some(line) = runner_support_line(report) {
+            println!("    {line}");
+        }
+    }
+    let invalid = reports.iter().filter(|r| !r.is_valid()).count();
+    println!();
+    println!(
+        "{} skill(s) checked: {} valid, {} invalid",
+        reports.len(),
+        reports.len() - invalid,
+        invalid
+    );
+}
+
+/// `allowed-tools honored by: claude-code 2/2, cursor-agent 0/2, codex 0/2`, or `None` when
+/// the skill declares no tools.
+fn runner_support_line(report: &SkillReport) -> Option<String> {
+    let support = report.runner_support();
+    if support.is_empty() {
+        return None;
+    }
+    let runners: Vec<String> = support
+        .iter()
+        .map(|runner| {
+            format!(
+                "{} {}/{}",
+                runner.runner.display_name(),
+                runner.honored.len(),
+                report.allowed_tools.len()
+            )
+        })
+        .collect();
+    Some(format!("allowed-tools honored by: {}", runners.join(", ")))
+}
+
+fn print_json(reports: &[SkillReport]) {
+    let skills: Vec<_> = reports
+        .iter()
+        .map(|report| {
+            let errors: Vec<_> = report
+                .errors
+                .iter()
+                .map(|e| json!({ "field": e.field(), "message": e.message() }))
+                .collect();
+            let runners: Vec<_> = report
+                .runner_support()
+                .iter()
+                .map(|runner| {
+                    json!({
+                        "runner": runner.runner.display_name(),
+                        "honored": runner.honored,
+                        "ignored": runner.ignored,
+                    })
+                })
+                .collect();
+            json!({
+                "path": report.path.display().to_string(),
+                "name": report.name,
+                "valid": report.is_valid(),
+                "errors": errors,
+                "lint": report.lint,
+                "runners": runners,
+            })
+        })
+        .collect();
+    let invalid = reports.iter().filter(|r| !r.is_valid()).count();
+    let document = json!({
+        "skills": skills,
+        "summary": {
+            "total": reports.len(),
+            "valid": reports.len() - invalid,
+            "invalid": invalid,
+        },
+    });
+    print_pretty_json(&document);
+}
+
+fn print_sarif(fs: &impl FileSystem, reports: &[SkillReport]) {
+    let log = to_sarif(&validation_rules(), &report_findings(fs, reports));
+    print_pretty_json(&log);
 }
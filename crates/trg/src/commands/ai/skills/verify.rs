@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use crate::agentskills::signing::{signature_path, verify_archive, verify_skill_dir, TrustedKeys};
+use clap::Args;
+
+use super::resolve_skill_path;
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[arg(help = "Skill archive, or an unpacked skill directory with MANIFEST.json and MANIFEST.sig")]
+    pub path: PathBuf,
+
+    #[arg(long, value_name = "DIR", help = "Directory of trusted public keys (*.pub)")]
+    pub trusted_keys: PathBuf,
+
+    #[arg(long, value_name = "FILE", help = "Signature of an archive [default: <ARCHIVE>.sig]")]
+    pub signature: Option<PathBuf>,
+}
+
+impl VerifyArgs {
+    pub fn handle(self) -> i32 {
+        let trusted = match TrustedKeys::load(&self.trusted_keys) {
+            Ok(trusted) => trusted,
+            Err(e) => {
+                eprintln!("✗ {e}");
+                return 1;
+            }
+        };
+        let result = if self.path.is_dir() || self.path.file_name().is_some_and(|name| name == "SKILL.md") {
+            verify_skill_dir(&resolve_skill_path(&self.path), &trusted)
+        } else {
+            let signature = self.signature.unwrap_or_else(|| signature_path(&self.path));
+            verify_archive(&self.path, &signature, &trusted)
+        };
+        match result {
+            Ok(verified) => {
+                println!(
+                    "✓ {} is signed by trusted key {} ({})",
+                    verified.skill, verified.key_name, verified.key_id
+                );
+                0
+            }
+            Err(e) => {
+                eprintln!("✗ {}: {e}", self.path.display());
+                1
+            }
+        }
+    }
+}
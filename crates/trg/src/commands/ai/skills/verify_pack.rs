@@ -0,0 +1,30 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use crate::agentskills::pack::read_pack;
+use clap::Args;
+
+#[derive(Args)]
+pub struct VerifyPackArgs {
+    #[arg(help = "Skill archive written by `trg ai skills pack`")]
+    pub archive: PathBuf,
+}
+
+impl VerifyPackArgs {
+    pub fn handle(self) -> i32 {
+        match File::open(&self.archive).map_err(Into::into).and_then(read_pack) {
+            Ok(pack) => {
+                let manifest = &pack.manifest;
+                println!("✓ {} matches its manifest", self.archive.display());
+                println!("  skill: {}", manifest.skill.name);
+                println!("  files: {}", manifest.files.len());
+                println!("  packed by: trg {}", manifest.trg_version);
+                0
+            }
+            Err(e) => {
+                eprintln!("✗ {}: {e}", self.archive.display());
+                1
+            }
+        }
+    }
+}
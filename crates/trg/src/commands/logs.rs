@@ -0,0 +1,329 @@
+//! `trg logs`: read the diagnostic log (`trg.log` and its rotated copies) written by
+//! [`crate::telemetry`].
+//!
+//! Lines are printed as written, text or JSON. `--server` relies on the pid every line
+//! carries: a process is attributed to the server named in any of its lines with a `server`
+//! field (the proxy's `startup` line comes first), so its other lines follow. Lines without a
+//! timestamp, such as the rest of a multi-line message, go with the line before them.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom, Write},
+    os::unix::fs::MetadataExt,
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use clap::Args;
+use serde_json::Value;
+
+use crate::{commands::when::parse_when, telemetry};
+
+/// How often `--follow` checks the log for new lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug, Clone)]
+pub struct LogsArgs {
+    /// Only lines from `trg mcp proxy` processes for this server.
+    #[arg(long)]
+    pub server: Option<String>,
+
+    /// Keep printing lines as they are written, across rotations.
+    #[arg(long, short)]
+    pub follow: bool,
+
+    /// Only lines at or after this time: RFC 3339 (`2026-01-31T09:00:00Z`) or an age such as `30m`, `12h`, `7d`.
+    #[arg(long, value_name = "WHEN", value_parser = parse_when)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogsError {
+    #[error("cannot locate trg.log: neither $XDG_CACHE_HOME nor $HOME is set")]
+    NoLogDir,
+
+    #[error("cannot read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl LogsArgs {
+    pub fn handle(self) -> i32 {
+        match run(&self) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("{e}");
+                1
+            }
+        }
+    }
+}
+
+fn run(args: &LogsArgs) -> Result<(), LogsError> {
+    let path = telemetry::log_path().ok_or(LogsError::NoLogDir)?;
+    let mut filter = LineFilter::new(args.server.clone(), args.since);
+    let mut out = io::stdout().lock();
+
+    let (rotated, current) = {
+        let mut files = telemetry::log_files(&path);
+        let current = files.pop();
+        (files, current)
+    };
+    for file in rotated {
+        let text = std::fs::read(&file).map_err(|source| LogsError::Read { path: file, source })?;
+        if !print_matching(&mut out, &mut filter, String::from_utf8_lossy(&text).lines()) {
+            return Ok(());
+        }
+    }
+    if current.is_none_or(|p| !p.exists()) && !args.follow {
+        eprintln!("No log at {} yet.", path.display());
+        return Ok(());
+    }
+
+    let mut tail = Tail::new(path);
+    loop {
+        let lines = tail.read_new().map_err(|source| LogsError::Read {
+            path: tail.path.clone(),
+            source,
+        })?;
+        if !print_matching(&mut out, &mut filter, lines.iter().map(String::as_str)) || !args.follow {
+            return Ok(());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Print the lines `filter` keeps; `false` once stdout is gone (`trg logs | head`).
+fn print_matching<'a>(out: &mut impl Write, filter: &mut LineFilter, lines: impl Iterator<Item = &'a str>) -> bool {
+    for line in lines {
+        if filter.keep(line) && writeln!(out, "{line}").is_err() {
+            return false;
+        }
+    }
+    out.flush().is_ok()
+}
+
+/// What `--server` and `--since` need from the start of a line.
+#[derive(Debug, PartialEq)]
+struct LineHead {
+    ts: DateTime<Utc>,
+    pid: Option<u32>,
+    server: Option<String>,
+}
+
+/// Text lines are `<rfc3339> [<pid>] LEVEL target: message key=value…`; older lines have no pid.
+fn parse_line(line: &str) -> Option<LineHead> {
+    if line.starts_with('{') {
+        let value: Value = serde_json::from_str(line).ok()?;
+        return Some(LineHead {
+            ts: DateTime::parse_from_rfc3339(value["timestamp"].as_str()?)
+                .ok()?
+                .with_timezone(&Utc),
+            pid: value["pid"].as_u64().and_then(|p| u32::try_from(p).ok()),
+            server: value["fields"]["server"].as_str().map(str::to_string),
+        });
+    }
+    let (ts, rest) = line.split_once(' ')?;
+    let ts = DateTime::parse_from_rfc3339(ts).ok()?.with_timezone(&Utc);
+    let pid = rest
+        .strip_prefix('[')
+        .and_then(|r| r.split_once(']'))
+        .and_then(|(pid, _)| pid.parse().ok());
+    let server = rest
+        .split_once(" server=")
+        .map(|(_, value)| match value.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next().unwrap_or_default().to_string(),
+            None => value.split_whitespace().next().unwrap_or_default().to_string(),
+        });
+    Some(LineHead { ts, pid, server })
+}
+
+struct LineFilter {
+    server: Option<String>,
+    since: Option<DateTime<Utc>>,
+    /// Server each pid was last seen logging for.
+    servers: HashMap<u32, String>,
+    /// Decision for the last line with a timestamp.
+    keep: bool,
+}
+
+impl LineFilter {
+    fn new(server: Option<String>, since: Option<DateTime<Utc>>) -> Self {
+        Self {
+            keep: server.is_none() && since.is_none(),
+            server,
+            since,
+            servers: HashMap::new(),
+        }
+    }
+
+    fn keep(&mut self, line: &str) -> bool {
+        let Some(head) = parse_line(line) else {
+            return self.keep;
+        };
+        if let (Some(pid), Some(server)) = (head.pid, &head.server) {
+            self.servers.insert(pid, server.clone());
+        }
+        let server = head
+            .server
+            .as_ref()
+            .or_else(|| head.pid.and_then(|pid| self.servers.get(&pid)));
+        let keep = self.since.is_none_or(|since| head.ts >= since)
+            && self.server.as_ref().is_none_or(|want| server == Some(want));
+        self.keep = keep;
+        keep
+    }
+}
+
+/// Reads the complete lines appended to the log since the last call, following the path
+/// across rotations (a new inode) and truncation.
+struct Tail {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+    inode: u64,
+    /// A line whose newline has not been written yet.
+    partial: String,
+}
+
+impl Tail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            reader: None,
+            inode: 0,
+            partial: String::new(),
+        }
+    }
+
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        // Finish the file we have open before moving to a replacement, so nothing written
+        // just before a rotation is lost.
+        self.drain(&mut lines)?;
+        let meta = match std::fs::metadata(&self.path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(lines),
+            Err(e) => return Err(e),
+        };
+        let reopen = match &mut self.reader {
+            None => true,
+            Some(_) if meta.ino() != self.inode => true,
+            Some(reader) => {
+                if meta.len() < reader.stream_position()? {
+                    reader.seek(SeekFrom::Start(0))?;
+                    self.partial.clear();
+                }
+                false
+            }
+        };
+        if reopen {
+            self.reader = Some(BufReader::new(File::open(&self.path)?));
+            self.inode = meta.ino();
+            self.partial.clear();
+        }
+        self.drain(&mut lines)?;
+        Ok(lines)
+    }
+
+    fn drain(&mut self, lines: &mut Vec<String>) -> io::Result<()> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(());
+        };
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                return Ok(());
+            }
+            self.partial.push_str(&String::from_utf8_lossy(&buf));
+            if self.partial.ends_with('\n') {
+                let line = std::mem::take(&mut self.partial);
+                lines.push(line.trim_end_matches(['\n', '\r']).to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::fs::rotate_numbered;
+
+    const STARTUP: &str =
+        r#"2026-03-10T10:00:00.000001Z [41]  INFO trg::commands::mcp::proxy::run: startup server="linear" pid=41"#;
+    const BRIDGE: &str = "2026-03-10T10:00:01.000001Z [41] DEBUG trg::commands::mcp::proxy::run: bridge: entering loop";
+    const OTHER: &str =
+        r#"2026-03-10T10:00:02.000001Z [42]  INFO trg::commands::mcp::proxy::run: startup server="github" pid=42"#;
+
+    #[test]
+    fn parses_text_and_json_lines() {
+        let head = parse_line(STARTUP).unwrap();
+        assert_eq!(head.pid, Some(41));
+        assert_eq!(head.server.as_deref(), Some("linear"));
+        assert_eq!(head.ts.to_rfc3339(), "2026-03-10T10:00:00.000001+00:00");
+
+        let json = r#"{"timestamp":"2026-03-10T10:00:00Z","level":"WARN","pid":7,"target":"trg","fields":{"message":"x","server":"linear"},"spans":[]}"#;
+        let head = parse_line(json).unwrap();
+        assert_eq!((head.pid, head.server.as_deref()), (Some(7), Some("linear")));
+
+        let old = parse_line("2026-03-10T10:00:00.000001Z  INFO trg: startup server=linear").unwrap();
+        assert_eq!((old.pid, old.server.as_deref()), (None, Some("linear")));
+        assert!(parse_line("    at src/main.rs:3").is_none());
+    }
+
+    #[test]
+    fn server_filter_follows_pids_and_continuation_lines() {
+        let mut filter = LineFilter::new(Some("linear".into()), None);
+        let kept: Vec<&str> = [STARTUP, BRIDGE, "  continued", OTHER, "  continued too"]
+            .into_iter()
+            .filter(|line| filter.keep(line))
+            .collect();
+        assert_eq!(kept, [STARTUP, BRIDGE, "  continued"]);
+
+        let since = DateTime::parse_from_rfc3339("2026-03-10T10:00:01Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut filter = LineFilter::new(None, Some(since));
+        assert!(!filter.keep(STARTUP));
+        assert!(filter.keep(BRIDGE));
+        assert!(LineFilter::new(None, None).keep("no timestamp"));
+    }
+
+    #[test]
+    fn tail_follows_rotation_and_partial_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trg.log");
+        let append = |text: &str| {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+        let mut tail = Tail::new(path.clone());
+        assert!(tail.read_new().unwrap().is_empty());
+
+        append("one\ntw");
+        assert_eq!(tail.read_new().unwrap(), ["one"]);
+        append("o\n");
+        assert_eq!(tail.read_new().unwrap(), ["two"]);
+
+        append("three\n");
+        rotate_numbered(&path, 2).unwrap();
+        append("four\n");
+        assert_eq!(tail.read_new().unwrap(), ["three", "four"]);
+
+        std::fs::write(&path, "").unwrap();
+        append("5\n");
+        assert_eq!(tail.read_new().unwrap(), ["5"]);
+    }
+}
@@ -0,0 +1,328 @@
+//! `trg mcp audit`: query the JSONL audit logs written by `trg mcp proxy` for servers with an
+//! `[mcp.servers.<name>.audit]` table.
+//!
+//! One line per `tools/call`: [`AuditRecord`]. Logs are kept apart from the diagnostic
+//! `trg.log`. They are append-only and never hold tokens, and they keep arguments only in
+//! the form the server's `arguments` setting allows.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use clap::{Args, ValueEnum};
+use rmcp::model::RequestId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{commands::when::parse_when, config};
+
+/// How a `tools/call` ended, as far as the host could tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// A result without `isError`.
+    Ok,
+    /// A result with `isError: true`: the tool ran and reported failure.
+    ToolError,
+    /// A JSON-RPC error, from the remote or from the proxy itself (invalid arguments,
+    /// rate limiting, a failed upstream).
+    Error,
+    /// The proxy exited (host disconnected, remote lost) before any answer arrived.
+    Unanswered,
+}
+
+impl AuditOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuditOutcome::Ok => "ok",
+            AuditOutcome::ToolError => "tool_error",
+            AuditOutcome::Error => "error",
+            AuditOutcome::Unanswered => "unanswered",
+        }
+    }
+}
+
+/// One line of an audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the proxy received the call from the host (RFC 3339, UTC).
+    pub ts: String,
+    pub server: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    pub tool: String,
+    pub id: RequestId,
+    /// SHA-256 of the arguments' canonical JSON (`arguments = "hash"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments_sha256: Option<String>,
+    /// The arguments with secrets scrubbed (`arguments = "redact"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    pub outcome: AuditOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub duration_ms: u64,
+    pub pid: u32,
+}
+
+/// `<dir>/<server>.jsonl`; characters outside `A-Z a-z 0-9 . _ -` in the server name become `_`.
+pub fn audit_file(dir: &Path, server: &str) -> PathBuf {
+    let name: String = server
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{name}.jsonl"))
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AuditArgs {
+    /// Only calls to this server (as named under `[mcp.servers.<name>]`).
+    #[arg(long)]
+    pub server: Option<String>,
+
+    /// Only calls to this tool.
+    #[arg(long)]
+    pub tool: Option<String>,
+
+    /// Only calls at or after this time: RFC 3339 (`2026-01-31T09:00:00Z`) or an age such as `30m`, `12h`, `7d`.
+    #[arg(long, value_name = "WHEN", value_parser = parse_when)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only calls before this time; same forms as `--since`.
+    #[arg(long, value_name = "WHEN", value_parser = parse_when)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only calls that ended this way.
+    #[arg(long, value_enum)]
+    pub outcome: Option<AuditOutcome>,
+
+    /// Output format. `json` prints the matching records as JSON lines.
+    #[arg(long, value_enum, default_value_t = AuditFormat::Text)]
+    pub format: AuditFormat,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum AuditFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditError {
+    #[error("{0}")]
+    Config(#[from] config::ConfigError),
+
+    #[error("cannot read audit log `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl AuditArgs {
+    pub fn handle(self) -> i32 {
+        match run(&self) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("{e}");
+                1
+            }
+        }
+    }
+}
+
+fn run(args: &AuditArgs) -> Result<(), AuditError> {
+    let mut records = Vec::new();
+    let mut malformed = 0;
+    for dir in config::load_audit_dirs()? {
+        for path in log_files(&dir)? {
+            let text = fs::read_to_string(&path).map_err(|source| AuditError::Read { path, source })?;
+            for line in text.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str::<AuditRecord>(line) {
+                    Ok(record) => records.push(record),
+                    Err(_) => malformed += 1,
+                }
+            }
+        }
+    }
+
+    let mut matching: Vec<(DateTime<FixedOffset>, AuditRecord)> = records
+        .into_iter()
+        .filter_map(|record| Some((DateTime::parse_from_rfc3339(&record.ts).ok()?, record)))
+        .filter(|(ts, record)| matches(args, ts, record))
+        .collect();
+    matching.sort_by_key(|(ts, _)| *ts);
+
+    match args.format {
+        AuditFormat::Json => {
+            for (_, record) in &matching {
+                println!("{}", serde_json::to_string(record).unwrap_or_default());
+            }
+        }
+        AuditFormat::Text => print_records(&matching),
+    }
+    if malformed > 0 {
+        eprintln!("Skipped {malformed} unreadable audit line(s).");
+    }
+    Ok(())
+}
+
+/// Current and rotated logs (`*.jsonl`, `*.jsonl.<n>`) in `dir`; a missing directory has none.
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>, AuditError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(AuditError::Read {
+                path: dir.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(is_log_name))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn is_log_name(name: &str) -> bool {
+    match name.split_once(".jsonl") {
+        Some((stem, "")) => !stem.is_empty(),
+        Some((stem, rotation)) => {
+            !stem.is_empty()
+                && rotation
+                    .strip_prefix('.')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        }
+        None => false,
+    }
+}
+
+fn matches(args: &AuditArgs, ts: &DateTime<FixedOffset>, record: &AuditRecord) -> bool {
+    args.server.as_deref().is_none_or(|s| s == record.server)
+        && args.tool.as_deref().is_none_or(|t| t == record.tool)
+        && args.outcome.is_none_or(|o| o == record.outcome)
+        && args.since.is_none_or(|since| *ts >= since)
+        && args.until.is_none_or(|until| *ts < until)
+}
+
+fn print_records(records: &[(DateTime<FixedOffset>, AuditRecord)]) {
+    if records.is_empty() {
+        println!("No matching audit records.");
+        return;
+    }
+    let width = |f: fn(&AuditRecord) -> usize, header: &str| {
+        records.iter().map(|(_, r)| f(r)).max().unwrap_or(0).max(header.len())
+    };
+    let server_width = width(|r| r.server.len(), "SERVER");
+    let account_width = width(|r| r.account.as_ref().map_or(1, String::len), "ACCOUNT");
+    let tool_width = width(|r| r.tool.len(), "TOOL");
+    println!(
+        "{:<24}  {:<server_width$}  {:<account_width$}  {:<tool_width$}  {:<10}  {:>9}  DETAIL",
+        "TIME", "SERVER", "ACCOUNT", "TOOL", "OUTCOME", "DURATION"
+    );
+    for (ts, record) in records {
+        let detail = match (&record.error_code, &record.error_message) {
+            (Some(code), Some(message)) => format!("{code}: {message}"),
+            (Some(code), None) => code.to_string(),
+            (None, _) => String::new(),
+        };
+        let line = format!(
+            "{:<24}  {:<server_width$}  {:<account_width$}  {:<tool_width$}  {:<10}  {:>7}ms  {detail}",
+            ts.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            record.server,
+            record.account.as_deref().unwrap_or("-"),
+            record.tool,
+            record.outcome.as_str(),
+            record.duration_ms,
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(server: &str, tool: &str, ts: &str, outcome: AuditOutcome) -> AuditRecord {
+        AuditRecord {
+            ts: ts.into(),
+            server: server.into(),
+            account: None,
+            tool: tool.into(),
+            id: RequestId::Number(1),
+            arguments_sha256: None,
+            arguments: None,
+            outcome,
+            error_code: None,
+            error_message: None,
+            duration_ms: 5,
+            pid: 1,
+        }
+    }
+
+    fn args() -> AuditArgs {
+        AuditArgs {
+            server: None,
+            tool: None,
+            since: None,
+            until: None,
+            outcome: None,
+            format: AuditFormat::Text,
+        }
+    }
+
+    #[test]
+    fn filters_combine() {
+        let ts = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+        let call = record("linear", "search", "2026-03-10T10:00:00Z", AuditOutcome::Ok);
+        let at = ts(&call.ts);
+        assert!(matches(&args(), &at, &call));
+
+        let mut filtered = args();
+        filtered.server = Some("linear".into());
+        filtered.tool = Some("search".into());
+        filtered.since = Some(ts("2026-03-10T10:00:00Z").with_timezone(&Utc));
+        filtered.until = Some(ts("2026-03-10T11:00:00Z").with_timezone(&Utc));
+        assert!(matches(&filtered, &at, &call));
+
+        filtered.until = Some(at.with_timezone(&Utc));
+        assert!(!matches(&filtered, &at, &call), "until is exclusive");
+
+        let mut other_tool = args();
+        other_tool.tool = Some("create".into());
+        assert!(!matches(&other_tool, &at, &call));
+
+        let mut errors = args();
+        errors.outcome = Some(AuditOutcome::Error);
+        assert!(!matches(&errors, &at, &call));
+    }
+
+    #[test]
+    fn recognises_current_and_rotated_logs() {
+        assert!(is_log_name("linear.jsonl"));
+        assert!(is_log_name("linear.jsonl.3"));
+        assert!(!is_log_name("linear.jsonl.tmp"));
+        assert!(!is_log_name("linear.jsonl."));
+        assert!(!is_log_name(".jsonl"));
+        assert!(!is_log_name("notes.txt"));
+        assert_eq!(
+            audit_file(Path::new("/a"), "acme/prod server"),
+            Path::new("/a/acme_prod_server.jsonl")
+        );
+    }
+}
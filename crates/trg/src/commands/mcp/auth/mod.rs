@@ -1,12 +1,28 @@
-//! `trg mcp auth`: manage OAuth credentials stored in the macOS Keychain.
+//! `trg mcp auth`: manage OAuth credentials in the configured credential store
+//! (macOS Keychain, Secret Service, or encrypted file; see `credential_store`).
 
+use std::future::Future;
+
+use async_trait::async_trait;
 use clap::{Args, Subcommand, ValueEnum};
 use oauth2::TokenResponse;
-use rmcp::transport::auth::{CredentialStore, OAuthTokenResponse, StoredCredentials};
+use rmcp::transport::auth::{AuthError as StoreError, CredentialStore, OAuthTokenResponse, StoredCredentials};
 use serde::Serialize;
 use serde_json::{json, Value};
 
-use crate::oauth::{ensure_credentials, store::KeychainCredentialStore, EnsureError, EnsureOutcome};
+use crate::config::{self, CredentialBackend, OAuthSettings};
+use crate::oauth::{
+    ensure_credentials_for,
+    flow::{CallbackMode, FlowConfig},
+    refresh_credentials,
+    revoke::{revoke_stored_tokens, RevocationOutcome},
+    step_up::{merge_scopes, missing_scopes, probe_insufficient_scope, required_scopes, step_up, StepUpError},
+    store::{
+        credential_key, list_accounts, split_credential_key, ConfiguredCredentialStore, EncryptedFileCredentialStore,
+        KEYRING_SERVICE,
+    },
+    EnsureError, EnsureOutcome,
+};
 
 /// Display view over an `OAuthTokenResponse`.
 ///
@@ -45,6 +61,9 @@ impl TokenSummary {
 
 #[derive(Serialize)]
 struct StoredCredentialsView<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<&'a str>,
+    store: CredentialBackend,
     client_id: &'a str,
     granted_scopes: &'a [String],
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -70,8 +89,15 @@ struct TokenResponseView<'a> {
 }
 
 impl<'a> StoredCredentialsView<'a> {
-    fn from(stored: &'a StoredCredentials, summary: Option<&'a TokenSummary>) -> Self {
+    fn from(
+        account: Option<&'a str>,
+        store: CredentialBackend,
+        stored: &'a StoredCredentials,
+        summary: Option<&'a TokenSummary>,
+    ) -> Self {
         Self {
+            account,
+            store,
             client_id: &stored.client_id,
             granted_scopes: &stored.granted_scopes,
             token_received_at: stored.token_received_at,
@@ -98,6 +124,12 @@ pub enum AuthCommands {
 
     /// Delete the cached OAuth credentials for a configured MCP server.
     Logout(LogoutArgs),
+
+    /// Refresh the stored access token now using the stored refresh token.
+    Refresh(RefreshArgs),
+
+    /// List every server with stored OAuth credentials, including servers removed from config.
+    List(ListArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -105,6 +137,20 @@ pub struct LoginArgs {
     /// Server name as it appears under `[mcp.servers.<name>]`.
     #[arg(long)]
     pub server: String,
+
+    /// Stored login to act on when the server has several (default: `account` from config, else the default login).
+    #[arg(long)]
+    pub account: Option<String>,
+
+    /// Skip the loopback listener: print the authorization URL and read the final
+    /// redirect URL pasted from any browser (containers, port-forwarded shells).
+    #[arg(long)]
+    pub manual: bool,
+
+    /// Additional OAuth scope to request (repeatable). Scopes not in the stored grant trigger
+    /// re-authorization for the union of configured, granted and requested scopes.
+    #[arg(long = "scope", value_name = "SCOPE")]
+    pub scopes: Vec<String>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -113,6 +159,10 @@ pub struct StatusArgs {
     #[arg(long)]
     pub server: String,
 
+    /// Stored login to act on when the server has several (default: `account` from config, else the default login).
+    #[arg(long)]
+    pub account: Option<String>,
+
     /// Output format. `json` prints a redacted credential summary (tokens are not emitted).
     #[arg(long, value_enum, default_value_t = StatusFormat::Text)]
     pub format: StatusFormat,
@@ -129,15 +179,51 @@ pub struct LogoutArgs {
     /// Server name as it appears under `[mcp.servers.<name>]`.
     #[arg(long)]
     pub server: String,
+
+    /// Stored login to act on when the server has several (default: `account` from config, else the default login).
+    #[arg(long)]
+    pub account: Option<String>,
+
+    /// Only delete the local entry; skip RFC 7009 revocation at the authorization server.
+    #[arg(long)]
+    pub no_revoke: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ListArgs {
+    /// Output format. `json` omits tokens, like `status --format json`.
+    #[arg(long, value_enum, default_value_t = StatusFormat::Text)]
+    pub format: StatusFormat,
+
+    /// Delete entries whose server is no longer under `[mcp.servers]`.
+    #[arg(long)]
+    pub prune: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RefreshArgs {
+    /// Server name as it appears under `[mcp.servers.<name>]`.
+    #[arg(long)]
+    pub server: String,
+
+    /// Stored login to act on when the server has several (default: `account` from config, else the default login).
+    #[arg(long)]
+    pub account: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum AuthError {
+    #[error("{0}")]
+    Config(#[from] config::ConfigError),
+
     #[error("{0}")]
     Ensure(#[from] EnsureError),
 
+    #[error("{0}")]
+    StepUp(#[from] StepUpError),
+
     #[error("OAuth: {0}")]
-    Store(#[from] rmcp::transport::auth::AuthError),
+    Store(#[from] StoreError),
 }
 
 impl AuthCommands {
@@ -152,10 +238,15 @@ impl AuthCommands {
                 Err(e) => emit(e),
             },
             AuthCommands::Logout(args) => match logout(&args).await {
-                Ok(()) => {
-                    println!("OAuth credentials cleared for `{}`.", args.server.trim());
-                    0
-                }
+                Ok(()) => 0,
+                Err(e) => emit(e),
+            },
+            AuthCommands::Refresh(args) => match refresh(&args).await {
+                Ok(()) => 0,
+                Err(e) => emit(e),
+            },
+            AuthCommands::List(args) => match list(&args).await {
+                Ok(()) => 0,
                 Err(e) => emit(e),
             },
         }
@@ -167,39 +258,121 @@ fn emit<E: std::fmt::Display>(e: E) -> i32 {
     1
 }
 
+/// Ask for `scopes` on top of the configured ones, so a first login covers them without a step-up.
+fn request_scopes(oauth: &mut OAuthSettings, scopes: &[String]) {
+    oauth.scopes = merge_scopes([oauth.scopes.as_slice(), scopes]);
+}
+
 async fn login(args: &LoginArgs) -> Result<(), AuthError> {
     let server = args.server.trim();
-    match ensure_credentials(server).await? {
+    let flow = FlowConfig {
+        callback_mode: if args.manual {
+            CallbackMode::Manual
+        } else {
+            CallbackMode::Loopback
+        },
+        ..FlowConfig::default()
+    };
+    let mut profile = config::load_mcp_server(server)?;
+    if let Some(account) = &args.account {
+        profile.account = Some(config::validate_account(account.clone())?);
+    }
+    request_scopes(&mut profile.oauth, &args.scopes);
+    let target = describe_target(server, profile.account.as_deref());
+    let outcome = ensure_credentials_for(&profile, server, flow).await?;
+    let store = ConfiguredCredentialStore::for_server(&profile.credential_store, server, profile.account.as_deref());
+    let manager = match outcome {
         EnsureOutcome::NoAuthRequired => {
             println!(
                 "`{server}` does not require OAuth (no discovery support, or static \
                  Authorization header configured)."
             );
+            return Ok(());
         }
-        EnsureOutcome::AlreadyAuthorized(_) => {
+        EnsureOutcome::AlreadyAuthorized(manager) => {
             println!(
-                "OAuth credentials already cached for `{server}` in the macOS Keychain. \
-                 Use `trg mcp auth logout --server {server}` to force re-auth."
+                "OAuth credentials already cached for {target} in {}. \
+                 Use `trg mcp auth logout --server {server}{}` to force re-auth.",
+                store.location(),
+                profile
+                    .account
+                    .as_deref()
+                    .map(|a| format!(" --account {a}"))
+                    .unwrap_or_default()
             );
+            manager
         }
-        EnsureOutcome::Authorized(_) => {
+        EnsureOutcome::Authorized(manager) => {
             println!(
-                "OAuth complete for `{server}`. Credentials stored in the macOS Keychain \
-                 (service `trg MCP Credentials`, account `{server}`)."
+                "OAuth complete for {target}. Credentials stored in {}.",
+                store.location()
+            );
+            manager
+        }
+    };
+
+    // Step up for scopes asked for on the command line, or for a challenge the server
+    // raises on `initialize`, so the proxy does not discover it on the first request.
+    let granted = store.load().await?.map(|s| s.granted_scopes).unwrap_or_default();
+    let mut required = missing_scopes(&granted, &args.scopes);
+    if required.is_empty() {
+        if let Some(challenge) = probe_insufficient_scope(&profile, manager).await {
+            required = required_scopes(&challenge);
+            if required.is_empty() {
+                return Err(StepUpError::UnknownScope {
+                    server: server.to_string(),
+                    header: challenge.www_authenticate_header,
+                }
+                .into());
+            }
+            println!(
+                "`{server}` requires additional scope(s) `{}`; re-authorizing.",
+                required.join(" ")
             );
         }
     }
+    if required.is_empty() {
+        return Ok(());
+    }
+
+    step_up(&profile, server, &required, flow).await?;
+    let granted = store.load().await?.map(|s| s.granted_scopes).unwrap_or_default();
+    println!("Step-up complete for {target}. Granted scopes: {}.", granted.join(" "));
     Ok(())
 }
 
+/// `--account` if given, else the server's configured `account`.
+fn selected_account(server: &str, cli: &Option<String>) -> Result<Option<String>, AuthError> {
+    match cli {
+        Some(account) => Ok(Some(config::validate_account(account.clone())?)),
+        None => Ok(config::load_account(server)?),
+    }
+}
+
+fn describe_target(server: &str, account: Option<&str>) -> String {
+    match account {
+        Some(account) => format!("`{server}` (account `{account}`)"),
+        None => format!("`{server}`"),
+    }
+}
+
 async fn status(args: &StatusArgs) -> Result<(), AuthError> {
     let server = args.server.trim();
-    let store = KeychainCredentialStore::new(server);
+    let settings = config::load_credential_store(server)?;
+    let account = selected_account(server, &args.account)?;
+    let store = ConfiguredCredentialStore::for_server(&settings, server, account.as_deref());
 
     let Some(stored) = store.load().await? else {
         match args.format {
-            StatusFormat::Text => println!("No OAuth credentials stored for `{server}`."),
-            StatusFormat::Json => println!("{}", json!({ "server": server, "stored": false })),
+            StatusFormat::Text => println!(
+                "No OAuth credentials stored for {} in {}.",
+                describe_target(server, account.as_deref()),
+                store.location()
+            ),
+            StatusFormat::Json => println!(
+                "{}",
+                json!({ "server": server, "account": account, "store": store.backend(), "stored": false })
+            ),
         }
         return Ok(());
     };
@@ -208,18 +381,36 @@ async fn status(args: &StatusArgs) -> Result<(), AuthError> {
 
     match args.format {
         StatusFormat::Json => {
-            let view = StoredCredentialsView::from(&stored, summary.as_ref());
+            let view = StoredCredentialsView::from(account.as_deref(), store.backend(), &stored, summary.as_ref());
             let json = serde_json::to_string_pretty(&view).unwrap_or_else(|e| format!("<failed to serialize: {e}>"));
             println!("{json}");
         }
-        StatusFormat::Text => print_summary(server, &stored, summary.as_ref()),
+        StatusFormat::Text => print_summary(server, account.as_deref(), &settings, &stored, summary.as_ref()),
     }
     Ok(())
 }
 
-fn print_summary(server: &str, stored: &StoredCredentials, summary: Option<&TokenSummary>) {
+fn print_summary(
+    server: &str,
+    account: Option<&str>,
+    settings: &config::CredentialStoreSettings,
+    stored: &StoredCredentials,
+    summary: Option<&TokenSummary>,
+) {
     println!("Server:       {server}");
-    println!("Service:      trg MCP Credentials");
+    if let Some(account) = account {
+        println!("Account:      {account}");
+    }
+    println!("Store:        {}", settings.backend);
+    match settings.backend {
+        CredentialBackend::Keychain | CredentialBackend::SecretService => {
+            println!("Service:      {KEYRING_SERVICE}");
+        }
+        CredentialBackend::EncryptedFile => {
+            let path = EncryptedFileCredentialStore::new(credential_key(server, account), &settings.file).path();
+            println!("File:         {}", path.display());
+        }
+    }
     println!("Client ID:    {}", stored.client_id);
 
     if stored.granted_scopes.is_empty() || (stored.granted_scopes.len() == 1 && stored.granted_scopes[0].is_empty()) {
@@ -306,7 +497,562 @@ fn format_duration(secs: u64) -> String {
 }
 
 async fn logout(args: &LogoutArgs) -> Result<(), AuthError> {
-    let store = KeychainCredentialStore::new(args.server.trim());
+    let server = args.server.trim();
+    let account = selected_account(server, &args.account)?;
+    let store =
+        ConfiguredCredentialStore::for_server(&config::load_credential_store(server)?, server, account.as_deref());
+    revoke_and_clear(&store, !args.no_revoke, |stored| async move {
+        revoke_stored_tokens(server, &stored).await
+    })
+    .await?;
+    println!(
+        "OAuth credentials cleared for {}.",
+        describe_target(server, account.as_deref())
+    );
+    Ok(())
+}
+
+/// Revokes the stored tokens through `revoke` (when `revoke_tokens`) and deletes the entry.
+async fn revoke_and_clear<S, R, F>(store: &S, revoke_tokens: bool, revoke: R) -> Result<(), AuthError>
+where
+    S: CredentialStore,
+    R: FnOnce(StoredCredentials) -> F,
+    F: Future<Output = RevocationOutcome>,
+{
+    if revoke_tokens {
+        // An unreadable entry (e.g. missing passphrase) cannot be revoked but must still be deletable.
+        match store.load().await {
+            Ok(Some(stored)) => print_revocation(&revoke(stored).await),
+            Ok(None) => {}
+            Err(e) => eprintln!("Skipping token revocation: cannot read stored credentials: {e}"),
+        }
+    }
     store.clear().await?;
     Ok(())
 }
+
+fn print_revocation(outcome: &RevocationOutcome) {
+    match outcome {
+        RevocationOutcome::Attempted { endpoint, results } => {
+            for (kind, result) in results {
+                match result {
+                    Ok(()) => println!("Revoked {kind} at {endpoint}."),
+                    Err(e) => eprintln!("Failed to revoke {kind} at {endpoint}: {e}"),
+                }
+            }
+        }
+        RevocationOutcome::NotAdvertised => {
+            println!("Authorization server advertises no revocation endpoint; tokens were only deleted locally.");
+        }
+        RevocationOutcome::Skipped(reason) => {
+            eprintln!("Skipping token revocation ({reason}); tokens were only deleted locally.");
+        }
+    }
+}
+
+/// Whether a stored entry still belongs to a configured server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum EntryState {
+    /// The server is configured and uses this store.
+    Configured,
+    /// The server is configured but now uses a different store; this entry is never read.
+    Unused,
+    /// The server is no longer under `[mcp.servers]`.
+    Orphaned,
+}
+
+impl EntryState {
+    fn as_str(self) -> &'static str {
+        match self {
+            EntryState::Configured => "configured",
+            EntryState::Unused => "unused",
+            EntryState::Orphaned => "orphaned",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ListedEntry {
+    server: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+    store: CredentialBackend,
+    location: String,
+    state: EntryState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    granted_scopes: Option<Vec<String>>,
+    /// Seconds until the access token expires (negative once expired).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_in: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pruned: bool,
+}
+
+/// Distinct stores referenced by config, the `[mcp]` default first.
+fn stores_in_use(layout: &config::CredentialStoreLayout) -> Vec<config::CredentialStoreSettings> {
+    let mut stores = vec![layout.default.clone()];
+    for settings in layout.servers.values() {
+        if !stores.contains(settings) {
+            stores.push(settings.clone());
+        }
+    }
+    stores
+}
+
+fn entry_state(
+    layout: &config::CredentialStoreLayout,
+    server: &str,
+    store: &config::CredentialStoreSettings,
+) -> EntryState {
+    match layout.servers.get(server) {
+        Some(settings) if settings == store => EntryState::Configured,
+        Some(_) => EntryState::Unused,
+        None => EntryState::Orphaned,
+    }
+}
+
+/// Where `list` finds and opens entries: the configured backends, or an in-memory map in tests.
+#[async_trait]
+trait StoreCatalog {
+    type Store: CredentialStore;
+
+    async fn accounts(&self, settings: &config::CredentialStoreSettings) -> Result<Vec<String>, StoreError>;
+
+    fn open(&self, settings: &config::CredentialStoreSettings, key: &str) -> Self::Store;
+
+    fn location(&self, store: &Self::Store) -> String;
+}
+
+struct ConfiguredStores;
+
+#[async_trait]
+impl StoreCatalog for ConfiguredStores {
+    type Store = ConfiguredCredentialStore;
+
+    async fn accounts(&self, settings: &config::CredentialStoreSettings) -> Result<Vec<String>, StoreError> {
+        list_accounts(settings).await
+    }
+
+    fn open(&self, settings: &config::CredentialStoreSettings, key: &str) -> ConfiguredCredentialStore {
+        ConfiguredCredentialStore::new(settings, key)
+    }
+
+    fn location(&self, store: &ConfiguredCredentialStore) -> String {
+        store.location()
+    }
+}
+
+async fn list(args: &ListArgs) -> Result<(), AuthError> {
+    let layout = config::load_credential_store_layout()?;
+    let now = crate::oauth::refresh::now_epoch_secs();
+    let entries = collect_entries(&ConfiguredStores, &layout, now, args.prune).await?;
+
+    match args.format {
+        StatusFormat::Json => {
+            let json = serde_json::to_string_pretty(&entries).unwrap_or_else(|e| format!("<failed to serialize: {e}>"));
+            println!("{json}");
+        }
+        StatusFormat::Text => print_list(&entries),
+    }
+    Ok(())
+}
+
+/// Every entry in the stores `layout` references. With `prune`, entries whose server is no
+/// longer configured are deleted; entries of configured servers are never touched.
+async fn collect_entries<C: StoreCatalog>(
+    catalog: &C,
+    layout: &config::CredentialStoreLayout,
+    now: u64,
+    prune: bool,
+) -> Result<Vec<ListedEntry>, AuthError> {
+    let mut entries = Vec::new();
+    for settings in stores_in_use(layout) {
+        let accounts = match catalog.accounts(&settings).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                eprintln!("Cannot enumerate the `{}` credential store: {e}", settings.backend);
+                continue;
+            }
+        };
+        for key in accounts {
+            let store = catalog.open(&settings, &key);
+            let (server, account) = split_credential_key(&key);
+            let state = entry_state(layout, &server, &settings);
+            let mut entry = ListedEntry {
+                server,
+                account: account.map(str::to_string),
+                location: catalog.location(&store),
+                store: settings.backend,
+                state,
+                granted_scopes: None,
+                expires_in: None,
+                error: None,
+                pruned: false,
+            };
+            match store.load().await {
+                Ok(Some(stored)) => {
+                    entry.granted_scopes = Some(stored.granted_scopes.into_iter().filter(|s| !s.is_empty()).collect());
+                    entry.expires_in = stored
+                        .token_response
+                        .as_ref()
+                        .and_then(|t| t.expires_in())
+                        .zip(stored.token_received_at)
+                        .map(|(lifetime, received_at)| (received_at + lifetime.as_secs()) as i64 - now as i64);
+                }
+                Ok(None) => continue,
+                Err(e) => entry.error = Some(e.to_string()),
+            }
+            if prune && state == EntryState::Orphaned {
+                store.clear().await?;
+                entry.pruned = true;
+            }
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn print_list(entries: &[ListedEntry]) {
+    if entries.is_empty() {
+        println!("No stored OAuth credentials.");
+        return;
+    }
+    let width = entries
+        .iter()
+        .map(|e| e.server.len())
+        .max()
+        .unwrap_or(0)
+        .max("SERVER".len());
+    let account_width = entries
+        .iter()
+        .filter_map(|e| e.account.as_ref().map(String::len))
+        .max()
+        .unwrap_or(0)
+        .max("ACCOUNT".len());
+    println!(
+        "{:<width$}  {:<account_width$}  {:<14}  {:<10}  {:<18}  SCOPES",
+        "SERVER", "ACCOUNT", "STORE", "STATE", "EXPIRES"
+    );
+    for entry in entries {
+        let expires = match (&entry.error, entry.expires_in) {
+            (Some(_), _) => "<unreadable>".to_string(),
+            (None, Some(secs)) if secs > 0 => format!("in {}", format_duration(secs as u64)),
+            (None, Some(secs)) => format!("expired {} ago", format_duration(secs.unsigned_abs())),
+            (None, None) => "-".to_string(),
+        };
+        let state = if entry.pruned { "pruned" } else { entry.state.as_str() };
+        let scopes = match &entry.granted_scopes {
+            Some(scopes) if !scopes.is_empty() => scopes.join(" "),
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<width$}  {:<account_width$}  {:<14}  {:<10}  {:<18}  {scopes}",
+            entry.server,
+            entry.account.as_deref().unwrap_or("-"),
+            entry.store.as_str(),
+            state,
+            expires
+        );
+        if let Some(e) = &entry.error {
+            println!("{:<width$}  ! {e}", "");
+        }
+    }
+}
+
+async fn refresh(args: &RefreshArgs) -> Result<(), AuthError> {
+    let server = args.server.trim();
+    let account = selected_account(server, &args.account)?;
+    let outcome = refresh_credentials(server, account.as_deref()).await?;
+
+    let refresh_secret = |stored: &StoredCredentials| {
+        stored
+            .token_response
+            .as_ref()
+            .and_then(|t| t.refresh_token().map(|r| r.secret().clone()))
+    };
+    let rotated = refresh_secret(&outcome.previous) != refresh_secret(&outcome.current);
+    let expires = outcome
+        .current
+        .token_response
+        .as_ref()
+        .and_then(|t| t.expires_in())
+        .map(|d| format!("expires in {}", format_duration(d.as_secs())))
+        .unwrap_or_else(|| "expiry not advertised".to_string());
+
+    println!(
+        "Refreshed OAuth access token for {} ({expires}; refresh token {}).",
+        describe_target(server, account.as_deref()),
+        if rotated { "rotated" } else { "unchanged" }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::config::{CredentialFileSettings, CredentialStoreLayout, CredentialStoreSettings, PassphraseSource};
+
+    /// Entries keyed by (backend, credential key); `None` stands for an entry that cannot be read.
+    type Entries = Arc<Mutex<BTreeMap<(&'static str, String), Option<StoredCredentials>>>>;
+
+    #[derive(Default)]
+    struct MemoryCatalog {
+        entries: Entries,
+    }
+
+    impl MemoryCatalog {
+        fn insert(&self, backend: CredentialBackend, key: &str, stored: Option<StoredCredentials>) {
+            self.entries
+                .lock()
+                .unwrap()
+                .insert((backend.as_str(), key.to_string()), stored);
+        }
+
+        fn keys(&self, backend: CredentialBackend) -> Vec<String> {
+            self.entries
+                .lock()
+                .unwrap()
+                .keys()
+                .filter(|(b, _)| *b == backend.as_str())
+                .map(|(_, key)| key.clone())
+                .collect()
+        }
+    }
+
+    struct MemoryStore {
+        entries: Entries,
+        id: (&'static str, String),
+    }
+
+    #[async_trait]
+    impl CredentialStore for MemoryStore {
+        async fn load(&self) -> Result<Option<StoredCredentials>, StoreError> {
+            match self.entries.lock().unwrap().get(&self.id) {
+                Some(Some(stored)) => Ok(Some(stored.clone())),
+                Some(None) => Err(StoreError::InternalError("cannot decrypt".to_string())),
+                None => Ok(None),
+            }
+        }
+
+        async fn save(&self, credentials: StoredCredentials) -> Result<(), StoreError> {
+            self.entries.lock().unwrap().insert(self.id.clone(), Some(credentials));
+            Ok(())
+        }
+
+        async fn clear(&self) -> Result<(), StoreError> {
+            self.entries.lock().unwrap().remove(&self.id);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl StoreCatalog for MemoryCatalog {
+        type Store = MemoryStore;
+
+        async fn accounts(&self, settings: &CredentialStoreSettings) -> Result<Vec<String>, StoreError> {
+            Ok(self.keys(settings.backend))
+        }
+
+        fn open(&self, settings: &CredentialStoreSettings, key: &str) -> MemoryStore {
+            MemoryStore {
+                entries: self.entries.clone(),
+                id: (settings.backend.as_str(), key.to_string()),
+            }
+        }
+
+        fn location(&self, store: &MemoryStore) -> String {
+            format!("{}:{}", store.id.0, store.id.1)
+        }
+    }
+
+    const NOW: u64 = 10_000;
+
+    fn settings(backend: CredentialBackend) -> CredentialStoreSettings {
+        CredentialStoreSettings {
+            backend,
+            file: CredentialFileSettings {
+                dir: PathBuf::from("/nonexistent"),
+                passphrase: PassphraseSource::Env("TRG_CREDENTIALS_PASSPHRASE".to_string()),
+            },
+        }
+    }
+
+    fn layout(servers: &[(&str, CredentialBackend)]) -> CredentialStoreLayout {
+        CredentialStoreLayout {
+            default: settings(CredentialBackend::Keychain),
+            servers: servers
+                .iter()
+                .map(|(name, backend)| (name.to_string(), settings(*backend)))
+                .collect(),
+        }
+    }
+
+    /// Credentials for a one-hour token received at `received_at`.
+    fn stored(received_at: u64) -> Option<StoredCredentials> {
+        let token: OAuthTokenResponse = serde_json::from_value(json!({
+            "access_token": "a",
+            "refresh_token": "r",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+        }))
+        .unwrap();
+        Some(StoredCredentials::new(
+            "c".to_string(),
+            Some(token),
+            vec!["read".to_string()],
+            Some(received_at),
+        ))
+    }
+
+    fn find<'a>(entries: &'a [ListedEntry], server: &str, account: Option<&str>) -> &'a ListedEntry {
+        entries
+            .iter()
+            .find(|e| e.server == server && e.account.as_deref() == account)
+            .unwrap_or_else(|| panic!("no entry for {server} {account:?}"))
+    }
+
+    #[tokio::test]
+    async fn lists_entries_across_backends_with_their_state() {
+        use CredentialBackend::{EncryptedFile, Keychain};
+        let catalog = MemoryCatalog::default();
+        catalog.insert(Keychain, "linear", stored(NOW));
+        catalog.insert(Keychain, "notion", stored(NOW));
+        catalog.insert(EncryptedFile, "notion", stored(0));
+        catalog.insert(EncryptedFile, "gone#work", stored(NOW));
+        let layout = layout(&[("linear", Keychain), ("notion", EncryptedFile)]);
+
+        let entries = collect_entries(&catalog, &layout, NOW, false).await.unwrap();
+
+        assert_eq!(entries.len(), 4);
+        let linear = find(&entries, "linear", None);
+        assert_eq!(linear.state, EntryState::Configured);
+        assert_eq!(linear.expires_in, Some(3600));
+        assert_eq!(linear.granted_scopes.as_deref(), Some(&["read".to_string()][..]));
+        let notion = entries
+            .iter()
+            .find(|e| e.server == "notion" && e.store == EncryptedFile)
+            .unwrap();
+        assert_eq!(notion.state, EntryState::Configured);
+        assert_eq!(notion.expires_in, Some(3600 - NOW as i64));
+        let stale = entries
+            .iter()
+            .find(|e| e.server == "notion" && e.store == Keychain)
+            .unwrap();
+        assert_eq!(stale.state, EntryState::Unused);
+        let gone = find(&entries, "gone", Some("work"));
+        assert_eq!(gone.state, EntryState::Orphaned);
+        assert!(entries.iter().all(|e| !e.pruned));
+        assert_eq!(catalog.keys(Keychain).len() + catalog.keys(EncryptedFile).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn prune_deletes_only_orphaned_entries() {
+        use CredentialBackend::{EncryptedFile, Keychain};
+        let catalog = MemoryCatalog::default();
+        // Configured, including an expired token and an unreadable entry: never pruned.
+        catalog.insert(Keychain, "linear", stored(0));
+        catalog.insert(Keychain, "linear#work", None);
+        catalog.insert(EncryptedFile, "notion", stored(NOW));
+        // Left behind after `notion` moved to the encrypted file: listed, but kept.
+        catalog.insert(Keychain, "notion", stored(NOW));
+        // Servers removed from config, readable or not, expired or not.
+        catalog.insert(Keychain, "gone", stored(0));
+        catalog.insert(Keychain, "gone#work", None);
+        catalog.insert(Keychain, "removed", stored(NOW));
+        let layout = layout(&[("linear", Keychain), ("notion", EncryptedFile)]);
+
+        let entries = collect_entries(&catalog, &layout, NOW, true).await.unwrap();
+
+        let pruned: Vec<_> = entries
+            .iter()
+            .filter(|e| e.pruned)
+            .map(|e| (e.server.as_str(), e.account.as_deref()))
+            .collect();
+        assert_eq!(pruned, vec![("gone", None), ("gone", Some("work")), ("removed", None)]);
+        assert!(entries
+            .iter()
+            .filter(|e| e.pruned)
+            .all(|e| e.state == EntryState::Orphaned));
+        assert!(find(&entries, "linear", Some("work")).error.is_some());
+        assert_eq!(catalog.keys(Keychain), vec!["linear", "linear#work", "notion"]);
+        assert_eq!(catalog.keys(EncryptedFile), vec!["notion"]);
+    }
+
+    #[tokio::test]
+    async fn prune_never_touches_a_configured_server() {
+        use CredentialBackend::Keychain;
+        let catalog = MemoryCatalog::default();
+        catalog.insert(Keychain, "linear", stored(0));
+        catalog.insert(Keychain, "linear#personal", stored(NOW));
+        let layout = layout(&[("linear", Keychain)]);
+
+        let entries = collect_entries(&catalog, &layout, NOW, true).await.unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| !e.pruned && e.state == EntryState::Configured));
+        assert_eq!(catalog.keys(Keychain), vec!["linear", "linear#personal"]);
+    }
+
+    #[tokio::test]
+    async fn logout_revokes_stored_tokens_before_clearing() {
+        let catalog = MemoryCatalog::default();
+        catalog.insert(CredentialBackend::Keychain, "linear", stored(NOW));
+        let store = catalog.open(&settings(CredentialBackend::Keychain), "linear");
+        let revoked = Arc::new(Mutex::new(None));
+
+        let seen = revoked.clone();
+        revoke_and_clear(&store, true, |stored| async move {
+            *seen.lock().unwrap() = Some(stored.client_id);
+            RevocationOutcome::NotAdvertised
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(revoked.lock().unwrap().as_deref(), Some("c"));
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn logout_skips_revocation_when_disabled_or_unreadable() {
+        let catalog = MemoryCatalog::default();
+        catalog.insert(CredentialBackend::Keychain, "linear", stored(NOW));
+        catalog.insert(CredentialBackend::Keychain, "notion", None);
+        let called = Arc::new(AtomicBool::new(false));
+
+        for key in ["linear", "notion", "missing"] {
+            let store = catalog.open(&settings(CredentialBackend::Keychain), key);
+            let revoke_tokens = key != "linear";
+            let called = called.clone();
+            revoke_and_clear(&store, revoke_tokens, |_| async move {
+                called.store(true, Ordering::SeqCst);
+                RevocationOutcome::NotAdvertised
+            })
+            .await
+            .unwrap();
+        }
+
+        assert!(!called.load(Ordering::SeqCst));
+        assert!(catalog.keys(CredentialBackend::Keychain).is_empty());
+    }
+
+    #[test]
+    fn first_login_requests_command_line_scopes() {
+        let mut oauth = OAuthSettings {
+            scopes: vec!["read".to_string()],
+            ..OAuthSettings::default()
+        };
+        let args = vec!["write".to_string(), "read".to_string()];
+        request_scopes(&mut oauth, &args);
+        assert_eq!(oauth.scopes, ["read", "write"]);
+        // The first authorization records what it requested as the grant, so no step-up follows.
+        assert!(missing_scopes(&oauth.scopes, &args).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+mod audit;
 mod auth;
 mod proxy;
 
@@ -5,6 +6,7 @@ pub use proxy::ProxyArgs;
 
 use clap::Subcommand;
 
+use audit::AuditArgs;
 use auth::AuthCommands;
 use proxy::{run_mcp_daemon, ProxyError};
 
@@ -13,9 +15,12 @@ pub enum McpCommands {
     /// Bridge stdio JSON-RPC MCP to a configured remote MCP endpoint over HTTP.
     Proxy(ProxyArgs),
 
-    /// Manage OAuth credentials stored for MCP servers (macOS Keychain).
+    /// Manage OAuth credentials stored for MCP servers (Keychain, Secret Service, or encrypted file).
     #[command(subcommand)]
     Auth(AuthCommands),
+
+    /// Query the audit log of proxied tool calls (servers with an `[mcp.servers.<name>.audit]` table).
+    Audit(AuditArgs),
 }
 
 impl McpCommands {
@@ -26,6 +31,7 @@ impl McpCommands {
                 Err(e) => emit_proxy_err(e),
             },
             McpCommands::Auth(cmd) => cmd.handle().await,
+            McpCommands::Audit(args) => args.handle(),
         }
     }
 }
@@ -0,0 +1,346 @@
+//! Audit sink for `tools/call` traffic (`[mcp.servers.<name>.audit]`).
+//!
+//! A call is noted when the host sends it and written out as one [`AuditRecord`] line once
+//! the host receives its answer, whoever produced it: the remote, the schema check, the
+//! limiter, or failover. Writes are best effort. A full disk must not take the bridge
+//! down, so failures are logged to `trg.log` and the call goes ahead.
+
+use std::{
+    collections::HashMap,
+    fs::{self, DirBuilder, OpenOptions},
+    io::{self, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::{SecondsFormat, Utc};
+use regex::Regex;
+use rmcp::{
+    model::{ClientRequest, JsonRpcMessage, RequestId, ServerResult},
+    service::{RxJsonRpcMessage, TxJsonRpcMessage},
+    RoleServer,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{
+    agentskills::redact::redact_secrets_counting,
+    commands::mcp::audit::{audit_file, AuditOutcome, AuditRecord},
+    config::{AuditArguments, AuditSettings, ResolvedMcpServer},
+    encoding::hex_encode,
+    fs::{lock_rotation, rotate_numbered},
+};
+
+/// Longest error message kept in a record.
+const MAX_ERROR_MESSAGE: usize = 500;
+
+struct Pending {
+    ts: String,
+    tool: String,
+    arguments_sha256: Option<String>,
+    arguments: Option<Value>,
+    started: Instant,
+}
+
+pub(super) struct AuditLog {
+    server: String,
+    account: Option<String>,
+    settings: AuditSettings,
+    /// `redact_patterns`, applied with the built-in detectors when `arguments = "redact"`.
+    patterns: Vec<Regex>,
+    path: PathBuf,
+    pending: HashMap<RequestId, Pending>,
+}
+
+impl AuditLog {
+    /// `None` unless the server has an `[mcp.servers.<name>.audit]` table.
+    pub(super) fn for_server(profile: &ResolvedMcpServer, server_name: &str) -> Option<Self> {
+        let settings = profile.audit.clone()?;
+        Some(Self {
+            server: server_name.to_string(),
+            account: profile.account.clone(),
+            path: audit_file(&settings.dir, server_name),
+            settings,
+            patterns: profile.redact_patterns.clone(),
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Note a `tools/call` from the host; other messages are ignored.
+    pub(super) fn request(&mut self, msg: &RxJsonRpcMessage<RoleServer>) {
+        let JsonRpcMessage::Request(request) = msg else {
+            return;
+        };
+        let ClientRequest::CallToolRequest(call) = &request.request else {
+            return;
+        };
+        let arguments = Value::Object(call.params.arguments.clone().unwrap_or_default());
+        let (arguments_sha256, arguments) = match self.settings.arguments {
+            AuditArguments::Hash => (Some(hex_encode(Sha256::digest(arguments.to_string().as_bytes()))), None),
+            AuditArguments::Redact => (None, Some(self.redacted(arguments))),
+            AuditArguments::Omit => (None, None),
+        };
+        self.pending.insert(
+            request.id.clone(),
+            Pending {
+                ts: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                tool: call.params.name.to_string(),
+                arguments_sha256,
+                arguments,
+                started: Instant::now(),
+            },
+        );
+    }
+
+    /// Write the record for the call `msg` answers, if it answers one.
+    pub(super) fn answered(&mut self, msg: &TxJsonRpcMessage<RoleServer>) {
+        let (id, outcome, error_code, error_message) = match msg {
+            JsonRpcMessage::Response(response) => {
+                let outcome = match &response.result {
+                    ServerResult::CallToolResult(result) if result.is_error == Some(true) => AuditOutcome::ToolError,
+                    _ => AuditOutcome::Ok,
+                };
+                (&response.id, outcome, None, None)
+            }
+            JsonRpcMessage::Error(error) => {
+                let Some(id) = &error.id else {
+                    return;
+                };
+                let (message, _) = redact_secrets_counting(&error.error.message, &self.patterns);
+                let message = message.chars().take(MAX_ERROR_MESSAGE).collect();
+                (id, AuditOutcome::Error, Some(error.error.code.0), Some(message))
+            }
+            _ => return,
+        };
+        if let Some(call) = self.pending.remove(id) {
+            self.write(id.clone(), call, outcome, error_code, error_message);
+        }
+    }
+
+    /// Record every call still waiting; runs when the bridge exits.
+    fn finish(&mut self) {
+        for (id, call) in std::mem::take(&mut self.pending) {
+            self.write(id, call, AuditOutcome::Unanswered, None, None);
+        }
+    }
+
+    fn redacted(&self, mut value: Value) -> Value {
+        fn walk(value: &mut Value, patterns: &[Regex]) {
+            match value {
+                Value::String(text) => *text = redact_secrets_counting(text, patterns).0,
+                Value::Array(items) => items.iter_mut().for_each(|v| walk(v, patterns)),
+                Value::Object(map) => map.values_mut().for_each(|v| walk(v, patterns)),
+                _ => {}
+            }
+        }
+        walk(&mut value, &self.patterns);
+        value
+    }
+
+    fn write(
+        &self,
+        id: RequestId,
+        call: Pending,
+        outcome: AuditOutcome,
+        error_code: Option<i32>,
+        error_message: Option<String>,
+    ) {
+        let record = AuditRecord {
+            ts: call.ts,
+            server: self.server.clone(),
+            account: self.account.clone(),
+            tool: call.tool,
+            id,
+            arguments_sha256: call.arguments_sha256,
+            arguments: call.arguments,
+            outcome,
+            error_code,
+            error_message,
+            duration_ms: u64::try_from(call.started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            pid: std::process::id(),
+        };
+        let result = serde_json::to_string(&record)
+            .map_err(io::Error::other)
+            .and_then(|line| append_line(&self.path, &line, &self.settings));
+        if let Err(e) = result {
+            warn!(
+                server = %self.server,
+                path = %self.path.display(),
+                error = %e,
+                "audit: failed to write record"
+            );
+        }
+    }
+}
+
+impl Drop for AuditLog {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Append `line` to `path`, rotating first when it would push the file past `max_file_size`.
+fn append_line(path: &Path, line: &str, settings: &AuditSettings) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    }
+    let due = || -> io::Result<bool> {
+        let len = match fs::metadata(path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        Ok(len > 0 && len + line.len() as u64 + 1 > settings.max_file_size)
+    };
+    if due()? {
+        // As for `trg.log`: proxies sharing the file decide again under the lock, so only
+        // the first renames it.
+        let _lock = lock_rotation(path)?;
+        if due()? {
+            rotate_numbered(path, settings.max_files)?;
+        }
+    }
+    let mut file = OpenOptions::new().create(true).append(true).mode(0o600).open(path)?;
+    // One write per record so concurrent proxies appending to the same file never interleave lines.
+    file.write_all(format!("{line}\n").as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::fs::rotated_path;
+
+    fn log(dir: &Path, arguments: AuditArguments, max_file_size: u64) -> AuditLog {
+        AuditLog {
+            server: "linear".into(),
+            account: Some("work".into()),
+            settings: AuditSettings {
+                arguments,
+                dir: dir.to_path_buf(),
+                max_file_size,
+                max_files: 2,
+            },
+            patterns: vec![Regex::new(r"password=\S+").unwrap()],
+            path: audit_file(dir, "linear"),
+            pending: HashMap::new(),
+        }
+    }
+
+    fn call(id: i64, arguments: Value) -> RxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": id, "method": "tools/call",
+            "params": {"name": "search", "arguments": arguments}
+        }))
+        .unwrap()
+    }
+
+    fn reply(value: Value) -> TxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn records(path: &Path) -> Vec<AuditRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn records_outcomes_with_hashed_arguments() {
+        let dir = tempdir().unwrap();
+        let mut audit = log(dir.path(), AuditArguments::Hash, u64::MAX);
+
+        audit.request(&call(1, json!({"q": "a"})));
+        audit.request(&call(2, json!({"q": "b"})));
+        audit.request(&call(3, json!({"q": "c"})));
+        audit.answered(&reply(json!({
+            "jsonrpc": "2.0", "id": 1, "result": {"content": [{"type": "text", "text": "ok"}]}
+        })));
+        audit.answered(&reply(json!({
+            "jsonrpc": "2.0", "id": 2, "error": {"code": -32602, "message": "bad password=hunter2"}
+        })));
+        audit.finish();
+
+        let written = records(&audit.path);
+        assert_eq!(written.len(), 3);
+        assert_eq!(written[0].outcome, AuditOutcome::Ok);
+        assert_eq!(written[0].account.as_deref(), Some("work"));
+        assert_eq!(
+            written[0].arguments_sha256.as_deref(),
+            Some(hex_encode(Sha256::digest(br#"{"q":"a"}"#)).as_str())
+        );
+        assert!(written[0].arguments.is_none());
+        assert_eq!(written[1].outcome, AuditOutcome::Error);
+        assert_eq!(written[1].error_code, Some(-32602));
+        assert_eq!(written[1].error_message.as_deref(), Some("bad <redacted>"));
+        assert_eq!(written[2].outcome, AuditOutcome::Unanswered);
+    }
+
+    #[test]
+    fn redact_mode_keeps_scrubbed_arguments() {
+        let dir = tempdir().unwrap();
+        let mut audit = log(dir.path(), AuditArguments::Redact, u64::MAX);
+        audit.request(&call(1, json!({"q": "x", "auth": {"pw": "password=hunter2"}})));
+        audit.answered(&reply(json!({
+            "jsonrpc": "2.0", "id": 1, "result": {"content": [], "isError": true}
+        })));
+
+        let written = records(&audit.path);
+        assert_eq!(written[0].outcome, AuditOutcome::ToolError);
+        assert_eq!(
+            written[0].arguments,
+            Some(json!({"q": "x", "auth": {"pw": "<redacted>"}}))
+        );
+        assert!(written[0].arguments_sha256.is_none());
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = tempdir().unwrap();
+        let mut audit = log(dir.path(), AuditArguments::Omit, 1);
+        for id in 1..=4 {
+            audit.request(&call(id, json!({})));
+            audit.finish();
+        }
+
+        let path = &audit.path;
+        let id = |p: &Path| records(p)[0].id.clone();
+        assert_eq!(id(path), RequestId::Number(4));
+        assert_eq!(id(&rotated_path(path, 1)), RequestId::Number(3));
+        assert_eq!(id(&rotated_path(path, 2)), RequestId::Number(2));
+        assert!(!rotated_path(path, 3).exists());
+        let mode = fs::metadata(path).unwrap().permissions();
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&mode) & 0o777, 0o600);
+    }
+
+    #[test]
+    fn concurrent_proxies_rotate_once() {
+        let dir = tempdir().unwrap();
+        let settings = log(dir.path(), AuditArguments::Omit, 16).settings.clone();
+        let path = audit_file(dir.path(), "linear");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "old record\n").unwrap();
+
+        // Another proxy is mid-rotation: this one finds the file due and waits for the lock.
+        let lock = lock_rotation(&path).unwrap();
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || append_line(&path, "second", &settings).unwrap())
+        };
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        rotate_numbered(&path, 2).unwrap();
+        fs::write(&path, "first\n").unwrap();
+        drop(lock);
+        writer.join().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "old record\n");
+        assert!(!rotated_path(&path, 2).exists());
+    }
+}
@@ -0,0 +1,370 @@
+//! Response cache for idempotent MCP reads (`[mcp.servers.<name>.cache]`).
+//!
+//! Requests for a configured method are keyed by method plus a SHA-256 of their params
+//! (minus `_meta`, which carries per-call progress tokens). A fresh entry is answered
+//! locally under the host's request id; a miss is forwarded and the remote's result
+//! stored when it comes back. `notifications/*/list_changed` (and `resources/updated`)
+//! drop the affected methods before being passed on to the host.
+//!
+//! With `disk = true` each entry is also written as `0600` JSON inside a `0700`
+//! per-server directory, so a restarted proxy starts warm. Disk errors are logged and
+//! otherwise ignored: the cache is an optimisation, never a reason to fail a request.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rmcp::{
+    model::{JsonRpcMessage, RequestId, ServerNotification, ServerResult},
+    service::{RxJsonRpcMessage, TxJsonRpcMessage},
+    RoleServer,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::{CacheSettings, ResolvedMcpServer},
+    encoding::hex_encode,
+    oauth::{refresh::now_epoch_secs, store::credential_key},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    method: String,
+    stored_at: u64,
+    result: Value,
+}
+
+pub(super) struct ResponseCache {
+    server: String,
+    ttls: BTreeMap<String, Duration>,
+    dir: Option<PathBuf>,
+    entries: HashMap<String, Entry>,
+    /// Cacheable requests sent upstream and awaiting the remote's answer: request id -> cache
+    /// key. Only filled by [`ResponseCache::forwarded`], so requests answered locally (schema
+    /// rejections, throttling errors, cache hits) never linger here.
+    pending: HashMap<RequestId, String>,
+}
+
+impl ResponseCache {
+    /// `None` when the server configures no cacheable methods.
+    pub(super) fn for_server(profile: &ResolvedMcpServer, server_name: &str) -> Option<Self> {
+        let CacheSettings { ttls, dir } = &profile.cache;
+        if ttls.is_empty() {
+            return None;
+        }
+        let key = credential_key(server_name, profile.account.as_deref());
+        Some(Self::new(
+            server_name,
+            ttls.clone(),
+            dir.as_ref().map(|root| root.join(server_dir_name(&key))),
+        ))
+    }
+
+    fn new(server: &str, ttls: BTreeMap<String, Duration>, dir: Option<PathBuf>) -> Self {
+        Self {
+            server: server.to_string(),
+            ttls,
+            dir,
+            entries: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Answer a host request from the cache.
+    pub(super) fn lookup(&mut self, msg: &RxJsonRpcMessage<RoleServer>) -> Option<TxJsonRpcMessage<RoleServer>> {
+        self.lookup_at(msg, now_epoch_secs())
+    }
+
+    fn lookup_at(&mut self, msg: &RxJsonRpcMessage<RoleServer>, now: u64) -> Option<TxJsonRpcMessage<RoleServer>> {
+        let JsonRpcMessage::Request(request) = msg else {
+            return None;
+        };
+        let method = request.request.method();
+        let ttl = *self.ttls.get(method)?;
+        let key = cache_key(method, &serde_json::to_value(&request.request).ok()?);
+
+        if let Some(entry) = self.fresh_entry(&key, ttl, now).cloned() {
+            match serde_json::from_value::<ServerResult>(entry.result.clone()) {
+                Ok(result) => {
+                    debug!(server = %self.server, method, age = now - entry.stored_at, "cache: hit");
+                    return Some(JsonRpcMessage::response(result, request.id.clone()));
+                }
+                Err(e) => {
+                    warn!(server = %self.server, method, error = %e, "cache: dropping undecodable entry");
+                    self.remove(&key);
+                }
+            }
+        }
+        debug!(server = %self.server, method, "cache: miss");
+        None
+    }
+
+    /// Remember a request that went upstream so its result gets stored.
+    pub(super) fn forwarded(&mut self, msg: &RxJsonRpcMessage<RoleServer>) {
+        let JsonRpcMessage::Request(request) = msg else {
+            return;
+        };
+        let method = request.request.method();
+        if !self.ttls.contains_key(method) {
+            return;
+        }
+        if let Ok(params) = serde_json::to_value(&request.request) {
+            self.pending.insert(request.id.clone(), cache_key(method, &params));
+        }
+    }
+
+    /// Store results for pending misses and apply invalidation notifications.
+    pub(super) fn observe(&mut self, msg: &TxJsonRpcMessage<RoleServer>) {
+        self.observe_at(msg, now_epoch_secs());
+    }
+
+    fn observe_at(&mut self, msg: &TxJsonRpcMessage<RoleServer>, now: u64) {
+        match msg {
+            JsonRpcMessage::Response(response) => {
+                let Some(key) = self.pending.remove(&response.id) else {
+                    return;
+                };
+                let Ok(result) = serde_json::to_value(&response.result) else {
+                    return;
+                };
+                let method = key.split_once(' ').map_or(key.as_str(), |(m, _)| m).to_string();
+                self.insert(
+                    key,
+                    Entry {
+                        method,
+                        stored_at: now,
+                        result,
+                    },
+                );
+            }
+            JsonRpcMessage::Error(error) => {
+                if let Some(id) = &error.id {
+                    self.pending.remove(id);
+                }
+            }
+            JsonRpcMessage::Notification(notification) => {
+                let stale: &[&str] = match &notification.notification {
+                    ServerNotification::ToolListChangedNotification(_) => &["tools/list"],
+                    ServerNotification::PromptListChangedNotification(_) => &["prompts/list"],
+                    ServerNotification::ResourceListChangedNotification(_) => {
+                        &["resources/list", "resources/templates/list", "resources/read"]
+                    }
+                    ServerNotification::ResourceUpdatedNotification(_) => &["resources/read"],
+                    _ => return,
+                };
+                self.invalidate(stale);
+            }
+            JsonRpcMessage::Request(_) => {}
+        }
+    }
+
+    fn fresh_entry(&mut self, key: &str, ttl: Duration, now: u64) -> Option<&Entry> {
+        if !self.entries.contains_key(key) {
+            let entry = self.dir.as_deref().and_then(|dir| read_entry(&entry_path(dir, key)))?;
+            self.entries.insert(key.to_string(), entry);
+        }
+        let expired = self
+            .entries
+            .get(key)
+            .is_some_and(|entry| now.saturating_sub(entry.stored_at) >= ttl.as_secs());
+        if expired {
+            self.remove(key);
+            return None;
+        }
+        self.entries.get(key)
+    }
+
+    fn insert(&mut self, key: String, entry: Entry) {
+        if let Some(dir) = &self.dir {
+            if let Err(e) = write_entry(dir, &entry_path(dir, &key), &entry) {
+                warn!(server = %self.server, error = %e, "cache: could not persist entry");
+            }
+        }
+        self.entries.insert(key, entry);
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+        if let Some(dir) = &self.dir {
+            let _ = fs::remove_file(entry_path(dir, key));
+        }
+    }
+
+    fn invalidate(&mut self, methods: &[&str]) {
+        let before = self.entries.len();
+        self.entries
+            .retain(|_, entry| !methods.contains(&entry.method.as_str()));
+        let dropped = before - self.entries.len();
+        let mut files = 0;
+        if let Some(dir) = &self.dir {
+            for method in methods {
+                let prefix = file_prefix(method);
+                let Ok(listing) = fs::read_dir(dir) else { break };
+                for file in listing.flatten() {
+                    if file.file_name().to_string_lossy().starts_with(&prefix) && fs::remove_file(file.path()).is_ok() {
+                        files += 1;
+                    }
+                }
+            }
+        }
+        info!(server = %self.server, methods = ?methods, dropped, files, "cache: invalidated after list_changed");
+    }
+}
+
+/// `"<method> <sha256 of params>"`; the method prefix lets invalidation match by method.
+fn cache_key(method: &str, request: &Value) -> String {
+    let mut params = request.get("params").cloned().unwrap_or(Value::Null);
+    if let Value::Object(map) = &mut params {
+        map.remove("_meta");
+    }
+    let digest = Sha256::digest(params.to_string().as_bytes());
+    format!("{method} {}", hex_encode(digest))
+}
+
+fn server_dir_name(credential_key: &str) -> String {
+    hex_encode(Sha256::digest(credential_key.as_bytes()))[..16].to_string()
+}
+
+fn file_prefix(method: &str) -> String {
+    format!("{}-", method.replace('/', "_"))
+}
+
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    let (method, digest) = key.split_once(' ').unwrap_or((key, ""));
+    dir.join(format!("{}{digest}.json", file_prefix(method)))
+}
+
+fn read_entry(path: &Path) -> Option<Entry> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn write_entry(dir: &Path, path: &Path, entry: &Entry) -> std::io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("entry");
+    let tmp = dir.join(format!(".{file_name}.{}.tmp", std::process::id()));
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp)?;
+        file.write_all(&serde_json::to_vec(entry)?)?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::{ListToolsResult, NumberOrString, Tool};
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(id: i64, method: &str, params: Value) -> RxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})).unwrap()
+    }
+
+    fn tools_response(id: i64, names: &[&str]) -> TxJsonRpcMessage<RoleServer> {
+        let tools = names
+            .iter()
+            .map(|name| Tool::new(name.to_string(), "test tool", serde_json::Map::new()))
+            .collect();
+        JsonRpcMessage::response(
+            ServerResult::ListToolsResult(ListToolsResult::with_all_items(tools)),
+            NumberOrString::Number(id),
+        )
+    }
+
+    fn notification(method: &str) -> TxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(json!({"jsonrpc": "2.0", "method": method})).unwrap()
+    }
+
+    fn cache(dir: Option<PathBuf>) -> ResponseCache {
+        let ttls = BTreeMap::from([("tools/list".to_string(), Duration::from_secs(60))]);
+        ResponseCache::new("test", ttls, dir)
+    }
+
+    fn reply_id(reply: &TxJsonRpcMessage<RoleServer>) -> Value {
+        serde_json::to_value(reply).unwrap()["id"].clone()
+    }
+
+    #[test]
+    fn serves_fresh_hits_under_the_new_request_id_until_ttl() {
+        let mut cache = cache(None);
+        assert!(cache.lookup_at(&request(1, "tools/list", json!({})), 100).is_none());
+        cache.forwarded(&request(1, "tools/list", json!({})));
+        cache.observe_at(&tools_response(1, &["search"]), 100);
+
+        let hit = cache.lookup_at(&request(7, "tools/list", json!({"_meta": {"progressToken": 3}})), 159);
+        let hit = hit.expect("fresh entry");
+        assert_eq!(reply_id(&hit), json!(7));
+        assert_eq!(
+            serde_json::to_value(&hit).unwrap()["result"]["tools"][0]["name"],
+            "search"
+        );
+
+        assert!(cache.lookup_at(&request(8, "tools/list", json!({})), 160).is_none());
+    }
+
+    #[test]
+    fn keys_on_params_and_skips_unconfigured_methods() {
+        let mut cache = cache(None);
+        cache.lookup_at(&request(1, "tools/list", json!({"cursor": "a"})), 0);
+        cache.forwarded(&request(1, "tools/list", json!({"cursor": "a"})));
+        cache.observe_at(&tools_response(1, &["a"]), 0);
+        assert!(cache
+            .lookup_at(&request(2, "tools/list", json!({"cursor": "b"})), 1)
+            .is_none());
+        assert!(cache.lookup_at(&request(3, "prompts/list", json!({})), 1).is_none());
+        cache.forwarded(&request(3, "prompts/list", json!({})));
+        assert!(!cache.pending.contains_key(&NumberOrString::Number(3)));
+    }
+
+    #[test]
+    fn only_forwarded_requests_are_pending() {
+        let mut cache = cache(None);
+        assert!(cache.lookup_at(&request(1, "tools/list", json!({})), 0).is_none());
+        assert!(cache.pending.is_empty());
+
+        cache.forwarded(&request(2, "tools/list", json!({})));
+        cache.observe_at(&tools_response(2, &["a"]), 0);
+        assert!(cache.lookup_at(&request(3, "tools/list", json!({})), 1).is_some());
+        assert!(cache.pending.is_empty());
+    }
+
+    #[test]
+    fn list_changed_invalidates_memory_and_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = cache(Some(dir.path().join("srv")));
+        cache.forwarded(&request(1, "tools/list", json!({})));
+        cache.observe_at(&tools_response(1, &["a"]), 0);
+        assert_eq!(fs::read_dir(dir.path().join("srv")).unwrap().count(), 1);
+
+        let mut restarted = cache_from(&dir);
+        assert!(restarted.lookup_at(&request(2, "tools/list", json!({})), 1).is_some());
+
+        cache.observe_at(&notification("notifications/tools/list_changed"), 2);
+        assert!(cache.entries.is_empty());
+        assert_eq!(fs::read_dir(dir.path().join("srv")).unwrap().count(), 0);
+        assert!(cache_from(&dir)
+            .lookup_at(&request(3, "tools/list", json!({})), 3)
+            .is_none());
+    }
+
+    fn cache_from(dir: &tempfile::TempDir) -> ResponseCache {
+        cache(Some(dir.path().join("srv")))
+    }
+}
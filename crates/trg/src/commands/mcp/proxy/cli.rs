@@ -6,4 +6,8 @@ pub struct ProxyArgs {
     /// Select `[mcp.servers.<name>]` from `trg`'s configuration file (required).
     #[arg(long)]
     pub server: String,
+
+    /// Stored OAuth login to use (`trg mcp auth login --account`); overrides `account` in config.
+    #[arg(long)]
+    pub account: Option<String>,
 }
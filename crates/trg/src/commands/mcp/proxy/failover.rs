@@ -0,0 +1,584 @@
+//! Failover across `url` and `failover_urls` for one server.
+//!
+//! The bridge keeps a single [`StreamableHttpClientTransport`] at a time. When sending fails
+//! with a connection error, a timeout, or a 5xx, or when the transport dies under it,
+//! [`Failover::fail_over`] probes the other upstreams in strategy order and connects to the
+//! first healthy one. Then it replays the host's `initialize` request and `initialized`
+//! notification, so the new upstream has a session for the host's next message. The host
+//! never sees the handshake repeat. Requests still in flight on the old upstream cannot be
+//! recovered, so each one gets a JSON-RPC error.
+//!
+//! Under `primary-then-secondary`, [`Failover::probe_primary`] checks `url` every
+//! [`FAILBACK_INTERVAL`] while the proxy runs on an alternate. The probe and the handshake
+//! replay run on a task of their own, so traffic keeps flowing through the alternate; once
+//! the primary is ready, [`Failover::fail_back`] moves over if nothing is in flight.
+//!
+//! Upstream URLs may embed credentials, so logs identify upstreams by index only.
+
+use std::{collections::HashSet, future::Future, sync::Arc, time::Duration};
+
+use rmcp::{
+    model::{ClientNotification, ClientRequest, ErrorCode, ErrorData, JsonRpcMessage, RequestId},
+    service::TxJsonRpcMessage,
+    transport::{
+        streamable_http_client::{
+            StreamableHttpClient, StreamableHttpClientTransport, StreamableHttpClientTransportConfig,
+            StreamableHttpError,
+        },
+        Transport,
+    },
+    RoleClient, RoleServer,
+};
+use secrecy::ExposeSecret;
+use serde_json::json;
+use tokio::{task::JoinHandle, time::Instant};
+use tracing::{debug, info, warn};
+
+use crate::config::{FailoverStrategy, ResolvedMcpServer};
+
+use super::http::HttpError;
+
+/// JSON-RPC error code for requests lost when their upstream failed (implementation-defined server error range).
+const UPSTREAM_FAILED: ErrorCode = ErrorCode(-32030);
+
+/// How long a health probe may take before the upstream counts as down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the replayed `initialize` may take on the new upstream.
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often the primary is probed while the proxy runs on an alternate.
+pub(super) const FAILBACK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The host's half of the MCP handshake, kept for replay on a new upstream.
+#[derive(Clone, Default)]
+struct Handshake {
+    initialize: Option<(RequestId, TxJsonRpcMessage<RoleClient>)>,
+    /// Whether the host already has the `initialize` result.
+    answered: bool,
+    initialized: Option<TxJsonRpcMessage<RoleClient>>,
+}
+
+pub(super) struct Failover<C: StreamableHttpClient> {
+    server: String,
+    client: C,
+    config: StreamableHttpClientTransportConfig,
+    urls: Vec<Arc<str>>,
+    strategy: FailoverStrategy,
+    current: usize,
+    probe: reqwest::Client,
+    handshake: Handshake,
+    /// Requests sent to the current upstream that have not been answered yet.
+    outstanding: HashSet<RequestId>,
+    /// Failovers since the last sign of life from an upstream; bounds the retry loop.
+    consecutive: usize,
+    /// When to next try returning to the primary; set only while away from it and not probing.
+    failback_at: Option<Instant>,
+    /// The running fail-back probe: the primary's transport with the handshake replayed.
+    probing: Option<JoinHandle<Result<Reconnected<C>, String>>>,
+}
+
+/// A transport with the handshake replayed, and the `initialize` result if the host still
+/// waits for it.
+type Reconnected<C> = (StreamableHttpClientTransport<C>, Option<TxJsonRpcMessage<RoleServer>>);
+
+impl<C> Failover<C>
+where
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+{
+    /// `config` is the transport configuration for `url`; alternates reuse it with another URI.
+    pub(super) fn for_server(
+        profile: &ResolvedMcpServer,
+        server_name: &str,
+        client: C,
+        config: StreamableHttpClientTransportConfig,
+    ) -> Self {
+        let urls = std::iter::once(config.uri.clone())
+            .chain(profile.failover_urls.iter().map(|url| url.expose_secret().into()))
+            .collect();
+        let probe = reqwest::Client::builder()
+            .timeout(PROBE_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            server: server_name.to_string(),
+            client,
+            config,
+            urls,
+            strategy: profile.failover,
+            current: 0,
+            probe,
+            handshake: Handshake::default(),
+            outstanding: HashSet::new(),
+            consecutive: 0,
+            failback_at: None,
+            probing: None,
+        }
+    }
+
+    /// Whether there is anywhere to fail over to.
+    pub(super) fn enabled(&self) -> bool {
+        self.urls.len() > 1
+    }
+
+    /// Transport for the primary upstream.
+    pub(super) fn connect_initial(&self) -> StreamableHttpClientTransport<C> {
+        self.connect(0)
+    }
+
+    fn connect(&self, index: usize) -> StreamableHttpClientTransport<C> {
+        connect(&self.client, &self.config, &self.urls[index])
+    }
+
+    /// Record a host message the current upstream accepted.
+    pub(super) fn sent(&mut self, msg: &TxJsonRpcMessage<RoleClient>) {
+        self.consecutive = 0;
+        match msg {
+            JsonRpcMessage::Request(request) => {
+                if matches!(request.request, ClientRequest::InitializeRequest(_)) {
+                    self.handshake = Handshake {
+                        initialize: Some((request.id.clone(), msg.clone())),
+                        ..Handshake::default()
+                    };
+                }
+                self.outstanding.insert(request.id.clone());
+            }
+            JsonRpcMessage::Notification(notification)
+                if matches!(
+                    notification.notification,
+                    ClientNotification::InitializedNotification(_)
+                ) =>
+            {
+                self.handshake.initialized = Some(msg.clone());
+            }
+            _ => {}
+        }
+    }
+
+    /// Note a message from the upstream on its way to the host.
+    pub(super) fn observe(&mut self, msg: &TxJsonRpcMessage<RoleServer>) {
+        self.consecutive = 0;
+        let id = match msg {
+            JsonRpcMessage::Response(response) => &response.id,
+            JsonRpcMessage::Error(error) => match &error.id {
+                Some(id) => id,
+                None => return,
+            },
+            _ => return,
+        };
+        self.outstanding.remove(id);
+        if self.handshake.initialize.as_ref().is_some_and(|(init, _)| init == id) {
+            self.handshake.answered = true;
+        }
+    }
+
+    /// Stop waiting for a request the host cancelled; it no longer holds up a fail-back.
+    pub(super) fn cancelled(&mut self, id: &RequestId) {
+        self.outstanding.remove(id);
+    }
+
+    /// Switch `remote` to the next healthy upstream and replay the handshake on it.
+    ///
+    /// Returns the messages owed to the host: an error for every request lost with the old
+    /// upstream, and the `initialize` result if the host was still waiting for it.
+    pub(super) async fn fail_over(
+        &mut self,
+        remote: &mut StreamableHttpClientTransport<C>,
+        reason: &str,
+    ) -> Result<Vec<TxJsonRpcMessage<RoleServer>>, String> {
+        self.consecutive += 1;
+        if self.consecutive > self.urls.len() {
+            return Err(format!("every upstream failed in turn; last error: {reason}"));
+        }
+        let failed = self.current;
+        warn!(server = %self.server, upstream = failed, error = reason, "failover: upstream failed");
+
+        if let Some(probe) = self.probing.take() {
+            probe.abort();
+        }
+        for index in candidates(self.strategy, self.urls.len(), failed) {
+            let (next, init_reply) = match self.reconnect(index).await {
+                Ok(reconnected) => reconnected,
+                Err(e) => {
+                    warn!(server = %self.server, upstream = index, error = %e, "failover: upstream unavailable");
+                    continue;
+                }
+            };
+            info!(server = %self.server, from = failed, to = index, "failover: switched upstream");
+            // Dropping the old transport cancels its worker; a graceful close would wait on a dead host.
+            *remote = next;
+            let replies = self.lost_requests(reason, init_reply);
+            self.current = index;
+            self.failback_at = (self.strategy == FailoverStrategy::PrimaryThenSecondary && index != 0)
+                .then(|| Instant::now() + FAILBACK_INTERVAL);
+            return Ok(replies);
+        }
+        Err(format!(
+            "no healthy upstream among {}; last error: {reason}",
+            self.urls.len()
+        ))
+    }
+
+    /// When the bridge should call [`Failover::probe_primary`].
+    pub(super) fn next_failback(&self) -> Option<Instant> {
+        self.failback_at
+    }
+
+    /// Start checking whether the primary is back, unless a request is in flight. The check
+    /// runs off the bridge task; its result arrives through [`Failover::primary_probed`].
+    pub(super) fn probe_primary(&mut self) {
+        self.failback_at = None;
+        if self.current == 0 {
+            return;
+        }
+        if !self.outstanding.is_empty() {
+            debug!(server = %self.server, in_flight = self.outstanding.len(), "failover: fail-back deferred");
+            self.failback_at = Some(Instant::now() + FAILBACK_INTERVAL);
+            return;
+        }
+        self.probing = Some(tokio::spawn(self.reconnect(0)));
+    }
+
+    /// Whether a fail-back probe is running.
+    pub(super) fn probing(&self) -> bool {
+        self.probing.is_some()
+    }
+
+    /// The outcome of the running fail-back probe; pending while there is none.
+    pub(super) async fn primary_probed(&mut self) -> Result<Reconnected<C>, String> {
+        let Some(probe) = self.probing.as_mut() else {
+            return std::future::pending().await;
+        };
+        let outcome = probe.await.unwrap_or_else(|e| Err(e.to_string()));
+        self.probing = None;
+        outcome
+    }
+
+    /// Return to the primary the probe reconnected to if no request would be lost by moving.
+    /// Otherwise stay put and probe again after [`FAILBACK_INTERVAL`].
+    pub(super) fn fail_back(
+        &mut self,
+        remote: &mut StreamableHttpClientTransport<C>,
+        probed: Result<Reconnected<C>, String>,
+    ) {
+        self.failback_at = Some(Instant::now() + FAILBACK_INTERVAL);
+        let primary = match probed {
+            Ok((primary, _)) => primary,
+            Err(e) => {
+                debug!(server = %self.server, error = %e, "failover: primary still unavailable");
+                return;
+            }
+        };
+        if !self.outstanding.is_empty() {
+            debug!(server = %self.server, in_flight = self.outstanding.len(), "failover: fail-back deferred");
+            return;
+        }
+        info!(server = %self.server, from = self.current, to = 0, "failover: returned to primary");
+        // As in `fail_over`, dropping the alternate's transport cancels its worker without
+        // holding up the bridge on a graceful close.
+        *remote = primary;
+        self.current = 0;
+        self.failback_at = None;
+    }
+
+    /// Check upstream `index`, connect to it and replay the handshake. The future owns what
+    /// it needs, so the fail-back probe can run on a task of its own.
+    fn reconnect(&self, index: usize) -> impl Future<Output = Result<Reconnected<C>, String>> + Send + 'static {
+        let probe = self.probe.clone();
+        let (client, config, url) = (self.client.clone(), self.config.clone(), self.urls[index].clone());
+        let (server, handshake) = (self.server.clone(), self.handshake.clone());
+        async move {
+            healthy(&probe, &url).await?;
+            let mut remote = connect(&client, &config, &url);
+            let init_reply = handshake
+                .replay(&server, &mut remote)
+                .await
+                .map_err(|e| format!("handshake replay failed: {e}"))?;
+            Ok((remote, init_reply))
+        }
+    }
+
+    fn lost_requests(
+        &mut self,
+        reason: &str,
+        init_reply: Option<TxJsonRpcMessage<RoleServer>>,
+    ) -> Vec<TxJsonRpcMessage<RoleServer>> {
+        let init_id = init_reply
+            .as_ref()
+            .and(self.handshake.initialize.as_ref())
+            .map(|(id, _)| id.clone());
+        let mut replies: Vec<_> = self
+            .outstanding
+            .drain()
+            .filter(|id| Some(id) != init_id.as_ref())
+            .map(|id| {
+                let data = json!({ "server": self.server, "upstream": self.current });
+                JsonRpcMessage::error(
+                    ErrorData::new(
+                        UPSTREAM_FAILED,
+                        format!("upstream failed before answering ({reason}); the request may or may not have run"),
+                        Some(data),
+                    ),
+                    Some(id),
+                )
+            })
+            .collect();
+        replies.extend(init_reply);
+        if let Some(id) = init_id {
+            self.handshake.answered = true;
+            debug!(server = %self.server, id = %id, "failover: initialize answered by new upstream");
+        }
+        replies
+    }
+}
+
+impl Handshake {
+    /// Re-run the handshake on `remote`; the `initialize` result comes back when the host
+    /// has not seen one yet.
+    async fn replay<C>(
+        &self,
+        server: &str,
+        remote: &mut StreamableHttpClientTransport<C>,
+    ) -> Result<Option<TxJsonRpcMessage<RoleServer>>, String>
+    where
+        C: StreamableHttpClient<Error = HttpError> + Send + Sync + 'static,
+    {
+        let Some((id, initialize)) = &self.initialize else {
+            return Ok(None);
+        };
+        remote.send(initialize.clone()).await.map_err(|e| e.to_string())?;
+        let result = tokio::time::timeout(REPLAY_TIMEOUT, async {
+            loop {
+                match remote.receive().await {
+                    None => return Err("transport closed during initialize".to_string()),
+                    Some(msg @ JsonRpcMessage::Response(_)) => return Ok(msg),
+                    Some(JsonRpcMessage::Error(error)) => return Err(error.error.message.to_string()),
+                    Some(_) => {
+                        debug!(server, "failover: dropping message received before initialize result")
+                    }
+                }
+            }
+        })
+        .await
+        .map_err(|_| "timed out waiting for initialize result".to_string())??;
+        if let Some(initialized) = &self.initialized {
+            remote.send(initialized.clone()).await.map_err(|e| e.to_string())?;
+        }
+        if self.answered {
+            return Ok(None);
+        }
+        debug!(server, id = %id, "failover: forwarding replayed initialize result");
+        Ok(Some(result))
+    }
+}
+
+fn connect<C>(
+    client: &C,
+    config: &StreamableHttpClientTransportConfig,
+    url: &Arc<str>,
+) -> StreamableHttpClientTransport<C>
+where
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+{
+    let mut config = config.clone();
+    config.uri = url.clone();
+    StreamableHttpClientTransport::with_client(client.clone(), config)
+}
+
+/// Any HTTP answer below 500 means the upstream is up. MCP endpoints commonly reject a
+/// bare GET with 401, 405 or 406.
+async fn healthy(probe: &reqwest::Client, url: &str) -> Result<(), String> {
+    match probe.get(url).send().await {
+        Ok(response) if response.status().is_server_error() => {
+            Err(format!("health check answered {}", response.status()))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("health check failed: {}", e.without_url())),
+    }
+}
+
+/// Order in which to try upstreams after `failed` went down. The failed upstream comes last
+/// because the fault may have been brief.
+fn candidates(strategy: FailoverStrategy, len: usize, failed: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = match strategy {
+        FailoverStrategy::PrimaryThenSecondary => (0..len).filter(|&i| i != failed).collect(),
+        FailoverStrategy::RoundRobin => (1..len).map(|step| (failed + step) % len).collect(),
+    };
+    order.push(failed);
+    order
+}
+
+/// Errors that mean the upstream itself is unreachable or broken, as opposed to the request.
+pub(super) fn is_upstream_failure(error: &StreamableHttpError<HttpError>) -> bool {
+    match error {
+        StreamableHttpError::Client(HttpError::Reqwest(e)) => {
+            e.is_connect() || e.is_timeout() || e.status().is_some_and(|status| status.is_server_error())
+        }
+        StreamableHttpError::Client(HttpError::ServerError { .. }) => true,
+        StreamableHttpError::Io(_) | StreamableHttpError::TransportChannelClosed => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use rmcp::model::NumberOrString;
+    use tempfile::tempdir;
+
+    use super::super::http::RateAwareClient;
+    use super::*;
+    use crate::config;
+
+    /// A primary/secondary pair, currently running on the secondary.
+    fn on_secondary(
+        primary: &str,
+    ) -> (
+        Failover<RateAwareClient>,
+        StreamableHttpClientTransport<RateAwareClient>,
+    ) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let body = format!("[mcp.servers.s]\nurl = \"{primary}\"\nfailover_urls = [\"http://127.0.0.1:1/mcp\"]\n");
+        std::fs::write(&path, body).unwrap();
+        let profile = config::load_mcp_server_at(&path, "s").unwrap();
+        let config = StreamableHttpClientTransportConfig::with_uri(primary);
+        let mut failover = Failover::for_server(&profile, "s", RateAwareClient::new(), config);
+        let remote = failover.connect(1);
+        failover.current = 1;
+        failover.failback_at = Some(Instant::now());
+        (failover, remote)
+    }
+
+    fn unused_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[tokio::test]
+    async fn fails_back_once_the_primary_is_healthy() {
+        let primary = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", primary.server_addr());
+        let handle = std::thread::spawn(move || {
+            let request = primary.recv().unwrap();
+            request.respond(tiny_http::Response::empty(405)).unwrap();
+        });
+        let (mut failover, mut remote) = on_secondary(&url);
+
+        failover.probe_primary();
+        assert!(failover.probing());
+        assert_eq!(failover.next_failback(), None);
+        let probed = failover.primary_probed().await;
+        handle.join().unwrap();
+        failover.fail_back(&mut remote, probed);
+
+        assert_eq!(failover.current, 0);
+        assert!(!failover.probing());
+        assert_eq!(failover.next_failback(), None);
+    }
+
+    #[tokio::test]
+    async fn fail_back_waits_for_in_flight_requests_and_a_healthy_primary() {
+        let url = format!("http://127.0.0.1:{}/mcp", unused_port());
+        let (mut failover, mut remote) = on_secondary(&url);
+        failover.outstanding.insert(NumberOrString::Number(1));
+
+        let before = Instant::now();
+        failover.probe_primary();
+        assert!(!failover.probing());
+        assert!(failover
+            .next_failback()
+            .is_some_and(|at| at >= before + FAILBACK_INTERVAL));
+
+        failover.cancelled(&NumberOrString::Number(1));
+        failover.probe_primary();
+        let probed = failover.primary_probed().await;
+        assert!(probed.is_err());
+        failover.fail_back(&mut remote, probed);
+        assert_eq!(failover.current, 1);
+        assert!(failover.next_failback().is_some());
+    }
+
+    #[tokio::test]
+    async fn requests_sent_during_the_probe_hold_back_the_switch() {
+        let primary = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", primary.server_addr());
+        let handle = std::thread::spawn(move || {
+            let request = primary.recv().unwrap();
+            request.respond(tiny_http::Response::empty(405)).unwrap();
+        });
+        let (mut failover, mut remote) = on_secondary(&url);
+
+        failover.probe_primary();
+        failover.outstanding.insert(NumberOrString::Number(1));
+        let probed = failover.primary_probed().await;
+        handle.join().unwrap();
+        assert!(probed.is_ok());
+        failover.fail_back(&mut remote, probed);
+
+        assert_eq!(failover.current, 1);
+        assert!(failover.next_failback().is_some());
+    }
+
+    #[test]
+    fn primary_then_secondary_prefers_the_earliest_upstream() {
+        assert_eq!(candidates(FailoverStrategy::PrimaryThenSecondary, 3, 0), [1, 2, 0]);
+        assert_eq!(candidates(FailoverStrategy::PrimaryThenSecondary, 3, 2), [0, 1, 2]);
+        assert_eq!(candidates(FailoverStrategy::PrimaryThenSecondary, 1, 0), [0]);
+    }
+
+    #[test]
+    fn round_robin_moves_past_the_failed_upstream() {
+        assert_eq!(candidates(FailoverStrategy::RoundRobin, 3, 0), [1, 2, 0]);
+        assert_eq!(candidates(FailoverStrategy::RoundRobin, 3, 2), [0, 1, 2]);
+        assert_eq!(candidates(FailoverStrategy::RoundRobin, 3, 1), [2, 0, 1]);
+    }
+
+    #[test]
+    fn only_transport_level_failures_trigger_failover() {
+        let server_error = StreamableHttpError::Client(HttpError::ServerError {
+            status: StatusCode::BAD_GATEWAY,
+            body: String::new(),
+        });
+        assert!(is_upstream_failure(&server_error));
+        assert!(is_upstream_failure(&StreamableHttpError::TransportChannelClosed));
+
+        let throttled = StreamableHttpError::Client(HttpError::TooManyRequests { retry_after: None });
+        assert!(!is_upstream_failure(&throttled));
+        assert!(!is_upstream_failure(&StreamableHttpError::SessionExpired));
+    }
+
+    #[tokio::test]
+    async fn request_building_errors_are_not_upstream_failures() {
+        let error = reqwest::Client::new()
+            .get("http://127.0.0.1:1/")
+            .header("bad\nheader", "x")
+            .send()
+            .await
+            .unwrap_err();
+        assert!(!is_upstream_failure(&StreamableHttpError::Client(HttpError::Reqwest(
+            error
+        ))));
+    }
+
+    #[tokio::test]
+    async fn connection_refused_is_an_upstream_failure() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let error = reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{port}/"))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(is_upstream_failure(&StreamableHttpError::Client(HttpError::Reqwest(
+            error
+        ))));
+    }
+}
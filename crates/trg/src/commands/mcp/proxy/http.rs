@@ -0,0 +1,318 @@
+//! `reqwest` transport client that reports HTTP 429 together with its `Retry-After` delay.
+//!
+//! rmcp's `StreamableHttpClient` impl for `reqwest::Client` folds a 429 into an opaque
+//! `UnexpectedServerResponse` string and drops the headers, so the bridge could neither
+//! tell a throttled request from a broken remote nor know when to try again.
+//! [`RateAwareClient::post_message`] mirrors rmcp's handling of the POST response and only
+//! adds the 429 branch, and reports a 5xx without a JSON-RPC body as [`HttpError::ServerError`]
+//! so failover can recognise an unhealthy upstream; streams and session deletion are delegated
+//! unchanged.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use futures::{stream::BoxStream, StreamExt};
+use http::{
+    header::{ACCEPT, CONTENT_TYPE, RETRY_AFTER, WWW_AUTHENTICATE},
+    HeaderMap, HeaderName, HeaderValue, StatusCode,
+};
+use rmcp::{
+    model::{ClientJsonRpcMessage, JsonRpcMessage, ServerJsonRpcMessage},
+    transport::streamable_http_client::{
+        AuthRequiredError, InsufficientScopeError, SseError, StreamableHttpClient, StreamableHttpError,
+        StreamableHttpPostResponse,
+    },
+};
+use sse_stream::{Sse, SseStream};
+
+const HEADER_SESSION_ID: &str = "Mcp-Session-Id";
+const EVENT_STREAM_MIME_TYPE: &str = "text/event-stream";
+const JSON_MIME_TYPE: &str = "application/json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum HttpError {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    /// The remote answered `429 Too Many Requests`; `retry_after` is its `Retry-After`, if any.
+    #[error("remote answered HTTP 429 Too Many Requests")]
+    TooManyRequests { retry_after: Option<Duration> },
+
+    /// The remote answered with a 5xx status and no JSON-RPC error body.
+    #[error("remote answered HTTP {status}: {body}")]
+    ServerError { status: StatusCode, body: String },
+}
+
+#[derive(Clone)]
+pub struct RateAwareClient {
+    inner: reqwest::Client,
+}
+
+impl RateAwareClient {
+    /// Same settings as rmcp's default transport client: no idle pooling, no redirects.
+    pub fn new() -> Self {
+        let inner = reqwest::Client::builder()
+            .pool_max_idle_per_host(0)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self { inner }
+    }
+}
+
+impl StreamableHttpClient for RateAwareClient {
+    type Error = HttpError;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        session_id: Option<Arc<str>>,
+        auth_token: Option<String>,
+        custom_headers: HashMap<HeaderName, HeaderValue>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<HttpError>> {
+        let mut request = self
+            .inner
+            .post(uri.as_ref())
+            .header(ACCEPT, [EVENT_STREAM_MIME_TYPE, JSON_MIME_TYPE].join(", "));
+        if let Some(token) = auth_token {
+            request = request.bearer_auth(token);
+        }
+        for (name, value) in custom_headers {
+            request = request.header(name, value);
+        }
+        let session_was_attached = session_id.is_some();
+        if let Some(session_id) = session_id {
+            request = request.header(HEADER_SESSION_ID, session_id.as_ref());
+        }
+        let response = request.json(&message).send().await.map_err(client_error)?;
+        let status = response.status();
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = retry_after(response.headers(), SystemTime::now());
+            return Err(StreamableHttpError::Client(HttpError::TooManyRequests { retry_after }));
+        }
+        if let Some(header) = www_authenticate(response.headers())? {
+            if status == StatusCode::UNAUTHORIZED {
+                return Err(StreamableHttpError::AuthRequired(AuthRequiredError::new(header)));
+            }
+            if status == StatusCode::FORBIDDEN {
+                let required_scope = scope_from_header(&header);
+                return Err(StreamableHttpError::InsufficientScope(InsufficientScopeError::new(
+                    header,
+                    required_scope,
+                )));
+            }
+        }
+        if matches!(status, StatusCode::ACCEPTED | StatusCode::NO_CONTENT) {
+            return Ok(StreamableHttpPostResponse::Accepted);
+        }
+        if status == StatusCode::NOT_FOUND && session_was_attached {
+            return Err(StreamableHttpError::SessionExpired);
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|ct| String::from_utf8_lossy(ct.as_bytes()).to_string());
+        let is_json = content_type.as_deref().is_some_and(|ct| ct.starts_with(JSON_MIME_TYPE));
+        let session_id = response
+            .headers()
+            .get(HEADER_SESSION_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        // Some servers answer notifications with an empty 200 instead of 202.
+        if status.is_success() && response.content_length() == Some(0) && !matches!(message, JsonRpcMessage::Request(_))
+        {
+            return Ok(StreamableHttpPostResponse::Accepted);
+        }
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<failed to read response body>".to_string());
+            if is_json {
+                if let Ok(error @ JsonRpcMessage::Error(_)) = serde_json::from_str::<ServerJsonRpcMessage>(&body) {
+                    return Ok(StreamableHttpPostResponse::Json(error, session_id));
+                }
+            }
+            if status.is_server_error() {
+                return Err(StreamableHttpError::Client(HttpError::ServerError { status, body }));
+            }
+            return Err(StreamableHttpError::UnexpectedServerResponse(Cow::Owned(format!(
+                "HTTP {status}: {body}"
+            ))));
+        }
+        match content_type.as_deref() {
+            Some(ct) if ct.starts_with(EVENT_STREAM_MIME_TYPE) => {
+                let events = SseStream::from_bytes_stream(response.bytes_stream()).boxed();
+                Ok(StreamableHttpPostResponse::Sse(events, session_id))
+            }
+            Some(_) if is_json => match response.json::<ServerJsonRpcMessage>().await {
+                Ok(message) => Ok(StreamableHttpPostResponse::Json(message, session_id)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "http: unparseable JSON response, treating as accepted");
+                    Ok(StreamableHttpPostResponse::Accepted)
+                }
+            },
+            _ => Err(StreamableHttpError::UnexpectedContentType(content_type)),
+        }
+    }
+
+    async fn delete_session(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        auth_token: Option<String>,
+        custom_headers: HashMap<HeaderName, HeaderValue>,
+    ) -> Result<(), StreamableHttpError<HttpError>> {
+        self.inner
+            .delete_session(uri, session_id, auth_token, custom_headers)
+            .await
+            .map_err(lift_error)
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        last_event_id: Option<String>,
+        auth_token: Option<String>,
+        custom_headers: HashMap<HeaderName, HeaderValue>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<HttpError>> {
+        self.inner
+            .get_stream(uri, session_id, last_event_id, auth_token, custom_headers)
+            .await
+            .map_err(lift_error)
+    }
+}
+
+fn client_error(e: reqwest::Error) -> StreamableHttpError<HttpError> {
+    StreamableHttpError::Client(HttpError::Reqwest(e))
+}
+
+/// Re-type an error from the delegated `reqwest::Client` calls.
+fn lift_error(e: StreamableHttpError<reqwest::Error>) -> StreamableHttpError<HttpError> {
+    match e {
+        StreamableHttpError::Client(e) => client_error(e),
+        StreamableHttpError::Sse(e) => StreamableHttpError::Sse(e),
+        StreamableHttpError::Io(e) => StreamableHttpError::Io(e),
+        StreamableHttpError::UnexpectedContentType(ct) => StreamableHttpError::UnexpectedContentType(ct),
+        StreamableHttpError::ServerDoesNotSupportSse => StreamableHttpError::ServerDoesNotSupportSse,
+        StreamableHttpError::SessionExpired => StreamableHttpError::SessionExpired,
+        StreamableHttpError::AuthRequired(e) => StreamableHttpError::AuthRequired(e),
+        StreamableHttpError::InsufficientScope(e) => StreamableHttpError::InsufficientScope(e),
+        StreamableHttpError::ReservedHeaderConflict(h) => StreamableHttpError::ReservedHeaderConflict(h),
+        other => StreamableHttpError::UnexpectedServerResponse(Cow::Owned(other.to_string())),
+    }
+}
+
+fn www_authenticate(headers: &HeaderMap) -> Result<Option<String>, StreamableHttpError<HttpError>> {
+    headers
+        .get(WWW_AUTHENTICATE)
+        .map(|v| {
+            v.to_str().map(str::to_string).map_err(|_| {
+                StreamableHttpError::UnexpectedServerResponse(Cow::from("invalid www-authenticate header value"))
+            })
+        })
+        .transpose()
+}
+
+/// `scope="a b"` or `scope=a` from a `WWW-Authenticate` challenge.
+fn scope_from_header(header: &str) -> Option<String> {
+    let start = header.to_ascii_lowercase().find("scope=")? + "scope=".len();
+    let value = &header[start..];
+    match value.strip_prefix('"') {
+        Some(quoted) => quoted.find('"').map(|end| quoted[..end].to_string()),
+        None => {
+            let end = value
+                .find(|c: char| c == ',' || c == ';' || c.is_whitespace())
+                .unwrap_or(value.len());
+            (end > 0).then(|| value[..end].to_string())
+        }
+    }
+}
+
+/// `Retry-After` as delay-seconds or an HTTP-date (RFC 9110 §10.2.3); past dates mean "now".
+fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let at = SystemTime::UNIX_EPOCH + Duration::from_secs(u64::try_from(at.timestamp()).ok()?);
+    Some(at.duration_since(now).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(retry_after: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(retry_after).unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777); // Sun, 06 Nov 1994 08:49:37 GMT
+        assert_eq!(retry_after(&headers("120"), now), Some(Duration::from_secs(120)));
+        assert_eq!(
+            retry_after(&headers("Sun, 06 Nov 1994 08:50:07 GMT"), now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after(&headers("Sun, 06 Nov 1994 08:00:00 GMT"), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&headers("soon"), now), None);
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+    }
+
+    #[test]
+    fn scope_is_read_from_quoted_and_bare_challenges() {
+        assert_eq!(
+            scope_from_header(r#"Bearer error="insufficient_scope", scope="read write""#).as_deref(),
+            Some("read write")
+        );
+        assert_eq!(
+            scope_from_header("Bearer scope=admin, realm=x").as_deref(),
+            Some("admin")
+        );
+        assert_eq!(scope_from_header("Bearer realm=x"), None);
+    }
+
+    #[tokio::test]
+    async fn post_message_reports_429_with_retry_after() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let uri: Arc<str> = format!("http://{}/mcp", server.server_addr()).into();
+        let handle = std::thread::spawn(move || {
+            let request = server.recv().unwrap();
+            let response = tiny_http::Response::from_string("slow down")
+                .with_status_code(429)
+                .with_header(tiny_http::Header::from_bytes("Retry-After", "7").unwrap());
+            request.respond(response).unwrap();
+        });
+
+        let message: ClientJsonRpcMessage = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "search"}
+        }))
+        .unwrap();
+        let result = RateAwareClient::new()
+            .post_message(uri, message, None, None, HashMap::new())
+            .await;
+        handle.join().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(StreamableHttpError::Client(HttpError::TooManyRequests { retry_after }))
+                if retry_after == Some(Duration::from_secs(7))
+        ));
+    }
+}
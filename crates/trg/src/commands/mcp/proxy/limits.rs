@@ -0,0 +1,407 @@
+//! Client-side `tools/call` limits and HTTP 429 back-off for the bridge.
+//!
+//! Host messages pass through [`RequestLimiter::submit`]; `tools/call` requests join a FIFO
+//! queue and leave it through [`RequestLimiter::take_ready`] once the server-wide and the
+//! tool's own limits have room. Concurrency slots are released when the remote's response
+//! (or error) for the request id comes back, or when the host cancels the request. A request
+//! the remote rejected with 429 is put back at the head of the queue until its `Retry-After`
+//! has passed.
+//!
+//! Everything runs on the bridge task: the limiter only decides, the bridge sends.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::Duration,
+};
+
+use rmcp::{
+    model::{ClientRequest, ErrorCode, ErrorData, JsonRpcMessage, RequestId},
+    service::TxJsonRpcMessage,
+    RoleClient, RoleServer,
+};
+use serde_json::json;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::config::{RequestLimits, ResolvedMcpServer};
+
+/// JSON-RPC error code returned to the host once a throttled request runs out of retries.
+const RATE_LIMITED: ErrorCode = ErrorCode(-32029);
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// 429 retries per request before the host gets an error instead.
+const MAX_THROTTLE_RETRIES: u32 = 3;
+
+/// Wait used when a 429 carries no `Retry-After`, doubled per retry.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(2);
+
+/// Longest `Retry-After` honoured; anything beyond is reported to the host instead.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+struct Queued {
+    msg: TxJsonRpcMessage<RoleClient>,
+    /// `Some` for `tools/call`; other methods only queue while backing off from a 429.
+    tool: Option<String>,
+    enqueued: Instant,
+    not_before: Option<Instant>,
+}
+
+/// Usage of one limit scope (the whole server, or one tool).
+#[derive(Default)]
+struct Window {
+    in_flight: u32,
+    started: VecDeque<Instant>,
+}
+
+impl Window {
+    fn prune(&mut self, now: Instant) {
+        while self.started.front().is_some_and(|t| now.duration_since(*t) >= WINDOW) {
+            self.started.pop_front();
+        }
+    }
+
+    fn has_room(&self, limits: &RequestLimits) -> bool {
+        limits.max_concurrent.is_none_or(|max| self.in_flight < max.get())
+            && limits
+                .per_minute
+                .is_none_or(|max| self.started.len() < max.get() as usize)
+    }
+
+    /// When the oldest start leaves the window, if the per-minute cap is what blocks.
+    fn frees_up_at(&self, limits: &RequestLimits) -> Option<Instant> {
+        let max = limits.per_minute?.get() as usize;
+        (self.started.len() >= max).then(|| self.started.front().map(|t| *t + WINDOW))?
+    }
+}
+
+pub(super) struct RequestLimiter {
+    server: String,
+    limits: RequestLimits,
+    tool_limits: BTreeMap<String, RequestLimits>,
+    server_window: Window,
+    tool_windows: HashMap<String, Window>,
+    /// Dispatched `tools/call` requests awaiting a response: id -> tool.
+    in_flight: HashMap<RequestId, String>,
+    queue: VecDeque<Queued>,
+    /// 429 retries already spent per request id.
+    retries: HashMap<RequestId, u32>,
+}
+
+impl RequestLimiter {
+    pub(super) fn for_server(profile: &ResolvedMcpServer, server_name: &str) -> Self {
+        Self::new(server_name, profile.limits, profile.tool_limits.clone())
+    }
+
+    fn new(server: &str, limits: RequestLimits, tool_limits: BTreeMap<String, RequestLimits>) -> Self {
+        Self {
+            server: server.to_string(),
+            limits,
+            tool_limits,
+            server_window: Window::default(),
+            tool_windows: HashMap::new(),
+            in_flight: HashMap::new(),
+            queue: VecDeque::new(),
+            retries: HashMap::new(),
+        }
+    }
+
+    /// Hand back messages that are not subject to limits; queue `tools/call` requests.
+    pub(super) fn submit(
+        &mut self,
+        msg: TxJsonRpcMessage<RoleClient>,
+        now: Instant,
+    ) -> Option<TxJsonRpcMessage<RoleClient>> {
+        let tool = match &msg {
+            JsonRpcMessage::Request(request) => match &request.request {
+                ClientRequest::CallToolRequest(call) => call.params.name.to_string(),
+                _ => return Some(msg),
+            },
+            _ => return Some(msg),
+        };
+        self.queue.push_back(Queued {
+            msg,
+            tool: Some(tool),
+            enqueued: now,
+            not_before: None,
+        });
+        None
+    }
+
+    /// Dequeue, in order, every request the limits now admit, and start tracking them.
+    pub(super) fn take_ready(&mut self, now: Instant) -> Vec<TxJsonRpcMessage<RoleClient>> {
+        self.server_window.prune(now);
+        for window in self.tool_windows.values_mut() {
+            window.prune(now);
+        }
+
+        let mut ready = Vec::new();
+        let mut waiting = VecDeque::with_capacity(self.queue.len());
+        while let Some(item) = self.queue.pop_front() {
+            if item.not_before.is_some_and(|t| t > now) || !self.admits(item.tool.as_deref()) {
+                waiting.push_back(item);
+                continue;
+            }
+            if let Some(tool) = &item.tool {
+                self.start(tool, &item.msg, now);
+            }
+            let queued = now.duration_since(item.enqueued);
+            if queued >= Duration::from_millis(1) {
+                info!(
+                    server = %self.server,
+                    tool = item.tool.as_deref().unwrap_or("-"),
+                    queued_ms = queued.as_millis() as u64,
+                    "limits: dispatching queued request"
+                );
+            }
+            ready.push(item.msg);
+        }
+        self.queue = waiting;
+        ready
+    }
+
+    /// Release the concurrency slot held by a request once the remote has answered it.
+    pub(super) fn complete(&mut self, msg: &TxJsonRpcMessage<RoleServer>) {
+        let id = match msg {
+            JsonRpcMessage::Response(response) => &response.id,
+            JsonRpcMessage::Error(error) => match &error.id {
+                Some(id) => id,
+                None => return,
+            },
+            _ => return,
+        };
+        self.release(id);
+        self.retries.remove(id);
+    }
+
+    /// Forget a request the host cancelled: drop it from the queue, or free its slot if it is
+    /// already running. Returns whether it was still queued, i.e. the remote never saw it.
+    pub(super) fn cancel(&mut self, id: &RequestId) -> bool {
+        self.retries.remove(id);
+        if self.release(id).is_some() {
+            debug!(server = %self.server, id = %id, "limits: in-flight request cancelled");
+            return false;
+        }
+        let queued = self.queue.len();
+        self.queue
+            .retain(|item| !matches!(&item.msg, JsonRpcMessage::Request(request) if request.id == *id));
+        self.queue.len() < queued
+    }
+
+    /// Requeue a request the remote rejected with 429, or build the host's error reply once
+    /// it runs out of retries. Throttled notifications are dropped: nobody awaits them.
+    pub(super) fn throttled(
+        &mut self,
+        msg: TxJsonRpcMessage<RoleClient>,
+        retry_after: Option<Duration>,
+        now: Instant,
+    ) -> Option<TxJsonRpcMessage<RoleServer>> {
+        let JsonRpcMessage::Request(request) = &msg else {
+            warn!(server = %self.server, "limits: remote answered 429 to a notification; dropping it");
+            return None;
+        };
+        let id = request.id.clone();
+        let tool = self.release(&id);
+        let retries = self.retries.get(&id).copied().unwrap_or(0);
+        let delay = retry_after.unwrap_or(DEFAULT_RETRY_AFTER * 2u32.pow(retries));
+
+        if retries >= MAX_THROTTLE_RETRIES || delay > MAX_RETRY_AFTER {
+            warn!(
+                server = %self.server,
+                retries,
+                delay_s = delay.as_secs(),
+                "limits: giving up on throttled request"
+            );
+            self.retries.remove(&id);
+            let data = json!({ "server": self.server, "retryAfterSecs": delay.as_secs() });
+            let message = format!("`{}` is rate limiting requests (HTTP 429); retry later", self.server);
+            return Some(JsonRpcMessage::error(
+                ErrorData::new(RATE_LIMITED, message, Some(data)),
+                Some(id),
+            ));
+        }
+        warn!(
+            server = %self.server,
+            delay_ms = delay.as_millis() as u64,
+            attempt = retries + 1,
+            "limits: remote answered 429, retrying after delay"
+        );
+        self.retries.insert(id, retries + 1);
+        self.queue.push_front(Queued {
+            msg,
+            tool,
+            enqueued: now,
+            not_before: Some(now + delay),
+        });
+        None
+    }
+
+    /// Earliest moment a queued request could become ready without any response arriving.
+    pub(super) fn next_wake(&self) -> Option<Instant> {
+        self.queue
+            .iter()
+            .filter_map(|item| {
+                let limits = item.tool.as_deref().map(|tool| {
+                    let tool_wait = self
+                        .tool_limits
+                        .get(tool)
+                        .and_then(|l| self.tool_windows.get(tool).and_then(|w| w.frees_up_at(l)));
+                    tool_wait.max(self.server_window.frees_up_at(&self.limits))
+                });
+                item.not_before.max(limits.flatten())
+            })
+            .min()
+    }
+
+    fn admits(&self, tool: Option<&str>) -> bool {
+        let Some(tool) = tool else { return true };
+        self.server_window.has_room(&self.limits)
+            && self
+                .tool_limits
+                .get(tool)
+                .is_none_or(|limits| self.tool_windows.get(tool).is_none_or(|window| window.has_room(limits)))
+    }
+
+    fn start(&mut self, tool: &str, msg: &TxJsonRpcMessage<RoleClient>, now: Instant) {
+        let JsonRpcMessage::Request(request) = msg else { return };
+        self.in_flight.insert(request.id.clone(), tool.to_string());
+        self.server_window.in_flight += 1;
+        self.server_window.started.push_back(now);
+        if self.tool_limits.contains_key(tool) {
+            let window = self.tool_windows.entry(tool.to_string()).or_default();
+            window.in_flight += 1;
+            window.started.push_back(now);
+        }
+        debug!(server = %self.server, tool, in_flight = self.server_window.in_flight, "limits: dispatched tools/call");
+    }
+
+    fn release(&mut self, id: &RequestId) -> Option<String> {
+        let tool = self.in_flight.remove(id)?;
+        self.server_window.in_flight = self.server_window.in_flight.saturating_sub(1);
+        if let Some(window) = self.tool_windows.get_mut(&tool) {
+            window.in_flight = window.in_flight.saturating_sub(1);
+        }
+        Some(tool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use rmcp::model::{EmptyResult, NumberOrString, ServerResult};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn call(id: i64, tool: &str) -> TxJsonRpcMessage<RoleClient> {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": id, "method": "tools/call", "params": {"name": tool}
+        }))
+        .unwrap()
+    }
+
+    fn done(id: i64) -> TxJsonRpcMessage<RoleServer> {
+        JsonRpcMessage::response(ServerResult::EmptyResult(EmptyResult {}), NumberOrString::Number(id))
+    }
+
+    fn ids(msgs: &[TxJsonRpcMessage<RoleClient>]) -> Vec<Value> {
+        msgs.iter()
+            .map(|m| serde_json::to_value(m).unwrap()["id"].clone())
+            .collect()
+    }
+
+    fn limits(max_concurrent: u32, per_minute: u32) -> RequestLimits {
+        RequestLimits {
+            max_concurrent: NonZeroU32::new(max_concurrent),
+            per_minute: NonZeroU32::new(per_minute),
+        }
+    }
+
+    #[test]
+    fn concurrency_cap_releases_on_response() {
+        let mut limiter = RequestLimiter::new("s", limits(1, 0), BTreeMap::new());
+        let now = Instant::now();
+        assert!(limiter.submit(call(1, "a"), now).is_none());
+        assert!(limiter.submit(call(2, "a"), now).is_none());
+        assert_eq!(ids(&limiter.take_ready(now)), [json!(1)]);
+        assert!(limiter.take_ready(now).is_empty());
+        assert_eq!(limiter.next_wake(), None);
+
+        limiter.complete(&done(1));
+        assert_eq!(ids(&limiter.take_ready(now)), [json!(2)]);
+    }
+
+    #[test]
+    fn cancelling_frees_the_slot_or_the_queue_entry() {
+        let mut limiter = RequestLimiter::new("s", limits(1, 0), BTreeMap::new());
+        let now = Instant::now();
+        limiter.submit(call(1, "a"), now);
+        limiter.submit(call(2, "a"), now);
+        assert_eq!(ids(&limiter.take_ready(now)), [json!(1)]);
+
+        assert!(limiter.cancel(&NumberOrString::Number(2)));
+        assert!(!limiter.cancel(&NumberOrString::Number(1)));
+        assert!(limiter.submit(call(3, "a"), now).is_none());
+        assert_eq!(ids(&limiter.take_ready(now)), [json!(3)]);
+    }
+
+    #[test]
+    fn per_minute_cap_wakes_when_the_window_slides() {
+        let mut limiter = RequestLimiter::new("s", limits(0, 2), BTreeMap::new());
+        let t0 = Instant::now();
+        for id in 1..=3 {
+            limiter.submit(call(id, "a"), t0);
+        }
+        assert_eq!(ids(&limiter.take_ready(t0)), [json!(1), json!(2)]);
+        assert_eq!(limiter.next_wake(), Some(t0 + WINDOW));
+        assert!(limiter.take_ready(t0 + Duration::from_secs(59)).is_empty());
+        assert_eq!(ids(&limiter.take_ready(t0 + WINDOW)), [json!(3)]);
+    }
+
+    #[test]
+    fn tool_limits_only_hold_back_that_tool() {
+        let tools = BTreeMap::from([("slow".to_string(), limits(1, 0))]);
+        let mut limiter = RequestLimiter::new("s", RequestLimits::default(), tools);
+        let now = Instant::now();
+        limiter.submit(call(1, "slow"), now);
+        limiter.submit(call(2, "slow"), now);
+        limiter.submit(call(3, "fast"), now);
+        assert_eq!(ids(&limiter.take_ready(now)), [json!(1), json!(3)]);
+        limiter.complete(&done(1));
+        assert_eq!(ids(&limiter.take_ready(now)), [json!(2)]);
+    }
+
+    #[test]
+    fn other_messages_pass_straight_through() {
+        let mut limiter = RequestLimiter::new("s", limits(1, 1), BTreeMap::new());
+        let list: TxJsonRpcMessage<RoleClient> =
+            serde_json::from_value(json!({"jsonrpc": "2.0", "id": 9, "method": "tools/list"})).unwrap();
+        assert!(limiter.submit(list, Instant::now()).is_some());
+    }
+
+    #[test]
+    fn throttled_requests_wait_for_retry_after_then_give_up() {
+        let mut limiter = RequestLimiter::new("s", RequestLimits::default(), BTreeMap::new());
+        let t0 = Instant::now();
+        limiter.submit(call(1, "a"), t0);
+        let mut msg = limiter.take_ready(t0).pop().unwrap();
+
+        let mut now = t0;
+        for _ in 0..MAX_THROTTLE_RETRIES {
+            assert!(limiter.throttled(msg, Some(Duration::from_secs(5)), now).is_none());
+            assert_eq!(limiter.next_wake(), Some(now + Duration::from_secs(5)));
+            assert!(limiter.take_ready(now + Duration::from_secs(4)).is_empty());
+            now += Duration::from_secs(5);
+            msg = limiter.take_ready(now).pop().expect("retried after Retry-After");
+        }
+
+        let reply = limiter
+            .throttled(msg, Some(Duration::from_secs(5)), now)
+            .expect("error reply");
+        let reply = serde_json::to_value(&reply).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"]["code"], RATE_LIMITED.0);
+        assert!(limiter.queue.is_empty());
+    }
+}
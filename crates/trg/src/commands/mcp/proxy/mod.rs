@@ -1,7 +1,44 @@
 //! `trg mcp proxy`: stdio MCP bridge backed by RMCP streamable-http transport.
 
+mod audit;
+mod cache;
 mod cli;
+mod failover;
+mod http;
+mod limits;
+mod redact;
 mod run;
+#[cfg(any(feature = "schema-validation", test))]
+mod schema;
+mod spans;
+
+#[cfg(not(any(feature = "schema-validation", test)))]
+mod schema {
+    //! Built without `schema-validation`: `tools/call` arguments are forwarded unchecked.
+
+    use rmcp::{
+        service::{RxJsonRpcMessage, TxJsonRpcMessage},
+        RoleServer,
+    };
+
+    use crate::config::ResolvedMcpServer;
+
+    pub(super) enum ToolSchemas {}
+
+    impl ToolSchemas {
+        pub(super) fn for_server(_: &ResolvedMcpServer, _: &str) -> Option<Self> {
+            None
+        }
+
+        pub(super) fn check(&mut self, _: &RxJsonRpcMessage<RoleServer>) -> Option<TxJsonRpcMessage<RoleServer>> {
+            match *self {}
+        }
+
+        pub(super) fn observe(&mut self, _: &TxJsonRpcMessage<RoleServer>) {
+            match *self {}
+        }
+    }
+}
 
 pub use cli::ProxyArgs;
 pub use run::{run_mcp_daemon, ProxyError};
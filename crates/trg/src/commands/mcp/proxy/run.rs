@@ -1,28 +1,52 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use http::HeaderValue;
 use rmcp::{
+    model::{ClientNotification, ErrorCode, ErrorData, JsonRpcMessage, RequestId},
     service::{RxJsonRpcMessage, TxJsonRpcMessage},
     transport::{
         async_rw::AsyncRwTransport,
-        auth::AuthClient,
+        auth::{AuthClient, AuthorizationManager},
         stdio,
         streamable_http_client::{
-            StreamableHttpClient, StreamableHttpClientTransport, StreamableHttpClientTransportConfig,
+            InsufficientScopeError, StreamableHttpClient, StreamableHttpClientTransport,
+            StreamableHttpClientTransportConfig, StreamableHttpError,
         },
         Transport,
     },
     RoleClient, RoleServer,
 };
 use secrecy::ExposeSecret;
+use serde_json::json;
+use tokio::{
+    sync::Mutex,
+    time::{sleep_until, Instant},
+};
 use tracing::{debug, error, info, warn};
 
 use crate::{
     config::{self, ResolvedMcpServer},
-    oauth::{ensure_credentials_for, EnsureError, EnsureOutcome},
+    oauth::{
+        ensure_credentials_for,
+        flow::FlowConfig,
+        refresh::spawn_background_refresh,
+        step_up::{required_scopes, step_up, StepUpError},
+        store::ConfiguredCredentialStore,
+        EnsureError, EnsureOutcome,
+    },
 };
 
-use super::cli::ProxyArgs;
+use super::{
+    audit::AuditLog,
+    cache::ResponseCache,
+    cli::ProxyArgs,
+    failover::{is_upstream_failure, Failover},
+    http::{HttpError, RateAwareClient},
+    limits::RequestLimiter,
+    redact::ResponseRedactor,
+    schema::ToolSchemas,
+    spans::RequestSpans,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum TransportBuildError {
@@ -52,7 +76,7 @@ pub async fn run_mcp_daemon(args: &ProxyArgs) -> Result<(), ProxyError> {
     let server_name = args.server.trim();
     info!(server = server_name, pid = std::process::id(), "startup");
 
-    let resolved = match config::load_mcp_server(server_name) {
+    let resolved = match load_profile(server_name, args.account.as_deref()) {
         Ok(r) => r,
         Err(e) => {
             error!(server = server_name, error = %e, "config load failed");
@@ -61,13 +85,14 @@ pub async fn run_mcp_daemon(args: &ProxyArgs) -> Result<(), ProxyError> {
     };
     info!(
         server = server_name,
+        account = resolved.account.as_deref().unwrap_or("<default>"),
         headers = resolved.http_headers.len(),
         "config loaded"
     );
 
     let http_conf = streamable_http_config(&resolved)?;
 
-    let outcome = match ensure_credentials_for(&resolved, server_name).await {
+    let outcome = match ensure_credentials_for(&resolved, server_name, FlowConfig::default()).await {
         Ok(o) => o,
         Err(e) => {
             error!(server = server_name, error = %e, "ensure_credentials failed");
@@ -78,14 +103,48 @@ pub async fn run_mcp_daemon(args: &ProxyArgs) -> Result<(), ProxyError> {
     let result = match outcome {
         EnsureOutcome::NoAuthRequired => {
             info!(server = server_name, "auth: none required, using plain client");
-            let remote = StreamableHttpClientTransport::<reqwest::Client>::from_config(http_conf);
-            bridge_stdio_to_remote(remote).await
+            let hooks = BridgeHooks {
+                step_up: None,
+                redactor: ResponseRedactor::for_server(&resolved, server_name),
+                cache: ResponseCache::for_server(&resolved, server_name),
+                limiter: RequestLimiter::for_server(&resolved, server_name),
+                schemas: ToolSchemas::for_server(&resolved, server_name),
+                audit: AuditLog::for_server(&resolved, server_name),
+                spans: RequestSpans::for_server(server_name),
+                failover: Failover::for_server(&resolved, server_name, RateAwareClient::new(), http_conf),
+            };
+            bridge_stdio_to_remote(hooks).await
         }
         EnsureOutcome::AlreadyAuthorized(manager) | EnsureOutcome::Authorized(manager) => {
             info!(server = server_name, "auth: using AuthClient with stored credentials");
-            let auth_client = AuthClient::new(reqwest::Client::new(), manager);
-            let remote = StreamableHttpClientTransport::with_client(auth_client, http_conf);
-            bridge_stdio_to_remote(remote).await
+            let auth_client = AuthClient::new(RateAwareClient::new(), manager);
+            let refresher = spawn_background_refresh(
+                auth_client.auth_manager.clone(),
+                ConfiguredCredentialStore::for_server(
+                    &resolved.credential_store,
+                    server_name,
+                    resolved.account.as_deref(),
+                ),
+                server_name.to_string(),
+            );
+            let step_up = ScopeStepUp {
+                profile: &resolved,
+                server_name,
+                auth_manager: auth_client.auth_manager.clone(),
+            };
+            let hooks = BridgeHooks {
+                step_up: Some(step_up),
+                redactor: ResponseRedactor::for_server(&resolved, server_name),
+                cache: ResponseCache::for_server(&resolved, server_name),
+                limiter: RequestLimiter::for_server(&resolved, server_name),
+                schemas: ToolSchemas::for_server(&resolved, server_name),
+                audit: AuditLog::for_server(&resolved, server_name),
+                spans: RequestSpans::for_server(server_name),
+                failover: Failover::for_server(&resolved, server_name, auth_client, http_conf),
+            };
+            let result = bridge_stdio_to_remote(hooks).await;
+            refresher.abort();
+            result
         }
     };
 
@@ -96,15 +155,297 @@ pub async fn run_mcp_daemon(args: &ProxyArgs) -> Result<(), ProxyError> {
     result
 }
 
-async fn bridge_stdio_to_remote<C>(mut remote: StreamableHttpClientTransport<C>) -> Result<(), ProxyError>
+fn load_profile(server_name: &str, account: Option<&str>) -> Result<ResolvedMcpServer, config::ConfigError> {
+    let mut resolved = config::load_mcp_server(server_name)?;
+    if let Some(account) = account {
+        resolved.account = Some(config::validate_account(account.to_string())?);
+    }
+    Ok(resolved)
+}
+
+/// JSON-RPC error code returned to the host when a request needs scopes the token lacks
+/// and they cannot be obtained without a browser (implementation-defined server error range).
+const INSUFFICIENT_SCOPE: ErrorCode = ErrorCode(-32003);
+
+/// Re-authorizes in place when the remote answers `403 insufficient_scope`.
+///
+/// The transport's `AuthClient` shares `auth_manager`, so swapping the manager behind the
+/// mutex makes the next request carry the upgraded token without rebuilding the session.
+struct ScopeStepUp<'a> {
+    profile: &'a ResolvedMcpServer,
+    server_name: &'a str,
+    auth_manager: Arc<Mutex<AuthorizationManager>>,
+}
+
+impl ScopeStepUp<'_> {
+    async fn upgrade(&self, challenge: &InsufficientScopeError) -> Result<(), StepUpError> {
+        let required = required_scopes(challenge);
+        if required.is_empty() {
+            return Err(StepUpError::UnknownScope {
+                server: self.server_name.to_string(),
+                header: challenge.www_authenticate_header.clone(),
+            });
+        }
+        let manager = step_up(self.profile, self.server_name, &required, FlowConfig::default()).await?;
+        *self.auth_manager.lock().await = manager;
+        Ok(())
+    }
+}
+
+/// What became of a message handed to [`forward_to_remote`].
+enum Forwarded {
+    Sent,
+    /// The request cannot be forwarded but the session should survive: the host gets
+    /// this JSON-RPC error instead of a dead proxy.
+    Reply(TxJsonRpcMessage<RoleServer>),
+    /// The remote answered 429; the message is handed back for a later retry.
+    Throttled {
+        msg: TxJsonRpcMessage<RoleClient>,
+        retry_after: Option<Duration>,
+    },
+    /// The upstream is unreachable or answering 5xx; the message is handed back for failover.
+    UpstreamDown {
+        msg: TxJsonRpcMessage<RoleClient>,
+        reason: String,
+    },
+}
+
+/// Forward one host message; on an `insufficient_scope` challenge, step up and retry once.
+async fn forward_to_remote<C>(
+    remote: &mut StreamableHttpClientTransport<C>,
+    msg: TxJsonRpcMessage<RoleClient>,
+    step_up: Option<&ScopeStepUp<'_>>,
+) -> Result<Forwarded, ProxyError>
+where
+    C: StreamableHttpClient<Error = HttpError> + Send + Sync + 'static,
+{
+    let retry = msg.clone();
+    let error = match remote.send(msg).await {
+        Ok(()) => return Ok(Forwarded::Sent),
+        Err(e) => e,
+    };
+    let (challenge, step_up) = match (error, step_up) {
+        (StreamableHttpError::Client(HttpError::TooManyRequests { retry_after }), _) => {
+            return Ok(Forwarded::Throttled {
+                msg: retry,
+                retry_after,
+            });
+        }
+        (StreamableHttpError::InsufficientScope(challenge), Some(step_up)) => (challenge, step_up),
+        (e, _) if is_upstream_failure(&e) => {
+            return Ok(Forwarded::UpstreamDown {
+                msg: retry,
+                reason: e.to_string(),
+            });
+        }
+        (e, _) => return Err(remote_closed(e)),
+    };
+    warn!(
+        server = step_up.server_name,
+        required = ?challenge.get_required_scope(),
+        "bridge: remote answered 403 insufficient_scope"
+    );
+
+    let request_id = match &retry {
+        JsonRpcMessage::Request(request) => Some(request.id.clone()),
+        _ => None,
+    };
+    let failure = match step_up.upgrade(&challenge).await {
+        Ok(()) => match remote.send(retry.clone()).await {
+            Ok(()) => return Ok(Forwarded::Sent),
+            Err(StreamableHttpError::Client(HttpError::TooManyRequests { retry_after })) => {
+                return Ok(Forwarded::Throttled {
+                    msg: retry,
+                    retry_after,
+                });
+            }
+            Err(StreamableHttpError::InsufficientScope(again)) => format!(
+                "`{}` still reports insufficient_scope after re-authorization (requires `{}`); \
+                 the authorization server did not grant it",
+                step_up.server_name,
+                required_scopes(&again).join(" ")
+            ),
+            Err(e) => return Err(remote_closed(e)),
+        },
+        Err(e) => e.to_string(),
+    };
+    error!(server = step_up.server_name, error = %failure, "bridge: step-up failed");
+
+    let Some(id) = request_id else {
+        return Ok(Forwarded::Sent);
+    };
+    let data = json!({
+        "server": step_up.server_name,
+        "requiredScopes": required_scopes(&challenge),
+    });
+    Ok(Forwarded::Reply(JsonRpcMessage::error(
+        ErrorData::new(INSUFFICIENT_SCOPE, failure, Some(data)),
+        Some(id),
+    )))
+}
+
+/// Forward `msg` and deal with the outcome: error replies go to the host, 429s back to the
+/// limiter, and a failed upstream is replaced before `msg` is sent again.
+async fn dispatch<C, L>(
+    remote: &mut StreamableHttpClientTransport<C>,
+    local: &mut L,
+    hooks: &mut BridgeHooks<'_, C>,
+    mut msg: TxJsonRpcMessage<RoleClient>,
+) -> Result<(), ProxyError>
+where
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+    L: Transport<RoleServer>,
+{
+    let reply = loop {
+        let sent = msg.clone();
+        match forward_to_remote(remote, msg, hooks.step_up.as_ref()).await? {
+            Forwarded::Sent => {
+                if let Some(cache) = hooks.cache.as_mut() {
+                    cache.forwarded(&sent);
+                }
+                hooks.failover.sent(&sent);
+                break None;
+            }
+            Forwarded::Reply(reply) => break Some(reply),
+            Forwarded::Throttled { msg, retry_after } => {
+                break hooks.limiter.throttled(msg, retry_after, Instant::now());
+            }
+            Forwarded::UpstreamDown { msg: failed, reason } => {
+                if !hooks.failover.enabled() {
+                    return Err(remote_closed(reason));
+                }
+                fail_over(remote, local, hooks, &reason).await?;
+                msg = failed;
+            }
+        }
+    };
+    match reply {
+        // Through every response hook, so the request's concurrency slot is released too.
+        Some(reply) => deliver(local, hooks, reply).await,
+        None => Ok(()),
+    }
+}
+
+/// Forward whatever the limiter now admits.
+async fn dispatch_ready<C, L>(
+    remote: &mut StreamableHttpClientTransport<C>,
+    local: &mut L,
+    hooks: &mut BridgeHooks<'_, C>,
+) -> Result<(), ProxyError>
+where
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+    L: Transport<RoleServer>,
+{
+    for msg in hooks.limiter.take_ready(Instant::now()) {
+        dispatch(remote, local, hooks, msg).await?;
+    }
+    Ok(())
+}
+
+/// Move to another upstream and hand the host whatever the old one left unanswered.
+async fn fail_over<C, L>(
+    remote: &mut StreamableHttpClientTransport<C>,
+    local: &mut L,
+    hooks: &mut BridgeHooks<'_, C>,
+    reason: &str,
+) -> Result<(), ProxyError>
 where
-    C: StreamableHttpClient + Send + Sync + 'static,
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+    L: Transport<RoleServer>,
 {
+    let replies = hooks.failover.fail_over(remote, reason).await.map_err(remote_closed)?;
+    for reply in replies {
+        deliver(local, hooks, reply).await?;
+    }
+    Ok(())
+}
+
+/// Pass a message from the remote through the response hooks to the host.
+async fn deliver<C, L: Transport<RoleServer>>(
+    local: &mut L,
+    hooks: &mut BridgeHooks<'_, C>,
+    mut msg: TxJsonRpcMessage<RoleServer>,
+) -> Result<(), ProxyError>
+where
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+{
+    if let Some(redactor) = &hooks.redactor {
+        redactor.apply(&mut msg);
+    }
+    if let Some(cache) = hooks.cache.as_mut() {
+        cache.observe(&msg);
+    }
+    hooks.limiter.complete(&msg);
+    if let Some(schemas) = hooks.schemas.as_mut() {
+        schemas.observe(&msg);
+    }
+    if let Some(audit) = hooks.audit.as_mut() {
+        audit.answered(&msg);
+    }
+    hooks.spans.answered(&msg);
+    hooks.failover.observe(&msg);
+    send_to_host(local, msg).await
+}
+
+async fn send_to_host<L: Transport<RoleServer>>(
+    local: &mut L,
+    msg: TxJsonRpcMessage<RoleServer>,
+) -> Result<(), ProxyError> {
+    local.send(msg).await.map_err(|e| {
+        warn!(error = %e, "bridge: local send failed");
+        ProxyError::LocalClosed(e.to_string())
+    })
+}
+
+fn remote_closed(e: impl std::fmt::Display) -> ProxyError {
+    warn!(error = %e, "bridge: remote send failed");
+    ProxyError::RemoteClosed(e.to_string())
+}
+
+/// Per-server behaviour layered onto the otherwise transparent bridge.
+struct BridgeHooks<'a, C: StreamableHttpClient> {
+    step_up: Option<ScopeStepUp<'a>>,
+    redactor: Option<ResponseRedactor>,
+    cache: Option<ResponseCache>,
+    limiter: RequestLimiter,
+    schemas: Option<ToolSchemas>,
+    audit: Option<AuditLog>,
+    spans: RequestSpans,
+    failover: Failover<C>,
+}
+
+/// Answer `msg` from the cache, telling every hook that sees replies about the hit.
+fn answer_from_cache(
+    cache: Option<&mut ResponseCache>,
+    schemas: Option<&mut ToolSchemas>,
+    audit: Option<&mut AuditLog>,
+    spans: &mut RequestSpans,
+    msg: &RxJsonRpcMessage<RoleServer>,
+) -> Option<TxJsonRpcMessage<RoleServer>> {
+    let hit = cache?.lookup(msg)?;
+    debug!("bridge: answered from cache");
+    if let Some(schemas) = schemas {
+        schemas.observe(&hit);
+    }
+    if let Some(audit) = audit {
+        audit.answered(&hit);
+    }
+    spans.answered(&hit);
+    Some(hit)
+}
+
+async fn bridge_stdio_to_remote<C>(mut hooks: BridgeHooks<'_, C>) -> Result<(), ProxyError>
+where
+    C: StreamableHttpClient<Error = HttpError> + Clone + Send + Sync + 'static,
+{
+    let mut remote = hooks.failover.connect_initial();
     let (stdin, stdout) = stdio();
     let mut local = AsyncRwTransport::<RoleServer, _, _>::new_server(stdin, stdout);
     debug!("bridge: entering loop");
 
     loop {
+        let wake = hooks.limiter.next_wake();
+        let failback = hooks.failover.next_failback();
         tokio::select! {
             host_msg = local.receive() => {
                 let Some(msg) = host_msg else {
@@ -114,34 +455,91 @@ where
                     return Ok(());
                 };
 
+                if let Some(audit) = hooks.audit.as_mut() {
+                    audit.request(&msg);
+                }
+                hooks.spans.request(&msg);
+                if let Some(id) = cancelled_request(&msg) {
+                    hooks.spans.cancelled(id);
+                    hooks.failover.cancelled(id);
+                    if hooks.limiter.cancel(id) {
+                        debug!("bridge: dropped cancelled request before it was sent");
+                        continue;
+                    }
+                }
+                if let Some(reply) = hooks.schemas.as_mut().and_then(|schemas| schemas.check(&msg)) {
+                    if let Some(audit) = hooks.audit.as_mut() {
+                        audit.answered(&reply);
+                    }
+                    hooks.spans.answered(&reply);
+                    send_to_host(&mut local, reply).await?;
+                    continue;
+                }
+
+                if let Some(hit) = answer_from_cache(
+                    hooks.cache.as_mut(),
+                    hooks.schemas.as_mut(),
+                    hooks.audit.as_mut(),
+                    &mut hooks.spans,
+                    &msg,
+                ) {
+                    send_to_host(&mut local, hit).await?;
+                    continue;
+                }
+
                 debug!("bridge: host -> remote");
                 let forward: TxJsonRpcMessage<RoleClient> = host_receive_to_remote_send(msg);
 
-                if let Err(e) = remote.send(forward).await {
-                    warn!(error = %e, "bridge: remote send failed");
-                    return Err(ProxyError::RemoteClosed(e.to_string()));
+                if let Some(forward) = hooks.limiter.submit(forward, Instant::now()) {
+                    dispatch(&mut remote, &mut local, &mut hooks, forward).await?;
                 }
+                dispatch_ready(&mut remote, &mut local, &mut hooks).await?;
             }
 
             srv_msg = remote.receive() => {
                 let Some(msg) = srv_msg else {
                     warn!("bridge: remote disconnected");
-                    return Err(ProxyError::RemoteClosed("remote disconnected".into()));
+                    if !hooks.failover.enabled() {
+                        return Err(ProxyError::RemoteClosed("remote disconnected".into()));
+                    }
+                    fail_over(&mut remote, &mut local, &mut hooks, "remote disconnected").await?;
+                    dispatch_ready(&mut remote, &mut local, &mut hooks).await?;
+                    continue;
                 };
                 let msg: RxJsonRpcMessage<RoleClient> = msg;
 
                 debug!("bridge: remote -> host");
                 let back: TxJsonRpcMessage<RoleServer> = remote_receive_to_host_send(msg);
+                deliver(&mut local, &mut hooks, back).await?;
+                dispatch_ready(&mut remote, &mut local, &mut hooks).await?;
+            }
 
-                if let Err(e) = local.send(back).await {
-                    warn!(error = %e, "bridge: local send failed");
-                    return Err(ProxyError::LocalClosed(e.to_string()));
-                }
+            _ = sleep_until(wake.unwrap_or_else(Instant::now)), if wake.is_some() => {
+                dispatch_ready(&mut remote, &mut local, &mut hooks).await?;
+            }
+
+            _ = sleep_until(failback.unwrap_or_else(Instant::now)), if failback.is_some() => {
+                hooks.failover.probe_primary();
+            }
+
+            probed = hooks.failover.primary_probed(), if hooks.failover.probing() => {
+                hooks.failover.fail_back(&mut remote, probed);
             }
         }
     }
 }
 
+/// The request id a host `notifications/cancelled` refers to.
+fn cancelled_request(msg: &RxJsonRpcMessage<RoleServer>) -> Option<&RequestId> {
+    match msg {
+        JsonRpcMessage::Notification(notification) => match &notification.notification {
+            ClientNotification::CancelledNotification(cancelled) => cancelled.params.request_id.as_ref(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn host_receive_to_remote_send(msg: RxJsonRpcMessage<RoleServer>) -> TxJsonRpcMessage<RoleClient> {
     msg
 }
@@ -169,3 +567,145 @@ fn streamable_http_config(cfg: &ResolvedMcpServer) -> Result<StreamableHttpClien
     cfg_out.allow_stateless = true;
     Ok(cfg_out)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tempfile::tempdir;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+    use crate::commands::mcp::audit::{audit_file, AuditOutcome, AuditRecord};
+
+    fn call(id: i64) -> RxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": id, "method": "tools/call",
+            "params": {"name": "search", "arguments": {"q": "a"}}
+        }))
+        .unwrap()
+    }
+
+    /// `[mcp.servers.linear]` with `body`, resolved the way the proxy resolves it.
+    fn profile(dir: &Path, body: &str) -> ResolvedMcpServer {
+        let path = dir.join("config.toml");
+        std::fs::write(&path, format!("[mcp.servers.linear]\n{body}")).unwrap();
+        config::load_mcp_server_at(&path, "linear").unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_step_up_releases_the_concurrency_slot() {
+        let upstream = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", upstream.server_addr());
+        let handle = std::thread::spawn(move || {
+            let request = upstream.recv().unwrap();
+            // No `scope` in the challenge: the step-up fails without opening a browser.
+            let challenge = tiny_http::Header::from_bytes("WWW-Authenticate", r#"Bearer error="insufficient_scope""#);
+            let response = tiny_http::Response::from_string("forbidden")
+                .with_status_code(403)
+                .with_header(challenge.unwrap());
+            request.respond(response).unwrap();
+        });
+        let dir = tempdir().unwrap();
+        let resolved = profile(dir.path(), &format!("url = \"{url}\"\nmax_concurrent_requests = 1\n"));
+        let mut hooks = BridgeHooks {
+            step_up: Some(ScopeStepUp {
+                profile: &resolved,
+                server_name: "linear",
+                auth_manager: Arc::new(Mutex::new(AuthorizationManager::new(url.as_str()).await.unwrap())),
+            }),
+            redactor: None,
+            cache: None,
+            limiter: RequestLimiter::for_server(&resolved, "linear"),
+            schemas: None,
+            audit: None,
+            spans: RequestSpans::for_server("linear"),
+            failover: Failover::for_server(
+                &resolved,
+                "linear",
+                RateAwareClient::new(),
+                streamable_http_config(&resolved).unwrap(),
+            ),
+        };
+        let mut remote = hooks.failover.connect_initial();
+        let (host, proxy) = tokio::io::duplex(4096);
+        let (proxy_read, proxy_write) = tokio::io::split(proxy);
+        let mut local = AsyncRwTransport::<RoleServer, _, _>::new_server(proxy_read, proxy_write);
+
+        assert!(hooks.limiter.submit(call(1), Instant::now()).is_none());
+        dispatch_ready(&mut remote, &mut local, &mut hooks).await.unwrap();
+        handle.join().unwrap();
+
+        let mut line = String::new();
+        BufReader::new(host).read_line(&mut line).await.unwrap();
+        let reply: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"]["code"], INSUFFICIENT_SCOPE.0);
+        assert!(hooks.limiter.submit(call(2), Instant::now()).is_none());
+        assert_eq!(hooks.limiter.take_ready(Instant::now()).len(), 1);
+    }
+
+    fn request(id: i64, method: &str, params: serde_json::Value) -> RxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })).unwrap()
+    }
+
+    /// A restarted proxy answers `tools/list` from its disk cache. The hit still teaches the
+    /// schema check, and the call it then rejects is audited; the list itself is not.
+    #[test]
+    fn cache_hits_reach_the_reply_hooks() {
+        let dir = tempdir().unwrap();
+        let resolved = profile(
+            dir.path(),
+            &format!(
+                "url = \"https://linear.example/mcp\"\ncache = {{ ttl = {{ \"tools/list\" = 300 }}, disk = true, dir = \"{cache}\" }}\naudit = {{ dir = \"{audit}\" }}\n",
+                cache = dir.path().join("cache").display(),
+                audit = dir.path().join("audit").display(),
+            ),
+        );
+        let list = request(1, "tools/list", json!({}));
+        let tools: TxJsonRpcMessage<RoleServer> = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": 1, "result": {"tools": [{
+                "name": "search",
+                "inputSchema": {"type": "object", "properties": {"q": {"type": "string"}}, "required": ["q"]}
+            }]}
+        }))
+        .unwrap();
+        let mut first = ResponseCache::for_server(&resolved, "linear").unwrap();
+        first.forwarded(&list);
+        first.observe(&tools);
+
+        let mut cache = ResponseCache::for_server(&resolved, "linear");
+        let mut schemas = ToolSchemas::for_server(&resolved, "linear").unwrap();
+        let mut audit = AuditLog::for_server(&resolved, "linear").unwrap();
+        let mut spans = RequestSpans::for_server("linear");
+
+        let relist = request(2, "tools/list", json!({}));
+        audit.request(&relist);
+        assert!(schemas.check(&relist).is_none());
+        let hit = answer_from_cache(
+            cache.as_mut(),
+            Some(&mut schemas),
+            Some(&mut audit),
+            &mut spans,
+            &relist,
+        );
+        assert!(hit.is_some());
+
+        let bad = request(3, "tools/call", json!({"name": "search", "arguments": {"q": 1}}));
+        audit.request(&bad);
+        let rejected = schemas.check(&bad).expect("schema learned from the cached tools/list");
+        audit.answered(&rejected);
+        drop(audit);
+
+        let records: Vec<AuditRecord> = std::fs::read_to_string(audit_file(&dir.path().join("audit"), "linear"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let outcomes: Vec<_> = records
+            .iter()
+            .map(|record| (record.tool.as_str(), record.outcome))
+            .collect();
+        assert_eq!(outcomes, [("search", AuditOutcome::Error)]);
+    }
+}
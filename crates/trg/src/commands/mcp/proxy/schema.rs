@@ -0,0 +1,234 @@
+//! `tools/call` argument validation against the `inputSchema` each tool advertised in `tools/list`.
+//!
+//! Validators are compiled from `tools/list` results as they pass through the bridge, cached
+//! responses included, and dropped on `notifications/tools/list_changed`. A call to a tool
+//! the proxy has not seen listed is forwarded unchecked. Schemas are compiled without
+//! fetching remote or local `$ref`s: a tool description must not make the proxy read files
+//! or open connections.
+
+use std::collections::{HashMap, HashSet};
+
+use rmcp::{
+    model::{ClientRequest, ErrorData, JsonRpcMessage, RequestId, ServerNotification},
+    service::{RxJsonRpcMessage, TxJsonRpcMessage},
+    RoleServer,
+};
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::config::ResolvedMcpServer;
+
+/// Argument errors reported to the host per rejected call.
+const MAX_REPORTED_ERRORS: usize = 10;
+
+pub(super) struct ToolSchemas {
+    server: String,
+    validators: HashMap<String, jsonschema::Validator>,
+    /// `tools/list` requests awaiting their result.
+    pending: HashSet<RequestId>,
+}
+
+impl ToolSchemas {
+    /// `None` when the server opted out with `validate_tool_arguments = false`.
+    pub(super) fn for_server(profile: &ResolvedMcpServer, server_name: &str) -> Option<Self> {
+        profile.validate_tool_arguments.then(|| Self {
+            server: server_name.to_string(),
+            validators: HashMap::new(),
+            pending: HashSet::new(),
+        })
+    }
+
+    /// Check a host message; returns the `invalid params` error to send back instead of forwarding.
+    pub(super) fn check(&mut self, msg: &RxJsonRpcMessage<RoleServer>) -> Option<TxJsonRpcMessage<RoleServer>> {
+        let JsonRpcMessage::Request(request) = msg else {
+            return None;
+        };
+        let call = match &request.request {
+            ClientRequest::ListToolsRequest(_) => {
+                self.pending.insert(request.id.clone());
+                return None;
+            }
+            ClientRequest::CallToolRequest(call) => &call.params,
+            _ => return None,
+        };
+        let validator = self.validators.get(call.name.as_ref())?;
+        let arguments = Value::Object(call.arguments.clone().unwrap_or_default());
+        let errors: Vec<Value> = validator
+            .iter_errors(&arguments)
+            .take(MAX_REPORTED_ERRORS)
+            .map(|error| {
+                json!({
+                    "instancePath": error.instance_path().as_str(),
+                    "schemaPath": error.schema_path().as_str(),
+                    "message": error.to_string(),
+                })
+            })
+            .collect();
+        let first = errors.first()?;
+        let message = format!(
+            "invalid arguments for tool `{}` at `{}`: {}",
+            call.name,
+            first["instancePath"].as_str().unwrap_or_default(),
+            first["message"].as_str().unwrap_or_default(),
+        );
+        info!(
+            server = %self.server,
+            tool = %call.name,
+            id = %request.id,
+            errors = errors.len(),
+            "schema: rejected tools/call arguments"
+        );
+        let data = json!({ "tool": call.name, "errors": errors });
+        Some(JsonRpcMessage::error(
+            ErrorData::invalid_params(message, Some(data)),
+            Some(request.id.clone()),
+        ))
+    }
+
+    /// Learn schemas from `tools/list` results and forget them when the tool list changes.
+    pub(super) fn observe(&mut self, msg: &TxJsonRpcMessage<RoleServer>) {
+        match msg {
+            JsonRpcMessage::Response(response) if self.pending.remove(&response.id) => {
+                // The result union is untagged, so read the tools from its JSON form.
+                let Ok(result) = serde_json::to_value(&response.result) else {
+                    return;
+                };
+                for tool in result["tools"].as_array().into_iter().flatten() {
+                    self.learn(tool);
+                }
+            }
+            JsonRpcMessage::Error(error) => {
+                if let Some(id) = &error.id {
+                    self.pending.remove(id);
+                }
+            }
+            JsonRpcMessage::Notification(notification)
+                if matches!(
+                    notification.notification,
+                    ServerNotification::ToolListChangedNotification(_)
+                ) =>
+            {
+                debug!(server = %self.server, "schema: tool list changed, dropping validators");
+                self.validators.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn learn(&mut self, tool: &Value) {
+        let (Some(name), Some(schema)) = (tool["name"].as_str(), tool.get("inputSchema")) else {
+            return;
+        };
+        match jsonschema::options().with_retriever(NoRetrieve).build(schema) {
+            Ok(validator) => {
+                self.validators.insert(name.to_string(), validator);
+            }
+            Err(e) => {
+                warn!(server = %self.server, tool = name, error = %e, "schema: unusable inputSchema, calls go unchecked");
+                self.validators.remove(name);
+            }
+        }
+    }
+}
+
+/// Refuses every external `$ref`.
+struct NoRetrieve;
+
+impl jsonschema::Retrieve for NoRetrieve {
+    fn retrieve(&self, uri: &jsonschema::Uri<String>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!("external $ref `{}` is not fetched", uri.as_str()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::{ErrorCode, NumberOrString};
+
+    use super::*;
+
+    fn schemas() -> ToolSchemas {
+        ToolSchemas {
+            server: "test".into(),
+            validators: HashMap::new(),
+            pending: HashSet::new(),
+        }
+    }
+
+    fn host(value: Value) -> RxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn remote(value: Value) -> TxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn learn_search(schemas: &mut ToolSchemas) {
+        let list = host(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}));
+        assert!(schemas.check(&list).is_none());
+        schemas.observe(&remote(json!({
+            "jsonrpc": "2.0", "id": 1,
+            "result": {"tools": [{
+                "name": "search",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"query": {"type": "string"}, "limit": {"type": "integer"}},
+                    "required": ["query"]
+                }
+            }]}
+        })));
+    }
+
+    fn call(id: i64, name: &str, arguments: Value) -> RxJsonRpcMessage<RoleServer> {
+        host(json!({
+            "jsonrpc": "2.0", "id": id, "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        }))
+    }
+
+    #[test]
+    fn rejects_arguments_that_violate_the_listed_schema() {
+        let mut schemas = schemas();
+        learn_search(&mut schemas);
+
+        assert!(schemas.check(&call(2, "search", json!({"query": "rust"}))).is_none());
+
+        let reply = schemas
+            .check(&call(3, "search", json!({"query": "rust", "limit": "ten"})))
+            .unwrap();
+        let JsonRpcMessage::Error(error) = reply else {
+            panic!("expected an error reply");
+        };
+        assert_eq!(error.id, Some(NumberOrString::Number(3)));
+        assert_eq!(error.error.code, ErrorCode::INVALID_PARAMS);
+        assert!(error.error.message.contains("at `/limit`"), "{}", error.error.message);
+        let data = error.error.data.unwrap();
+        assert_eq!(data["errors"][0]["schemaPath"], "/properties/limit/type");
+    }
+
+    #[test]
+    fn missing_arguments_are_checked_as_an_empty_object() {
+        let mut schemas = schemas();
+        learn_search(&mut schemas);
+        let bare = host(json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call", "params": {"name": "search"}}));
+        assert!(schemas.check(&bare).is_some());
+    }
+
+    #[test]
+    fn unknown_tools_and_changed_lists_pass_through() {
+        let mut schemas = schemas();
+        assert!(schemas.check(&call(2, "search", json!({}))).is_none());
+
+        learn_search(&mut schemas);
+        schemas.observe(&remote(
+            json!({"jsonrpc": "2.0", "method": "notifications/tools/list_changed"}),
+        ));
+        assert!(schemas.check(&call(3, "search", json!({}))).is_none());
+    }
+
+    #[test]
+    fn external_refs_are_not_fetched() {
+        let mut schemas = schemas();
+        schemas.learn(&json!({"name": "x", "inputSchema": {"$ref": "file:///etc/passwd"}}));
+        assert!(schemas.validators.is_empty());
+    }
+}
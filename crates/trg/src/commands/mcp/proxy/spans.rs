@@ -0,0 +1,188 @@
+//! One `tracing` span per JSON-RPC request the host sends, open until the host has its answer.
+//!
+//! The spans are plain `tracing` spans. In a build with the `otel` feature and an OTLP
+//! endpoint configured they are exported (see `crate::telemetry`); otherwise they cost a
+//! map entry per request in flight. Each request is its own trace: a proxy lives as long as
+//! the host session, so one parent span would make a single trace hours long.
+
+use std::collections::HashMap;
+
+use rmcp::{
+    model::{ClientRequest, JsonRpcMessage, RequestId},
+    service::{RxJsonRpcMessage, TxJsonRpcMessage},
+    RoleServer,
+};
+use tracing::{field::Empty, info_span, Span};
+
+pub(super) struct RequestSpans {
+    server: String,
+    open: HashMap<RequestId, Span>,
+}
+
+impl RequestSpans {
+    pub(super) fn for_server(server_name: &str) -> Self {
+        Self {
+            server: server_name.to_string(),
+            open: HashMap::new(),
+        }
+    }
+
+    /// Open a span for a request from the host; notifications and replies are ignored.
+    pub(super) fn request(&mut self, msg: &RxJsonRpcMessage<RoleServer>) {
+        let JsonRpcMessage::Request(request) = msg else {
+            return;
+        };
+        let method = request.request.method();
+        let tool = match &request.request {
+            ClientRequest::CallToolRequest(call) => Some(call.params.name.as_ref()),
+            _ => None,
+        };
+        let span = info_span!(
+            parent: None,
+            "mcp.request",
+            "otel.name" = tool.map_or_else(|| method.to_string(), |tool| format!("{method} {tool}")),
+            "otel.kind" = "client",
+            "otel.status_code" = Empty,
+            "mcp.server" = %self.server,
+            "mcp.method.name" = method,
+            "gen_ai.tool.name" = tool,
+            "jsonrpc.request.id" = %request.id,
+            "rpc.jsonrpc.error_code" = Empty,
+            outcome = Empty,
+        );
+        self.open.insert(request.id.clone(), span);
+    }
+
+    /// Close the span of the request `msg` answers, if it answers one.
+    pub(super) fn answered(&mut self, msg: &TxJsonRpcMessage<RoleServer>) {
+        let (id, error_code) = match msg {
+            JsonRpcMessage::Response(response) => (&response.id, None),
+            JsonRpcMessage::Error(error) => match &error.id {
+                Some(id) => (id, Some(error.error.code.0)),
+                None => return,
+            },
+            _ => return,
+        };
+        let Some(span) = self.open.remove(id) else {
+            return;
+        };
+        match error_code {
+            Some(code) => {
+                span.record("otel.status_code", "error");
+                span.record("rpc.jsonrpc.error_code", code);
+                span.record("outcome", "error");
+            }
+            None => {
+                span.record("outcome", "ok");
+            }
+        }
+    }
+
+    /// Close the span of a request the host cancelled; any later answer is not its outcome.
+    pub(super) fn cancelled(&mut self, id: &RequestId) {
+        if let Some(span) = self.open.remove(id) {
+            span.record("outcome", "cancelled");
+        }
+    }
+}
+
+impl Drop for RequestSpans {
+    fn drop(&mut self) {
+        for span in self.open.values() {
+            span.record("otel.status_code", "error");
+            span.record("outcome", "unanswered");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{json, Value};
+    use tracing::{
+        span::{Attributes, Id, Record},
+        subscriber::Interest,
+        Metadata,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        registry::LookupSpan,
+        Layer,
+    };
+
+    use super::*;
+
+    /// Collects `(field, value)` for every field set on a span.
+    #[derive(Clone, Default)]
+    struct Fields(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl tracing::field::Visit for Fields {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.lock().unwrap().push((
+                field.name().to_string(),
+                format!("{value:?}").trim_matches('"').to_string(),
+            ));
+        }
+    }
+
+    impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Fields {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            attrs.record(&mut self.clone());
+        }
+
+        fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    fn host(value: Value) -> RxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn remote(value: Value) -> TxJsonRpcMessage<RoleServer> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn spans_carry_method_tool_and_outcome() {
+        let fields = Fields::default();
+        let subscriber = tracing_subscriber::registry().with(fields.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut spans = RequestSpans::for_server("linear");
+            spans.request(&host(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                "params": {"name": "search", "arguments": {}}
+            })));
+            spans.request(&host(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"})));
+            spans.request(&host(json!({"jsonrpc": "2.0", "id": 3, "method": "ping"})));
+            assert_eq!(spans.open.len(), 3);
+
+            spans.answered(&remote(json!({
+                "jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "bad"}
+            })));
+            spans.answered(&remote(json!({"jsonrpc": "2.0", "id": 2, "result": {"tools": []}})));
+            assert_eq!(spans.open.len(), 1);
+
+            spans.request(&host(json!({"jsonrpc": "2.0", "id": 4, "method": "ping"})));
+            spans.cancelled(&RequestId::Number(4));
+            assert_eq!(spans.open.len(), 1);
+        });
+
+        let recorded = fields.0.lock().unwrap().clone();
+        let has = |name: &str, value: &str| recorded.iter().any(|(n, v)| n == name && v == value);
+        assert!(has("otel.name", "tools/call search"), "{recorded:?}");
+        assert!(has("otel.name", "tools/list"));
+        assert!(has("mcp.server", "linear"));
+        assert!(has("gen_ai.tool.name", "search"));
+        assert!(has("rpc.jsonrpc.error_code", "-32602"));
+        assert!(has("outcome", "error"));
+        assert!(has("outcome", "ok"));
+        assert!(has("outcome", "unanswered"));
+        assert!(has("outcome", "cancelled"));
+    }
+}
@@ -1,9 +1,12 @@
 pub mod ai;
+pub mod logs;
 pub mod mcp;
+mod when;
 
 use clap::Subcommand;
 
 use ai::AiCommands;
+use logs::LogsArgs;
 use mcp::McpCommands;
 
 #[derive(Subcommand)]
@@ -19,4 +22,6 @@ pub enum Commands {
         #[command(subcommand)]
         command: McpCommands,
     },
+    /// Show the diagnostic log (`trg.log`), optionally for one MCP server or as it grows
+    Logs(LogsArgs),
 }
@@ -0,0 +1,56 @@
+//! Time arguments shared by the log viewers (`--since`, `--until`).
+
+use chrono::{DateTime, Duration, Utc};
+
+/// RFC 3339, or `<n>s|m|h|d` before now.
+pub(crate) fn parse_when(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_when_at(value, Utc::now())
+}
+
+fn parse_when_at(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    let invalid = || format!("`{value}` is neither an RFC 3339 time nor an age like `30m`, `12h` or `7d`");
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    now.checked_sub_signed(age).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_and_timestamps_parse() {
+        let now = DateTime::parse_from_rfc3339("2026-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_when_at("90m", now).unwrap().to_rfc3339(),
+            "2026-03-10T10:30:00+00:00"
+        );
+        assert_eq!(
+            parse_when_at("2d", now).unwrap().to_rfc3339(),
+            "2026-03-08T12:00:00+00:00"
+        );
+        assert_eq!(
+            parse_when_at("2026-03-01T08:00:00+01:00", now).unwrap().to_rfc3339(),
+            "2026-03-01T07:00:00+00:00"
+        );
+        assert!(parse_when_at("soon", now).is_err());
+        assert!(parse_when_at("5w", now).is_err());
+        assert!(parse_when_at("", now).is_err());
+        assert!(parse_when_at("5é", now).is_err());
+        assert!(parse_when_at("3µ", now).is_err());
+    }
+}
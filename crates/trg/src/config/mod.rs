@@ -8,15 +8,22 @@
 //!   - a TOML array mixing the above two, concatenated in order.
 //!
 //! Inline `{ env = "..." }` is rejected outside the `vars` table.
+//!
+//! OAuth credential storage is selected with `credential_store`, either
+//! globally under `[mcp]` or per server; the encrypted-file backend reads its
+//! location and passphrase source from `[mcp.credential_file]`.
 
 mod var;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use http::HeaderName;
+use regex::Regex;
 pub use secrecy::SecretString;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 pub use var::{Segment, VarRef, VarResolveError, VarSource, VarTemplate};
 
 #[derive(Debug, thiserror::Error)]
@@ -47,6 +54,89 @@ pub enum ConfigError {
 
     #[error("duplicate header `{name}` collides with `{existing}` after canonicalization")]
     DuplicateHeader { name: String, existing: String },
+
+    #[error("`[mcp.credential_file]` sets both `passphrase_env` and `passphrase_command`; pick one")]
+    ConflictingPassphraseSource,
+
+    #[error("`[mcp.credential_file]` `passphrase_command` must not be empty")]
+    EmptyPassphraseCommand,
+
+    #[error("invalid `[mcp.servers.<name>.oauth]`: {0}")]
+    InvalidOAuth(String),
+
+    #[error("invalid account label `{0}`: use 1-64 characters from A-Z a-z 0-9 . _ - + @")]
+    InvalidAccount(String),
+
+    #[error("invalid `redact_patterns` entry `{pattern}`: {cause}")]
+    InvalidRedactPattern { pattern: String, cause: String },
+
+    #[error("invalid [mcp.servers.<name>.audit]: {0}")]
+    InvalidAudit(String),
+
+    #[error("invalid [mcp.servers.<name>.cache]: {0}")]
+    InvalidCache(String),
+}
+
+/// Default env var holding the encrypted credential file passphrase when
+/// `[mcp.credential_file]` names no source.
+pub const DEFAULT_PASSPHRASE_ENV: &str = "TRG_CREDENTIALS_PASSPHRASE";
+
+/// Where OAuth credentials for an MCP server are persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CredentialBackend {
+    /// macOS login Keychain via `/usr/bin/security`.
+    Keychain,
+    /// freedesktop.org Secret Service over D-Bus via `secret-tool`.
+    SecretService,
+    /// One passphrase-encrypted file per server under `[mcp.credential_file].dir`.
+    EncryptedFile,
+}
+
+impl CredentialBackend {
+    /// Platform default when neither `[mcp]` nor the server sets `credential_store`.
+    pub fn platform_default() -> Self {
+        if cfg!(target_os = "macos") {
+            CredentialBackend::Keychain
+        } else {
+            CredentialBackend::SecretService
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CredentialBackend::Keychain => "keychain",
+            CredentialBackend::SecretService => "secret-service",
+            CredentialBackend::EncryptedFile => "encrypted-file",
+        }
+    }
+}
+
+impl std::fmt::Display for CredentialBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How the encrypted-file backend obtains its passphrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassphraseSource {
+    Env(String),
+    /// argv of a helper (e.g. `["pass", "show", "trg"]`); stdout minus the trailing newline is the passphrase.
+    Command(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialFileSettings {
+    pub dir: PathBuf,
+    pub passphrase: PassphraseSource,
+}
+
+/// Resolved `credential_store` selection for one server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialStoreSettings {
+    pub backend: CredentialBackend,
+    pub file: CredentialFileSettings,
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,6 +151,21 @@ struct FileRoot {
 struct McpSection {
     #[serde(default)]
     servers: HashMap<String, McpServerEntryRaw>,
+    #[serde(default)]
+    credential_store: Option<CredentialBackend>,
+    #[serde(default)]
+    credential_file: Option<CredentialFileRaw>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+struct CredentialFileRaw {
+    #[serde(default)]
+    dir: Option<PathBuf>,
+    #[serde(default)]
+    passphrase_env: Option<String>,
+    #[serde(default)]
+    passphrase_command: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -79,6 +184,191 @@ struct McpServerEntryRaw {
     headers: Option<HashMap<String, VarTemplate>>,
     #[serde(default)]
     vars: Option<HashMap<String, VarSource>>,
+    #[serde(default)]
+    credential_store: Option<CredentialBackend>,
+    #[serde(default)]
+    oauth: Option<OAuthRaw>,
+    #[serde(default)]
+    account: Option<String>,
+    #[serde(default)]
+    redact_responses: bool,
+    #[serde(default)]
+    redact_patterns: Vec<String>,
+    #[serde(default)]
+    cache: Option<CacheRaw>,
+    #[serde(default)]
+    max_concurrent_requests: Option<NonZeroU32>,
+    #[serde(default)]
+    requests_per_minute: Option<NonZeroU32>,
+    #[serde(default)]
+    tool_limits: BTreeMap<String, RequestLimitsRaw>,
+    #[serde(default)]
+    failover_urls: Vec<VarTemplate>,
+    #[serde(default)]
+    failover: FailoverStrategy,
+    #[serde(default)]
+    validate_tool_arguments: Option<bool>,
+    #[serde(default)]
+    audit: Option<AuditRaw>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+struct AuditRaw {
+    #[serde(default)]
+    arguments: AuditArguments,
+    #[serde(default)]
+    dir: Option<PathBuf>,
+    #[serde(default)]
+    max_file_size: Option<u64>,
+    #[serde(default)]
+    max_files: Option<u32>,
+}
+
+/// How `tools/call` arguments appear in the audit log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditArguments {
+    /// SHA-256 of the canonical JSON: proves what was sent without storing it.
+    #[default]
+    Hash,
+    /// The arguments with secrets scrubbed as for `redact_responses`.
+    Redact,
+    /// Nothing about the arguments.
+    Omit,
+}
+
+/// Default size at which an audit log is rotated.
+pub const DEFAULT_AUDIT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated audit files kept per server.
+pub const DEFAULT_AUDIT_MAX_FILES: u32 = 10;
+
+/// `[mcp.servers.<name>.audit]`: append-only JSONL record of the server's `tools/call` traffic.
+///
+/// The proxy writes `<dir>/<server>.jsonl`. Once the file reaches `max_file_size` bytes it
+/// becomes `<server>.jsonl.1`, older files shift up, and anything past `max_files` is deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSettings {
+    pub arguments: AuditArguments,
+    pub dir: PathBuf,
+    pub max_file_size: u64,
+    pub max_files: u32,
+}
+
+/// Where audit logs live when `[mcp.servers.<name>.audit]` sets no `dir`.
+pub fn default_audit_dir() -> PathBuf {
+    env_data_dir().join("trg").join("audit")
+}
+
+/// How `trg mcp proxy` picks among `url` and `failover_urls` when an upstream fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailoverStrategy {
+    /// Always prefer the earliest healthy URL in the list, and return to `url` once it recovers.
+    #[default]
+    PrimaryThenSecondary,
+    /// Move on to the next URL after the one that failed, wrapping around.
+    RoundRobin,
+}
+
+impl FailoverStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FailoverStrategy::PrimaryThenSecondary => "primary-then-secondary",
+            FailoverStrategy::RoundRobin => "round-robin",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+struct RequestLimitsRaw {
+    #[serde(default)]
+    max_concurrent_requests: Option<NonZeroU32>,
+    #[serde(default)]
+    requests_per_minute: Option<NonZeroU32>,
+}
+
+impl From<RequestLimitsRaw> for RequestLimits {
+    fn from(raw: RequestLimitsRaw) -> Self {
+        Self {
+            max_concurrent: raw.max_concurrent_requests,
+            per_minute: raw.requests_per_minute,
+        }
+    }
+}
+
+/// Client-side caps on `tools/call` traffic; `None` leaves that dimension unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Calls awaiting a response at any one time.
+    pub max_concurrent: Option<NonZeroU32>,
+    /// Calls started within any sliding 60-second window.
+    pub per_minute: Option<NonZeroU32>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+struct OAuthRaw {
+    #[serde(default)]
+    scopes: Vec<String>,
+    #[serde(default)]
+    client_id: Option<String>,
+    #[serde(default)]
+    client_secret: Option<VarTemplate>,
+    #[serde(default)]
+    redirect_port: Option<u16>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+struct CacheRaw {
+    #[serde(default)]
+    ttl: BTreeMap<String, u64>,
+    #[serde(default)]
+    disk: bool,
+    #[serde(default)]
+    dir: Option<PathBuf>,
+}
+
+/// MCP methods whose results `trg mcp proxy` may cache: reads with no side effects whose
+/// staleness the server announces through a `list_changed` / `updated` notification.
+pub const CACHEABLE_METHODS: &[&str] = &[
+    "tools/list",
+    "prompts/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+];
+
+/// `[mcp.servers.<name>.cache]`: which responses the proxy answers locally, and for how long.
+///
+/// Empty `ttls` disables caching. Entries live in memory and, with `disk = true`, also
+/// under `dir` so they survive the host restarting the proxy.
+#[derive(Debug, Clone, Default)]
+pub struct CacheSettings {
+    /// Method name (one of [`CACHEABLE_METHODS`]) to time-to-live.
+    pub ttls: BTreeMap<String, Duration>,
+    /// Persistent cache root, when `disk = true`; the proxy keeps one subdirectory per server.
+    pub dir: Option<PathBuf>,
+}
+
+/// `[mcp.servers.<name>.oauth]`: how `trg` presents itself to the authorization server.
+///
+/// Everything is optional. Without `client_id` the client registers itself
+/// dynamically (RFC 7591) on first login; without `redirect_port` the
+/// loopback redirect URI uses a random port.
+#[derive(Debug, Clone, Default)]
+pub struct OAuthSettings {
+    /// Requested on the authorization URL (and hence the token); empty lets the AS pick its defaults.
+    pub scopes: Vec<String>,
+    /// Pre-registered client id; skips dynamic client registration.
+    pub client_id: Option<String>,
+    /// Secret for a confidential pre-registered client.
+    pub client_secret: Option<SecretString>,
+    /// Fixed port for `http://127.0.0.1:<port>/oauth/callback`, for clients registered with an exact redirect URI.
+    pub redirect_port: Option<u16>,
 }
 
 /// Resolved server profile for MCP `proxy`.
@@ -90,12 +380,52 @@ pub struct ResolvedMcpServer {
     pub initial_retry_interval: Option<u64>,
     pub override_protocol_version: Option<String>,
     pub http_headers: HashMap<HeaderName, SecretString>,
+    pub credential_store: CredentialStoreSettings,
+    pub oauth: OAuthSettings,
+    /// Which stored OAuth login to use when several exist for this server (`None` = the default one).
+    pub account: Option<String>,
+    /// Scrub secrets from `tools/call` / `resources/read` results before they reach the host.
+    pub redact_responses: bool,
+    /// Extra patterns applied after the built-in detectors when `redact_responses` is on.
+    pub redact_patterns: Vec<Regex>,
+    pub cache: CacheSettings,
+    /// Server-wide `tools/call` limits.
+    pub limits: RequestLimits,
+    /// Per-tool limits, enforced in addition to `limits`.
+    pub tool_limits: BTreeMap<String, RequestLimits>,
+    /// Alternates to `url`, in order; empty means no failover.
+    pub failover_urls: Vec<SecretString>,
+    pub failover: FailoverStrategy,
+    /// Check `tools/call` arguments against the tool's `inputSchema` before forwarding
+    /// (default on; needs the `schema-validation` feature).
+    pub validate_tool_arguments: bool,
+    /// `None` unless the server has an `[mcp.servers.<name>.audit]` table.
+    pub audit: Option<AuditSettings>,
 }
 
 pub fn trg_config_path() -> PathBuf {
     env_config_dir().join("trg").join("config.toml")
 }
 
+fn env_data_dir() -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).unwrap_or_else(|| {
+        std::env::var_os("HOME").map(PathBuf::from).map_or_else(
+            || PathBuf::from("/").join(".local").join("share"),
+            |home| home.join(".local").join("share"),
+        )
+    })
+}
+
+fn env_cache_dir() -> PathBuf {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            std::env::var_os("HOME")
+                .map(PathBuf::from)
+                .map_or_else(|| PathBuf::from("/").join(".cache"), |home| home.join(".cache"))
+        })
+}
+
 fn env_config_dir() -> PathBuf {
     std::env::var_os("XDG_CONFIG_HOME")
         .map(PathBuf::from)
@@ -110,7 +440,99 @@ pub fn load_mcp_server(selected_name: &str) -> Result<ResolvedMcpServer, ConfigE
     load_mcp_server_at(&trg_config_path(), selected_name)
 }
 
-fn load_mcp_server_at(path: &Path, selected_name: &str) -> Result<ResolvedMcpServer, ConfigError> {
+/// Credential store selection for `server_name` without resolving its `url`/headers.
+///
+/// Used by `trg mcp auth status`/`logout`, which must work when env-backed vars are
+/// unset or the server has since been removed from config: a missing config file
+/// or unknown server falls back to the global `[mcp]` setting, then the platform default.
+pub fn load_credential_store(server_name: &str) -> Result<CredentialStoreSettings, ConfigError> {
+    load_credential_store_at(&trg_config_path(), server_name)
+}
+
+fn load_credential_store_at(path: &Path, server_name: &str) -> Result<CredentialStoreSettings, ConfigError> {
+    let mcp = match read_root(path) {
+        Ok(root) => root.mcp,
+        Err(ConfigError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    let Some(mcp) = mcp else {
+        return resolve_credential_store(None, None, None);
+    };
+    let server_override = mcp.servers.get(server_name).and_then(|s| s.credential_store);
+    resolve_credential_store(mcp.credential_store, server_override, mcp.credential_file.as_ref())
+}
+
+/// Credential stores referenced by config: the `[mcp]`-level default (used for servers
+/// no longer in config) and each configured server's own selection.
+#[derive(Debug, Clone)]
+pub struct CredentialStoreLayout {
+    pub default: CredentialStoreSettings,
+    pub servers: BTreeMap<String, CredentialStoreSettings>,
+}
+
+/// Every server's credential store selection without resolving `url`/headers, for
+/// `trg mcp auth list`. A missing config file yields no servers and the platform default.
+pub fn load_credential_store_layout() -> Result<CredentialStoreLayout, ConfigError> {
+    load_credential_store_layout_at(&trg_config_path())
+}
+
+fn load_credential_store_layout_at(path: &Path) -> Result<CredentialStoreLayout, ConfigError> {
+    let mcp = match read_root(path) {
+        Ok(root) => root.mcp,
+        Err(ConfigError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    let Some(mcp) = mcp else {
+        return Ok(CredentialStoreLayout {
+            default: resolve_credential_store(None, None, None)?,
+            servers: BTreeMap::new(),
+        });
+    };
+    let file = mcp.credential_file.as_ref();
+    let servers = mcp
+        .servers
+        .iter()
+        .map(|(name, entry)| {
+            let settings = resolve_credential_store(mcp.credential_store, entry.credential_store, file)?;
+            Ok((name.clone(), settings))
+        })
+        .collect::<Result<_, ConfigError>>()?;
+    Ok(CredentialStoreLayout {
+        default: resolve_credential_store(mcp.credential_store, None, file)?,
+        servers,
+    })
+}
+
+fn resolve_credential_store(
+    global: Option<CredentialBackend>,
+    server_override: Option<CredentialBackend>,
+    file: Option<&CredentialFileRaw>,
+) -> Result<CredentialStoreSettings, ConfigError> {
+    let backend = server_override
+        .or(global)
+        .unwrap_or_else(CredentialBackend::platform_default);
+
+    let file = file.cloned().unwrap_or_default();
+    let passphrase = match (file.passphrase_env, file.passphrase_command) {
+        (Some(_), Some(_)) => return Err(ConfigError::ConflictingPassphraseSource),
+        (Some(env), None) => PassphraseSource::Env(env),
+        (None, Some(cmd)) if cmd.is_empty() || cmd[0].trim().is_empty() => {
+            return Err(ConfigError::EmptyPassphraseCommand);
+        }
+        (None, Some(cmd)) => PassphraseSource::Command(cmd),
+        (None, None) => PassphraseSource::Env(DEFAULT_PASSPHRASE_ENV.to_string()),
+    };
+    let dir = file
+        .dir
+        .unwrap_or_else(|| env_data_dir().join("trg").join("mcp-credentials"));
+
+    Ok(CredentialStoreSettings {
+        backend,
+        file: CredentialFileSettings { dir, passphrase },
+    })
+}
+
+fn read_root(path: &Path) -> Result<FileRoot, ConfigError> {
     let text = match std::fs::read_to_string(path) {
         Ok(t) => t,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
@@ -119,11 +541,17 @@ fn load_mcp_server_at(path: &Path, selected_name: &str) -> Result<ResolvedMcpSer
         Err(_) => return Err(ConfigError::NotFound(path.to_path_buf())),
     };
 
-    let root: FileRoot = toml::from_str(&text)?;
-    let servers = root
+    Ok(toml::from_str(&text)?)
+}
+
+/// [`load_mcp_server`] reading the config file at `path`.
+pub(crate) fn load_mcp_server_at(path: &Path, selected_name: &str) -> Result<ResolvedMcpServer, ConfigError> {
+    let root = read_root(path)?;
+    let mcp = root
         .mcp
-        .and_then(|m| (!m.servers.is_empty()).then_some(m.servers))
+        .and_then(|m| (!m.servers.is_empty()).then_some(m))
         .ok_or(ConfigError::NoMcpServers)?;
+    let servers = &mcp.servers;
 
     let Some(raw) = servers.get(selected_name) else {
         let names: Vec<_> = servers.keys().cloned().collect();
@@ -148,6 +576,14 @@ fn load_mcp_server_at(path: &Path, selected_name: &str) -> Result<ResolvedMcpSer
     if url_string.trim().is_empty() {
         return Err(ConfigError::EmptyUrl);
     }
+    let mut failover_urls = Vec::with_capacity(raw.failover_urls.len());
+    for template in &raw.failover_urls {
+        let url = template.resolve(&resolved_vars)?;
+        if url.trim().is_empty() {
+            return Err(ConfigError::EmptyUrl);
+        }
+        failover_urls.push(SecretString::new(url.into_boxed_str()));
+    }
 
     let mut http_headers = HashMap::new();
     let mut header_origins: HashMap<HeaderName, String> = HashMap::new();
@@ -177,6 +613,17 @@ fn load_mcp_server_at(path: &Path, selected_name: &str) -> Result<ResolvedMcpSer
         }
     }
 
+    let credential_store =
+        resolve_credential_store(mcp.credential_store, raw.credential_store, mcp.credential_file.as_ref())?;
+    let oauth = match &raw.oauth {
+        None => OAuthSettings::default(),
+        Some(oauth) => resolve_oauth(oauth, &resolved_vars)?,
+    };
+    let cache = match &raw.cache {
+        None => CacheSettings::default(),
+        Some(cache) => resolve_cache(cache)?,
+    };
+
     Ok(ResolvedMcpServer {
         url: SecretString::new(url_string.into_boxed_str()),
         transport: raw.transport.clone(),
@@ -184,9 +631,186 @@ fn load_mcp_server_at(path: &Path, selected_name: &str) -> Result<ResolvedMcpSer
         initial_retry_interval: raw.initial_retry_interval,
         override_protocol_version: raw.override_protocol_version.clone(),
         http_headers,
+        credential_store,
+        oauth,
+        account: raw.account.clone().map(validate_account).transpose()?,
+        redact_responses: raw.redact_responses,
+        redact_patterns: raw
+            .redact_patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| ConfigError::InvalidRedactPattern {
+                    pattern: pattern.clone(),
+                    cause: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        cache,
+        limits: RequestLimits {
+            max_concurrent: raw.max_concurrent_requests,
+            per_minute: raw.requests_per_minute,
+        },
+        tool_limits: raw
+            .tool_limits
+            .iter()
+            .map(|(tool, limits)| (tool.clone(), (*limits).into()))
+            .collect(),
+        failover_urls,
+        failover: raw.failover,
+        validate_tool_arguments: raw.validate_tool_arguments.unwrap_or(true),
+        audit: raw.audit.as_ref().map(resolve_audit).transpose()?,
     })
 }
 
+/// Check an OAuth account label (`account = "..."`, `--account`). Labels become part of the
+/// credential store key, so they are kept to a conservative character set.
+pub fn validate_account(label: String) -> Result<String, ConfigError> {
+    let valid = (1..=64).contains(&label.len())
+        && label
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-' | b'+' | b'@'));
+    if valid {
+        Ok(label)
+    } else {
+        Err(ConfigError::InvalidAccount(label))
+    }
+}
+
+/// The `account` configured for `server_name` without resolving its `url`/headers; like
+/// [`load_credential_store`], a missing config file or unknown server yields `None`.
+pub fn load_account(server_name: &str) -> Result<Option<String>, ConfigError> {
+    load_account_at(&trg_config_path(), server_name)
+}
+
+fn load_account_at(path: &Path, server_name: &str) -> Result<Option<String>, ConfigError> {
+    let mcp = match read_root(path) {
+        Ok(root) => root.mcp,
+        Err(ConfigError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    mcp.and_then(|mcp| mcp.servers.get(server_name).and_then(|s| s.account.clone()))
+        .map(validate_account)
+        .transpose()
+}
+
+fn resolve_oauth(raw: &OAuthRaw, vars: &HashMap<String, String>) -> Result<OAuthSettings, ConfigError> {
+    if let Some(scope) = raw
+        .scopes
+        .iter()
+        .find(|s| s.trim().is_empty() || s.contains(char::is_whitespace))
+    {
+        return Err(ConfigError::InvalidOAuth(format!(
+            "scope `{scope}` must be a single non-empty token; list each scope separately"
+        )));
+    }
+
+    let client_id = match &raw.client_id {
+        Some(id) if id.trim().is_empty() => {
+            return Err(ConfigError::InvalidOAuth("`client_id` must not be empty".into()));
+        }
+        other => other.clone(),
+    };
+
+    let client_secret = match &raw.client_secret {
+        None => None,
+        Some(_) if client_id.is_none() => {
+            return Err(ConfigError::InvalidOAuth(
+                "`client_secret` requires a pre-registered `client_id`".into(),
+            ));
+        }
+        Some(template) => {
+            let secret = template.resolve(vars)?;
+            if secret.trim().is_empty() {
+                return Err(ConfigError::InvalidOAuth("`client_secret` must not be empty".into()));
+            }
+            Some(SecretString::new(secret.into_boxed_str()))
+        }
+    };
+
+    if raw.redirect_port == Some(0) {
+        return Err(ConfigError::InvalidOAuth(
+            "`redirect_port` must be 1-65535; omit it for a random port".into(),
+        ));
+    }
+
+    Ok(OAuthSettings {
+        scopes: raw.scopes.clone(),
+        client_id,
+        client_secret,
+        redirect_port: raw.redirect_port,
+    })
+}
+
+fn resolve_audit(raw: &AuditRaw) -> Result<AuditSettings, ConfigError> {
+    if raw.max_file_size == Some(0) {
+        return Err(ConfigError::InvalidAudit(
+            "`max_file_size` must be at least 1 byte".into(),
+        ));
+    }
+    if raw.max_files == Some(0) {
+        return Err(ConfigError::InvalidAudit(
+            "`max_files` must be at least 1; rotated records would be deleted immediately".into(),
+        ));
+    }
+    Ok(AuditSettings {
+        arguments: raw.arguments,
+        dir: raw.dir.clone().unwrap_or_else(default_audit_dir),
+        max_file_size: raw.max_file_size.unwrap_or(DEFAULT_AUDIT_MAX_FILE_SIZE),
+        max_files: raw.max_files.unwrap_or(DEFAULT_AUDIT_MAX_FILES),
+    })
+}
+
+/// Every directory holding audit logs: the default one plus any server's own `dir`, for
+/// `trg mcp audit`. Servers are read without resolving `url`/headers; a missing config
+/// file yields just the default.
+pub fn load_audit_dirs() -> Result<Vec<PathBuf>, ConfigError> {
+    load_audit_dirs_at(&trg_config_path())
+}
+
+fn load_audit_dirs_at(path: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+    let mcp = match read_root(path) {
+        Ok(root) => root.mcp,
+        Err(ConfigError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    let mut dirs = vec![default_audit_dir()];
+    for raw in mcp.iter().flat_map(|mcp| mcp.servers.values()) {
+        if let Some(dir) = raw.audit.as_ref().and_then(|audit| audit.dir.clone()) {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+fn resolve_cache(raw: &CacheRaw) -> Result<CacheSettings, ConfigError> {
+    let mut ttls = BTreeMap::new();
+    for (method, secs) in &raw.ttl {
+        if !CACHEABLE_METHODS.contains(&method.as_str()) {
+            return Err(ConfigError::InvalidCache(format!(
+                "`{method}` cannot be cached; supported methods: {}",
+                CACHEABLE_METHODS.join(", ")
+            )));
+        }
+        if *secs == 0 {
+            return Err(ConfigError::InvalidCache(format!(
+                "ttl for `{method}` must be at least 1 second; omit the method to disable caching"
+            )));
+        }
+        ttls.insert(method.clone(), Duration::from_secs(*secs));
+    }
+    if raw.dir.is_some() && !raw.disk {
+        return Err(ConfigError::InvalidCache("`dir` requires `disk = true`".into()));
+    }
+    let dir = raw.disk.then(|| {
+        raw.dir
+            .clone()
+            .unwrap_or_else(|| env_cache_dir().join("trg").join("mcp-cache"))
+    });
+    Ok(CacheSettings { ttls, dir })
+}
+
 #[cfg(test)]
 mod tests {
     use secrecy::ExposeSecret as _;
@@ -670,6 +1294,552 @@ transport = "from-toml"
         assert_eq!(resolved.transport.as_deref(), Some("from-toml"));
     }
 
+    #[test]
+    fn credential_store_defaults_to_platform_backend() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+"#,
+        );
+        let resolved = load_at(&path, "s1").unwrap();
+        assert_eq!(resolved.credential_store.backend, CredentialBackend::platform_default());
+        assert_eq!(
+            resolved.credential_store.file.passphrase,
+            PassphraseSource::Env(DEFAULT_PASSPHRASE_ENV.to_string())
+        );
+    }
+
+    #[test]
+    fn credential_store_server_override_beats_global() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp]
+credential_store = "secret-service"
+
+[mcp.credential_file]
+dir = "/tmp/trg-creds"
+passphrase_command = ["pass", "show", "trg"]
+
+[mcp.servers.global]
+url = "https://ok"
+
+[mcp.servers.local]
+url = "https://ok"
+credential_store = "encrypted-file"
+"#,
+        );
+        let global = load_at(&path, "global").unwrap().credential_store;
+        assert_eq!(global.backend, CredentialBackend::SecretService);
+
+        let local = load_at(&path, "local").unwrap().credential_store;
+        assert_eq!(local.backend, CredentialBackend::EncryptedFile);
+        assert_eq!(local.file.dir, PathBuf::from("/tmp/trg-creds"));
+        assert_eq!(
+            local.file.passphrase,
+            PassphraseSource::Command(vec!["pass".into(), "show".into(), "trg".into()])
+        );
+    }
+
+    #[test]
+    fn credential_store_rejects_unknown_backend() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+credential_store = "plaintext"
+"#,
+        );
+        assert!(matches!(load_at(&path, "s1").unwrap_err(), ConfigError::Toml(_)));
+    }
+
+    #[test]
+    fn credential_store_rejects_conflicting_passphrase_sources() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.credential_file]
+passphrase_env = "X"
+passphrase_command = ["y"]
+
+[mcp.servers.s1]
+url = "https://ok"
+"#,
+        );
+        assert!(matches!(
+            load_at(&path, "s1").unwrap_err(),
+            ConfigError::ConflictingPassphraseSource
+        ));
+    }
+
+    #[test]
+    fn credential_store_rejects_empty_passphrase_command() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.credential_file]
+passphrase_command = []
+
+[mcp.servers.s1]
+url = "https://ok"
+"#,
+        );
+        assert!(matches!(
+            load_at(&path, "s1").unwrap_err(),
+            ConfigError::EmptyPassphraseCommand
+        ));
+    }
+
+    #[test]
+    fn credential_store_lookup_tolerates_unknown_server_and_unset_vars() {
+        let missing = unique_integration_env("TRG_CRED_LOOKUP");
+        std::env::remove_var(&missing);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let cfg = format!(
+            r#"
+[mcp]
+credential_store = "encrypted-file"
+
+[mcp.servers.s]
+url = {{ var = "u" }}
+credential_store = "secret-service"
+
+[mcp.servers.s.vars]
+u = {{ env = "{missing}" }}
+"#,
+        );
+        write_secure_config(&path, &cfg);
+        assert_eq!(
+            load_credential_store_at(&path, "s").unwrap().backend,
+            CredentialBackend::SecretService
+        );
+        assert_eq!(
+            load_credential_store_at(&path, "removed").unwrap().backend,
+            CredentialBackend::EncryptedFile
+        );
+    }
+
+    #[test]
+    fn request_limits_resolve_with_tool_overrides() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.limited]
+url = "https://ok"
+max_concurrent_requests = 4
+requests_per_minute = 60
+
+[mcp.servers.limited.tool_limits.search]
+max_concurrent_requests = 1
+"#,
+        );
+        let limited = load_mcp_server_at(&path, "limited").unwrap();
+        assert_eq!(limited.limits.max_concurrent, NonZeroU32::new(4));
+        assert_eq!(limited.limits.per_minute, NonZeroU32::new(60));
+        assert_eq!(
+            limited.tool_limits["search"],
+            RequestLimits {
+                max_concurrent: NonZeroU32::new(1),
+                per_minute: None,
+            }
+        );
+
+        let zero = dir.path().join("zero.toml");
+        write_secure_config(
+            &zero,
+            "[mcp.servers.zero]\nurl = \"https://ok\"\nrequests_per_minute = 0\n",
+        );
+        assert!(matches!(load_mcp_server_at(&zero, "zero"), Err(ConfigError::Toml(_))));
+    }
+
+    #[test]
+    fn failover_urls_resolve_templates_and_strategy() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.multi]
+url = "https://us.example/mcp"
+failover_urls = [["https://", { var = "region" }, ".example/mcp"], "https://ap.example/mcp"]
+failover = "round-robin"
+vars = { region = "eu" }
+
+[mcp.servers.single]
+url = "https://ok"
+"#,
+        );
+        let multi = load_mcp_server_at(&path, "multi").unwrap();
+        let urls: Vec<_> = multi.failover_urls.iter().map(|u| u.expose_secret()).collect();
+        assert_eq!(urls, ["https://eu.example/mcp", "https://ap.example/mcp"]);
+        assert_eq!(multi.failover, FailoverStrategy::RoundRobin);
+
+        let single = load_mcp_server_at(&path, "single").unwrap();
+        assert!(single.failover_urls.is_empty());
+        assert_eq!(single.failover, FailoverStrategy::PrimaryThenSecondary);
+    }
+
+    #[test]
+    fn audit_settings_resolve_defaults_and_dirs() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.plain]
+url = "https://ok"
+audit = {}
+
+[mcp.servers.custom]
+url = "https://ok"
+audit = { arguments = "redact", dir = "/var/log/trg-audit", max_file_size = 4096, max_files = 2 }
+
+[mcp.servers.off]
+url = "https://ok"
+"#,
+        );
+        let plain = load_mcp_server_at(&path, "plain").unwrap().audit.unwrap();
+        assert_eq!(plain.arguments, AuditArguments::Hash);
+        assert_eq!(plain.dir, default_audit_dir());
+        assert_eq!(plain.max_file_size, DEFAULT_AUDIT_MAX_FILE_SIZE);
+        assert_eq!(plain.max_files, DEFAULT_AUDIT_MAX_FILES);
+
+        let custom = load_mcp_server_at(&path, "custom").unwrap().audit.unwrap();
+        assert_eq!(custom.arguments, AuditArguments::Redact);
+        assert_eq!(custom.max_file_size, 4096);
+        assert!(load_mcp_server_at(&path, "off").unwrap().audit.is_none());
+
+        assert_eq!(
+            load_audit_dirs_at(&path).unwrap(),
+            [default_audit_dir(), PathBuf::from("/var/log/trg-audit")]
+        );
+
+        let zero = dir.path().join("zero.toml");
+        write_secure_config(
+            &zero,
+            "[mcp.servers.z]\nurl = \"https://ok\"\naudit = { max_files = 0 }\n",
+        );
+        assert!(matches!(
+            load_mcp_server_at(&zero, "z"),
+            Err(ConfigError::InvalidAudit(_))
+        ));
+    }
+
+    #[test]
+    fn tool_argument_validation_defaults_on() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.strict]
+url = "https://ok"
+
+[mcp.servers.lenient]
+url = "https://ok"
+validate_tool_arguments = false
+"#,
+        );
+        assert!(load_mcp_server_at(&path, "strict").unwrap().validate_tool_arguments);
+        assert!(!load_mcp_server_at(&path, "lenient").unwrap().validate_tool_arguments);
+    }
+
+    #[test]
+    fn cache_settings_are_validated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.mem]
+url = "https://ok"
+cache = { ttl = { "tools/list" = 300, "resources/read" = 60 } }
+
+[mcp.servers.disk]
+url = "https://ok"
+cache = { ttl = { "prompts/list" = 30 }, disk = true, dir = "/tmp/trg-cache" }
+
+[mcp.servers.write]
+url = "https://ok"
+cache = { ttl = { "tools/call" = 30 } }
+
+[mcp.servers.zero]
+url = "https://ok"
+cache = { ttl = { "tools/list" = 0 } }
+
+[mcp.servers.nodisk]
+url = "https://ok"
+cache = { ttl = { "tools/list" = 5 }, dir = "/tmp/x" }
+"#,
+        );
+        let mem = load_mcp_server_at(&path, "mem").unwrap().cache;
+        assert_eq!(mem.ttls["tools/list"], Duration::from_secs(300));
+        assert_eq!(mem.ttls["resources/read"], Duration::from_secs(60));
+        assert!(mem.dir.is_none());
+        let disk = load_mcp_server_at(&path, "disk").unwrap().cache;
+        assert_eq!(disk.dir.as_deref(), Some(Path::new("/tmp/trg-cache")));
+        for name in ["write", "zero", "nodisk"] {
+            assert!(
+                matches!(load_mcp_server_at(&path, name), Err(ConfigError::InvalidCache(_))),
+                "{name} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn redaction_settings_compile_custom_patterns() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.on]
+url = "https://ok"
+redact_responses = true
+redact_patterns = ["sk_live_[0-9a-zA-Z]{24}"]
+
+[mcp.servers.off]
+url = "https://ok"
+
+[mcp.servers.bad]
+url = "https://ok"
+redact_responses = true
+redact_patterns = ["(unclosed"]
+"#,
+        );
+        let on = load_mcp_server_at(&path, "on").unwrap();
+        assert!(on.redact_responses);
+        assert_eq!(on.redact_patterns.len(), 1);
+        let off = load_mcp_server_at(&path, "off").unwrap();
+        assert!(!off.redact_responses);
+        assert!(off.redact_patterns.is_empty());
+        assert!(matches!(
+            load_mcp_server_at(&path, "bad"),
+            Err(ConfigError::InvalidRedactPattern { pattern, .. }) if pattern == "(unclosed"
+        ));
+    }
+
+    #[test]
+    fn account_label_is_resolved_and_validated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.work]
+url = "https://ok"
+account = "svc-bot@corp"
+
+[mcp.servers.personal]
+url = "https://ok"
+"#,
+        );
+        assert_eq!(
+            load_mcp_server_at(&path, "work").unwrap().account.as_deref(),
+            Some("svc-bot@corp")
+        );
+        assert_eq!(load_mcp_server_at(&path, "personal").unwrap().account, None);
+        assert_eq!(load_account_at(&path, "work").unwrap().as_deref(), Some("svc-bot@corp"));
+        assert_eq!(load_account_at(&path, "removed").unwrap(), None);
+
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.bad]
+url = "https://ok"
+account = "has space"
+"#,
+        );
+        assert!(matches!(
+            load_mcp_server_at(&path, "bad"),
+            Err(ConfigError::InvalidAccount(label)) if label == "has space"
+        ));
+        assert!(validate_account(String::new()).is_err());
+        assert!(validate_account("a#b".to_string()).is_err());
+    }
+
+    #[test]
+    fn credential_store_layout_lists_every_server() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp]
+credential_store = "encrypted-file"
+
+[mcp.servers.b]
+url = "https://b"
+credential_store = "secret-service"
+
+[mcp.servers.a]
+url = "https://a"
+"#,
+        );
+        let layout = load_credential_store_layout_at(&path).unwrap();
+        assert_eq!(layout.default.backend, CredentialBackend::EncryptedFile);
+        let servers: Vec<(&str, CredentialBackend)> = layout
+            .servers
+            .iter()
+            .map(|(name, settings)| (name.as_str(), settings.backend))
+            .collect();
+        assert_eq!(
+            servers,
+            [
+                ("a", CredentialBackend::EncryptedFile),
+                ("b", CredentialBackend::SecretService)
+            ]
+        );
+
+        let missing = load_credential_store_layout_at(&dir.path().join("missing.toml")).unwrap();
+        assert!(missing.servers.is_empty());
+    }
+
+    #[test]
+    fn credential_store_lookup_without_config_uses_platform_default() {
+        let dir = tempdir().unwrap();
+        let settings = load_credential_store_at(&dir.path().join("missing.toml"), "s").unwrap();
+        assert_eq!(settings.backend, CredentialBackend::platform_default());
+    }
+
+    #[test]
+    fn oauth_defaults_to_dynamic_registration() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+"#,
+        );
+        let oauth = load_at(&path, "s1").unwrap().oauth;
+        assert!(oauth.scopes.is_empty());
+        assert!(oauth.client_id.is_none());
+        assert!(oauth.client_secret.is_none());
+        assert!(oauth.redirect_port.is_none());
+    }
+
+    #[test]
+    fn oauth_table_resolves_secret_from_vars() {
+        let key = unique_integration_env("TRG_OAUTH_SECRET");
+        std::env::set_var(&key, "s3cret");
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let cfg = format!(
+            r#"
+[mcp.servers.corp]
+url = "https://corp.example/mcp"
+
+[mcp.servers.corp.vars]
+secret = {{ env = "{key}" }}
+
+[mcp.servers.corp.oauth]
+scopes = ["mcp:read", "mcp:write"]
+client_id = "trg-cli"
+client_secret = {{ var = "secret" }}
+redirect_port = 8765
+"#,
+        );
+        write_secure_config(&path, &cfg);
+        let oauth = load_at(&path, "corp").unwrap().oauth;
+        assert_eq!(oauth.scopes, vec!["mcp:read".to_string(), "mcp:write".to_string()]);
+        assert_eq!(oauth.client_id.as_deref(), Some("trg-cli"));
+        assert_eq!(oauth.client_secret.unwrap().expose_secret(), "s3cret");
+        assert_eq!(oauth.redirect_port, Some(8765));
+        std::env::remove_var(&key);
+    }
+
+    #[test]
+    fn oauth_rejects_secret_without_client_id() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+
+[mcp.servers.s1.oauth]
+client_secret = "x"
+"#,
+        );
+        let e = load_at(&path, "s1").unwrap_err();
+        assert!(
+            matches!(&e, ConfigError::InvalidOAuth(m) if m.contains("client_id")),
+            "{e:?}"
+        );
+    }
+
+    #[test]
+    fn oauth_rejects_space_separated_scope_string() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+
+[mcp.servers.s1.oauth]
+scopes = ["read write"]
+"#,
+        );
+        let e = load_at(&path, "s1").unwrap_err();
+        assert!(
+            matches!(&e, ConfigError::InvalidOAuth(m) if m.contains("read write")),
+            "{e:?}"
+        );
+    }
+
+    #[test]
+    fn oauth_rejects_port_zero_and_unknown_fields() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+
+[mcp.servers.s1.oauth]
+redirect_port = 0
+"#,
+        );
+        assert!(matches!(
+            load_at(&path, "s1").unwrap_err(),
+            ConfigError::InvalidOAuth(_)
+        ));
+
+        write_secure_config(
+            &path,
+            r#"
+[mcp.servers.s1]
+url = "https://ok"
+
+[mcp.servers.s1.oauth]
+scope = "read"
+"#,
+        );
+        assert!(matches!(load_at(&path, "s1").unwrap_err(), ConfigError::Toml(_)));
+    }
+
     #[test]
     fn load_missing_config_yields_not_found() {
         let dir = tempdir().unwrap();
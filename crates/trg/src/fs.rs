@@ -1,5 +1,5 @@
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub trait FileSystem {
     fn read_to_string(&self, path: &Path) -> io::Result<String>;
@@ -33,6 +33,45 @@ impl FileSystem for RealFS {
     }
 }
 
+/// `<path>.<n>`, the name of the `n`th rotated copy of `path`.
+pub fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Shift `path` → `path.1` → … → `path.<keep>`, overwriting the oldest. Missing
+/// intermediate files are skipped; `path` itself must exist.
+pub fn rotate_numbered(path: &Path, keep: u32) -> io::Result<()> {
+    for n in (1..keep).rev() {
+        match std::fs::rename(rotated_path(path, n), rotated_path(path, n + 1)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+/// `<path>.lock`, the sibling file whose `flock` serialises rotation of `path` between
+/// processes; `path` itself is renamed by the rotation, so it cannot carry the lock.
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// Block until this process holds the rotation lock of `path`; it is released when the
+/// returned file is dropped.
+pub fn lock_rotation(path: &Path) -> io::Result<std::fs::File> {
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(path))?;
+    lock.lock()?;
+    Ok(lock)
+}
+
 #[cfg(test)]
 pub mod testutil {
     use super::*;
@@ -4,6 +4,7 @@ compile_error!("trg only supports Unix targets (Linux, macOS). On Windows, build
 pub mod agentskills;
 pub mod commands;
 pub mod config;
+pub mod encoding;
 pub mod fs;
 pub mod oauth;
 pub mod telemetry;
@@ -23,7 +23,9 @@ async fn main() {
             AiCommands::Skills { command } => command.handle(&fs),
         },
         Commands::Mcp { command } => command.handle().await,
+        Commands::Logs(args) => args.handle(),
     };
 
+    trg::telemetry::shutdown();
     std::process::exit(exit_code);
 }
@@ -1,15 +1,18 @@
 //! Interactive OAuth 2.1 authorization-code + PKCE flow.
 
-use std::io::{stderr, stdin, IsTerminal};
+use std::io::{stderr, stdin, BufRead, IsTerminal, Write};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use reqwest::Url;
 use rmcp::transport::auth::{
-    AuthError, AuthorizationManager, AuthorizationSession, OAuthTokenResponse, StoredCredentials,
+    AuthError, AuthorizationManager, AuthorizationSession, OAuthClientConfig, OAuthTokenResponse, StoredCredentials,
 };
+use secrecy::{ExposeSecret, SecretString};
 use tiny_http::{Header, Response, Server};
 
+use crate::config::OAuthSettings;
+
 #[derive(Debug, thiserror::Error)]
 pub enum FlowError {
     #[error("stdin/stderr is not a TTY; OAuth requires an interactive browser session")]
@@ -34,56 +37,124 @@ pub enum FlowError {
     #[error("OAuth state mismatch (csrf protection): expected `{expected}`, got `{got}`")]
     StateMismatch { expected: String, got: String },
 
+    #[error("stdin closed before a redirect URL was pasted")]
+    ManualInputClosed,
+
     #[error(transparent)]
     Oauth(#[from] AuthError),
 }
 
+/// How the authorization code gets back to `trg` after the user approves in the browser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CallbackMode {
+    /// Listen on a loopback port and let the browser redirect to it.
+    #[default]
+    Loopback,
+    /// Print the authorization URL and read the final redirect URL from stdin.
+    ///
+    /// For containers and port-forwarding setups where the browser cannot reach
+    /// the loopback listener: the redirect fails in the browser, and the user
+    /// copies the URL from its address bar.
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct FlowConfig {
     /// Default 5 minutes. Override for tests.
     pub callback_timeout: std::time::Duration,
+    pub callback_mode: CallbackMode,
 }
 
 impl Default for FlowConfig {
     fn default() -> Self {
         Self {
             callback_timeout: Duration::from_secs(300),
+            callback_mode: CallbackMode::Loopback,
         }
     }
 }
 
-/// Drives the interactive OAuth flow: loopback redirect, browser launch, and callback handling.
+/// Drives the interactive OAuth flow: loopback redirect (or pasted redirect URL), browser launch,
+/// and callback handling.
+///
+/// `oauth` supplies the requested scopes and, when configured, a pre-registered client and
+/// fixed redirect port; otherwise the client registers dynamically with a random loopback port.
 /// Token exchange uses [`AuthorizationSession::handle_callback`] (rmcp `AuthorizationManager::exchange_code_for_token`).
 pub async fn run_authorization(
     auth_manager: AuthorizationManager,
-    scopes: &[&str],
+    oauth: &OAuthSettings,
     config: FlowConfig,
 ) -> Result<StoredCredentials, FlowError> {
-    if !stdin().is_terminal() || !stderr().is_terminal() {
+    if !is_interactive() {
         return Err(FlowError::NotATerminal);
     }
 
-    let server = Server::http("127.0.0.1:0").map_err(|e| FlowError::BindFailed(boxed_error_to_io(e)))?;
-    let server = Arc::new(server);
-
-    let port = loopback_port(server.as_ref())?;
-    let redirect_uri = format!("http://127.0.0.1:{port}/oauth/callback");
+    let server = match config.callback_mode {
+        CallbackMode::Loopback => {
+            let addr = format!("127.0.0.1:{}", oauth.redirect_port.unwrap_or(0));
+            let server = Server::http(addr).map_err(|e| FlowError::BindFailed(boxed_error_to_io(e)))?;
+            Some(Arc::new(server))
+        }
+        CallbackMode::Manual => None,
+    };
 
-    let session = AuthorizationSession::new(auth_manager, scopes, &redirect_uri, None, None).await?;
+    let port = match (&server, oauth.redirect_port) {
+        (Some(server), _) => loopback_port(server.as_ref())?,
+        (None, Some(port)) => port,
+        (None, None) => unused_loopback_port()?,
+    };
+    let redirect_uri = redirect_uri_for_port(port);
+
+    let scopes: Vec<&str> = oauth.scopes.iter().map(String::as_str).collect();
+    let session = match &oauth.client_id {
+        Some(client_id) => {
+            pre_registered_session(
+                auth_manager,
+                client_id,
+                oauth.client_secret.as_ref(),
+                &scopes,
+                &redirect_uri,
+            )
+            .await?
+        }
+        None => AuthorizationSession::new(auth_manager, &scopes, &redirect_uri, None, None).await?,
+    };
 
     let auth_url = session.get_authorization_url().to_string();
     let expected_state = oauth_state_from_authorization_url(&auth_url)
         .ok_or_else(|| AuthError::InternalError("authorization URL missing state parameter".to_string()))?;
-    eprintln!("OAuth: open this URL in your browser if it doesn't open automatically: {auth_url}");
-
-    let browser_err = open::that(&auth_url).err();
 
-    let wait_outcome = {
-        let server = server.clone();
-        let timeout = config.callback_timeout;
-        let expected_state = expected_state.clone();
-        tokio::task::spawn_blocking(move || wait_for_callback(server, timeout, expected_state))
-            .await
-            .map_err(|e| AuthError::InternalError(format!("OAuth callback task failed to run: {e}")))?
+    let (wait_outcome, browser_err) = match server {
+        Some(server) => {
+            eprintln!("OAuth: open this URL in your browser if it doesn't open automatically: {auth_url}");
+            let browser_err = open::that(&auth_url).err();
+
+            let timeout = config.callback_timeout;
+            let expected_state = expected_state.clone();
+            let outcome = tokio::task::spawn_blocking(move || wait_for_callback(server, timeout, expected_state))
+                .await
+                .map_err(|e| AuthError::InternalError(format!("OAuth callback task failed to run: {e}")))?;
+            (outcome, browser_err)
+        }
+        None => {
+            eprintln!("OAuth: open this URL in any browser and approve access:\n  {auth_url}");
+            eprintln!(
+                "After approving, the browser is redirected to {redirect_uri} (the page may fail to load). \
+                 Copy the full URL from the address bar and paste it here."
+            );
+
+            let expected_state = expected_state.clone();
+            let read = tokio::task::spawn_blocking(move || {
+                wait_for_pasted_callback(stdin().lock(), stderr(), &expected_state)
+            });
+            let outcome = match tokio::time::timeout(config.callback_timeout, read).await {
+                Ok(joined) => {
+                    joined.map_err(|e| AuthError::InternalError(format!("OAuth callback task failed to run: {e}")))?
+                }
+                Err(_) => CallbackWait::Timeout,
+            };
+            (outcome, None)
+        }
     };
 
     let (code, state) = match wait_outcome {
@@ -94,6 +165,7 @@ pub async fn run_authorization(
             }
             return Err(FlowError::CallbackTimeout(config.callback_timeout));
         }
+        CallbackWait::InputClosed => return Err(FlowError::ManualInputClosed),
         CallbackWait::StateMismatch { expected, got } => {
             return Err(FlowError::StateMismatch { expected, got });
         }
@@ -131,6 +203,42 @@ fn loopback_port(server: &Server) -> Result<u16, FlowError> {
     }
 }
 
+/// Whether [`run_authorization`] can run here: it needs a TTY on stdin and stderr.
+pub fn is_interactive() -> bool {
+    stdin().is_terminal() && stderr().is_terminal()
+}
+
+pub fn redirect_uri_for_port(port: u16) -> String {
+    format!("http://127.0.0.1:{port}/oauth/callback")
+}
+
+/// Configure `manager` with a client registered out of band instead of RFC 7591 dynamic registration.
+async fn pre_registered_session(
+    mut manager: AuthorizationManager,
+    client_id: &str,
+    client_secret: Option<&SecretString>,
+    scopes: &[&str],
+    redirect_uri: &str,
+) -> Result<AuthorizationSession, AuthError> {
+    let mut client =
+        OAuthClientConfig::new(client_id, redirect_uri).with_scopes(scopes.iter().map(|s| s.to_string()).collect());
+    if let Some(secret) = client_secret {
+        client = client.with_client_secret(secret.expose_secret());
+    }
+    manager.configure_client(client)?;
+    let auth_url = manager.get_authorization_url(scopes).await?;
+    // rmcp names this constructor for its scope-upgrade path, but it is just "wrap an
+    // already-configured manager and its authorization URL", which is what we need here.
+    Ok(AuthorizationSession::for_scope_upgrade(manager, auth_url, redirect_uri))
+}
+
+/// Reserve-and-release an ephemeral loopback port for the redirect URI when nothing will listen on it.
+fn unused_loopback_port() -> Result<u16, FlowError> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(FlowError::BindFailed)?;
+    let port = listener.local_addr().map_err(FlowError::BindFailed)?.port();
+    Ok(port)
+}
+
 fn granted_scopes_from_token_response(token: &OAuthTokenResponse) -> Vec<String> {
     let Ok(value) = serde_json::to_value(token) else {
         return Vec::new();
@@ -203,6 +311,7 @@ enum CallbackWait {
     StateMismatch { expected: String, got: String },
     Provider { error: String, description: Option<String> },
     Timeout,
+    InputClosed,
 }
 
 struct UnblockServer(Arc<Server>);
@@ -256,6 +365,52 @@ fn wait_for_callback(server: Arc<Server>, overall_timeout: Duration, expected_st
     }
 }
 
+/// Read pasted redirect URLs line by line until one carries a code (or provider error).
+/// Lines that are not a callback URL re-prompt instead of aborting, so a stray paste is recoverable.
+fn wait_for_pasted_callback(mut input: impl BufRead, mut prompt: impl Write, expected_state: &str) -> CallbackWait {
+    loop {
+        let _ = write!(prompt, "Redirect URL: ");
+        let _ = prompt.flush();
+
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => return CallbackWait::InputClosed,
+            Ok(_) => {}
+        }
+        let pasted = line.trim();
+        if pasted.is_empty() {
+            continue;
+        }
+
+        match parse_oauth_callback_url(pasted) {
+            Ok(ParsedOAuthCallback::Success { code, state }) if state == expected_state => {
+                return CallbackWait::Success { code, state };
+            }
+            Ok(ParsedOAuthCallback::Success { state, .. }) => {
+                return CallbackWait::StateMismatch {
+                    expected: expected_state.to_string(),
+                    got: state,
+                };
+            }
+            Ok(ParsedOAuthCallback::Provider { error, description }) => {
+                return CallbackWait::Provider { error, description };
+            }
+            Err(CallbackPathError::WrongPath) => {
+                let _ = writeln!(
+                    prompt,
+                    "That URL does not point at /oauth/callback; paste the final redirect URL."
+                );
+            }
+            Err(CallbackPathError::Incomplete) => {
+                let _ = writeln!(
+                    prompt,
+                    "That URL is missing the `code` or `state` parameter; paste the full redirect URL."
+                );
+            }
+        }
+    }
+}
+
 fn success_response() -> Response<std::io::Cursor<Vec<u8>>> {
     let body = "<html><body>Authentication complete. You can close this tab.</body></html>";
     html_response(body)
@@ -332,6 +487,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn flow_config_default_uses_loopback_callback() {
+        assert_eq!(FlowConfig::default().callback_mode, CallbackMode::Loopback);
+    }
+
+    #[tokio::test]
+    async fn pre_registered_session_skips_registration_and_pins_redirect_uri() {
+        let mut manager = AuthorizationManager::new("https://mcp.example.com/mcp").await.unwrap();
+        let mut metadata = rmcp::transport::auth::AuthorizationMetadata::default();
+        metadata.authorization_endpoint = "https://as.example.com/authorize".to_string();
+        metadata.token_endpoint = "https://as.example.com/token".to_string();
+        manager.set_metadata(metadata);
+
+        let secret = SecretString::new("shh".into());
+        let redirect_uri = redirect_uri_for_port(8765);
+        let session = pre_registered_session(manager, "trg-cli", Some(&secret), &["read", "write"], &redirect_uri)
+            .await
+            .unwrap();
+
+        let url = Url::parse(session.get_authorization_url()).unwrap();
+        let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(url.host_str(), Some("as.example.com"));
+        assert_eq!(query["client_id"], "trg-cli");
+        assert_eq!(query["redirect_uri"], "http://127.0.0.1:8765/oauth/callback");
+        assert_eq!(query["scope"], "read write");
+        assert!(query.contains_key("state"));
+        assert!(!session.get_authorization_url().contains("shh"));
+    }
+
+    #[test]
+    fn pasted_callback_returns_code_when_state_matches() {
+        let input = b"http://127.0.0.1:4242/oauth/callback?code=abc&state=xyz\n";
+        let outcome = wait_for_pasted_callback(&input[..], std::io::sink(), "xyz");
+        match outcome {
+            CallbackWait::Success { code, state } => {
+                assert_eq!(code, "abc");
+                assert_eq!(state, "xyz");
+            }
+            _ => panic!("expected success"),
+        }
+    }
+
+    #[test]
+    fn pasted_callback_rejects_state_mismatch() {
+        let input = b"http://127.0.0.1:4242/oauth/callback?code=abc&state=evil\n";
+        let outcome = wait_for_pasted_callback(&input[..], std::io::sink(), "xyz");
+        assert!(
+            matches!(outcome, CallbackWait::StateMismatch { ref expected, ref got } if expected == "xyz" && got == "evil")
+        );
+    }
+
+    #[test]
+    fn pasted_callback_reprompts_on_unusable_lines() {
+        let input = b"\nhttps://example.com/elsewhere\nhttp://127.0.0.1:1/oauth/callback?code=abc\n  http://127.0.0.1:1/oauth/callback?code=c&state=s  \n";
+        let mut prompt = Vec::new();
+        let outcome = wait_for_pasted_callback(&input[..], &mut prompt, "s");
+        assert!(matches!(outcome, CallbackWait::Success { ref code, .. } if code == "c"));
+        let prompt = String::from_utf8(prompt).unwrap();
+        assert!(prompt.contains("does not point at /oauth/callback"), "{prompt}");
+        assert!(prompt.contains("missing the `code` or `state`"), "{prompt}");
+    }
+
+    #[test]
+    fn pasted_callback_surfaces_provider_error() {
+        let input = b"http://127.0.0.1:1/oauth/callback?error=access_denied\n";
+        let outcome = wait_for_pasted_callback(&input[..], std::io::sink(), "s");
+        assert!(matches!(outcome, CallbackWait::Provider { ref error, .. } if error == "access_denied"));
+    }
+
+    #[test]
+    fn pasted_callback_eof_is_input_closed() {
+        let outcome = wait_for_pasted_callback(&b""[..], std::io::sink(), "s");
+        assert!(matches!(outcome, CallbackWait::InputClosed));
+    }
+
     #[test]
     fn flow_error_display_not_a_terminal() {
         assert_eq!(
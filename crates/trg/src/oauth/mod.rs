@@ -1,20 +1,27 @@
 //! OAuth 2.1 support for `trg mcp proxy`.
 //!
-//! Storage lives in [`store`]; the interactive browser/loopback dance lives in [`flow`].
-//! See `crates/trg/PLAN.md` for the milestone scope (macOS Keychain only for now).
+//! Storage lives in [`store`] (backend chosen by `credential_store` in config);
+//! the interactive browser/loopback dance lives in [`flow`].
 
 pub mod flow;
+pub mod refresh;
+pub mod revoke;
+pub mod step_up;
 pub mod store;
 
 use http::header::AUTHORIZATION;
-use rmcp::transport::auth::{AuthError, AuthorizationManager};
+use rmcp::transport::auth::{
+    AuthError, AuthorizationManager, AuthorizationMetadata, CredentialStore, OAuthClientConfig, StoredCredentials,
+};
 use secrecy::ExposeSecret;
+use tracing::warn;
 
 use crate::{
     config::{self, ResolvedMcpServer},
     oauth::{
-        flow::{run_authorization, FlowConfig, FlowError},
-        store::KeychainCredentialStore,
+        flow::{redirect_uri_for_port, run_authorization, FlowConfig, FlowError},
+        step_up::merge_scopes,
+        store::ConfiguredCredentialStore,
     },
 };
 
@@ -35,21 +42,34 @@ pub enum EnsureError {
     #[error("OAuth: {0}")]
     Flow(#[from] FlowError),
 
-    #[error("OAuth completed but credentials are missing from the keychain — refusing to start")]
-    MissingAfterFlow,
+    #[error("OAuth completed but credentials are missing from the `{0}` credential store — refusing to start")]
+    MissingAfterFlow(config::CredentialBackend),
+
+    #[error("`{0}` does not use OAuth (no discovery support, or static Authorization header configured)")]
+    NotOAuth(String),
+
+    #[error("no OAuth credentials stored for `{0}`; run `trg mcp auth login --server {0}` first")]
+    NotLoggedIn(String),
+}
+
+/// Stored credentials before and after an explicit refresh.
+pub struct RefreshOutcome {
+    pub previous: StoredCredentials,
+    pub current: StoredCredentials,
 }
 
 /// Resolve `server_name` from config and return a ready-to-use
 /// `AuthorizationManager` (running the interactive flow if needed) or signal
 /// that no OAuth is required.
-pub async fn ensure_credentials(server_name: &str) -> Result<EnsureOutcome, EnsureError> {
+pub async fn ensure_credentials(server_name: &str, flow: FlowConfig) -> Result<EnsureOutcome, EnsureError> {
     let resolved = config::load_mcp_server(server_name)?;
-    ensure_credentials_for(&resolved, server_name).await
+    ensure_credentials_for(&resolved, server_name, flow).await
 }
 
 pub async fn ensure_credentials_for(
     profile: &ResolvedMcpServer,
     server_name: &str,
+    flow: FlowConfig,
 ) -> Result<EnsureOutcome, EnsureError> {
     if profile.http_headers.contains_key(&AUTHORIZATION) {
         return Ok(EnsureOutcome::NoAuthRequired);
@@ -63,19 +83,123 @@ pub async fn ensure_credentials_for(
         Err(AuthError::NoAuthorizationSupport) => return Ok(EnsureOutcome::NoAuthRequired),
         Err(e) => return Err(e.into()),
     };
-    manager.set_metadata(metadata);
-    manager.set_credential_store(KeychainCredentialStore::new(server_name));
+    manager.set_metadata(metadata.clone());
+    let store =
+        ConfiguredCredentialStore::for_server(&profile.credential_store, server_name, profile.account.as_deref());
+
+    // A pre-registered client replaces whatever client id a previous (dynamic) login stored;
+    // those tokens were issued to a different client and cannot be refreshed by this one.
+    let stale_client = match &profile.oauth.client_id {
+        Some(configured) => match store.load().await? {
+            Some(stored) if stored.client_id != *configured => {
+                warn!(
+                    server = server_name,
+                    stored = %stored.client_id,
+                    configured = %configured,
+                    "stored OAuth credentials belong to a different client id; re-authorizing"
+                );
+                true
+            }
+            _ => false,
+        },
+        None => false,
+    };
+    manager.set_credential_store(store);
 
-    if manager.initialize_from_store().await? {
+    if !stale_client && manager.initialize_from_store().await? {
+        configure_pre_registered_client(&mut manager, profile)?;
         return Ok(EnsureOutcome::AlreadyAuthorized(manager));
     }
 
-    let _ = run_authorization(manager, &[], FlowConfig::default()).await?;
+    let mut issued = run_authorization(manager, &profile.oauth, flow).await?;
+    // As in `step_up`: a token response without `scope` granted what was requested.
+    if merge_scopes([issued.granted_scopes.as_slice()]).is_empty() && !profile.oauth.scopes.is_empty() {
+        issued.granted_scopes = profile.oauth.scopes.clone();
+        ConfiguredCredentialStore::for_server(&profile.credential_store, server_name, profile.account.as_deref())
+            .save(issued)
+            .await?;
+    }
 
-    let mut manager = AuthorizationManager::new(url).await?;
-    manager.set_credential_store(KeychainCredentialStore::new(server_name));
+    let manager = manager_from_store(profile, server_name, metadata).await?;
+    Ok(EnsureOutcome::Authorized(manager))
+}
+
+/// Fresh manager over the credentials a just-completed flow saved. The flow consumed the
+/// previous manager (it lives on inside the `AuthorizationSession`).
+async fn manager_from_store(
+    profile: &ResolvedMcpServer,
+    server_name: &str,
+    metadata: AuthorizationMetadata,
+) -> Result<AuthorizationManager, EnsureError> {
+    let mut manager = AuthorizationManager::new(profile.url.expose_secret()).await?;
+    manager.set_metadata(metadata);
+    manager.set_credential_store(ConfiguredCredentialStore::for_server(
+        &profile.credential_store,
+        server_name,
+        profile.account.as_deref(),
+    ));
     if !manager.initialize_from_store().await? {
-        return Err(EnsureError::MissingAfterFlow);
+        return Err(EnsureError::MissingAfterFlow(profile.credential_store.backend));
     }
-    Ok(EnsureOutcome::Authorized(manager))
+    configure_pre_registered_client(&mut manager, profile)?;
+    Ok(manager)
+}
+
+/// `initialize_from_store` configures a public client from the stored client id; a
+/// confidential pre-registered client also needs its secret for token refresh.
+fn configure_pre_registered_client(
+    manager: &mut AuthorizationManager,
+    profile: &ResolvedMcpServer,
+) -> Result<(), AuthError> {
+    let (Some(client_id), Some(secret)) = (&profile.oauth.client_id, &profile.oauth.client_secret) else {
+        return Ok(());
+    };
+    let redirect_uri = redirect_uri_for_port(profile.oauth.redirect_port.unwrap_or(0));
+    manager.configure_client(
+        OAuthClientConfig::new(client_id, redirect_uri)
+            .with_client_secret(secret.expose_secret())
+            .with_scopes(profile.oauth.scopes.clone()),
+    )
+}
+
+/// Refresh `server_name`'s access token now, persisting the response (including a
+/// rotated refresh token) to its credential store. `account` overrides the configured one.
+pub async fn refresh_credentials(server_name: &str, account: Option<&str>) -> Result<RefreshOutcome, EnsureError> {
+    let mut profile = config::load_mcp_server(server_name)?;
+    if let Some(account) = account {
+        profile.account = Some(account.to_string());
+    }
+    if profile.http_headers.contains_key(&AUTHORIZATION) {
+        return Err(EnsureError::NotOAuth(server_name.to_string()));
+    }
+
+    let mut manager = AuthorizationManager::new(profile.url.expose_secret()).await?;
+    let metadata = match manager.discover_metadata().await {
+        Ok(m) => m,
+        Err(AuthError::NoAuthorizationSupport) => return Err(EnsureError::NotOAuth(server_name.to_string())),
+        Err(e) => return Err(e.into()),
+    };
+    manager.set_metadata(metadata);
+
+    let store =
+        ConfiguredCredentialStore::for_server(&profile.credential_store, server_name, profile.account.as_deref());
+    let Some(previous) = store.load().await? else {
+        return Err(EnsureError::NotLoggedIn(server_name.to_string()));
+    };
+    manager.set_credential_store(ConfiguredCredentialStore::for_server(
+        &profile.credential_store,
+        server_name,
+        profile.account.as_deref(),
+    ));
+    if !manager.initialize_from_store().await? {
+        return Err(EnsureError::NotLoggedIn(server_name.to_string()));
+    }
+    configure_pre_registered_client(&mut manager, &profile)?;
+
+    manager.refresh_token().await?;
+    let current = store
+        .load()
+        .await?
+        .ok_or(EnsureError::MissingAfterFlow(profile.credential_store.backend))?;
+    Ok(RefreshOutcome { previous, current })
 }
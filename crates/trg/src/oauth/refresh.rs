@@ -0,0 +1,198 @@
+//! Proactive access-token refresh.
+//!
+//! rmcp's `AuthClient` only refreshes when a request is about to go out with a
+//! token inside its 30 s expiry buffer, or after the remote answers 401. A
+//! proxy that sat idle in a long Cursor session therefore spends its first
+//! tool call on a refresh round-trip (or, if the refresh token was rotated by
+//! another process in the meantime, on a confusing 401 + retry). The
+//! background task here refreshes shortly *before* `expires_in` elapses,
+//! measured from the `token_received_at` recorded in `StoredCredentials`.
+//!
+//! `AuthorizationManager::refresh_token` re-reads the credential store and
+//! saves the response, so rotated refresh tokens are persisted, and a refresh
+//! done by a sibling proxy for the same server is picked up: the task re-reads
+//! the store after every sleep and only refreshes when the token *it sees* is
+//! due.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use oauth2::TokenResponse;
+use rmcp::transport::auth::{AuthorizationManager, CredentialStore, StoredCredentials};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::store::ConfiguredCredentialStore;
+
+/// Lower and upper bound for how long before expiry the refresh fires.
+const MIN_LEAD: Duration = Duration::from_secs(60);
+const MAX_LEAD: Duration = Duration::from_secs(300);
+/// Shortest time between two refreshes. A token that lives shorter than this (or that the AS
+/// reissues with the same short lifetime) would otherwise be refreshed in a tight loop, since
+/// every refresh resets `token_received_at`.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// Consecutive failed refreshes before the task gives up and leaves it to rmcp's
+/// on-demand path (which surfaces `AuthorizationRequired` to the host).
+const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+pub(crate) fn now_epoch_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// How long to wait before refreshing `stored`, or `None` when it cannot be scheduled
+/// (no token, no refresh token, or the AS did not advertise `expires_in`).
+///
+/// The lead never exceeds half the token's lifetime, and the refresh is never scheduled sooner
+/// than [`MIN_REFRESH_INTERVAL`] after the token was received.
+pub fn refresh_delay(stored: &StoredCredentials, now: u64) -> Option<Duration> {
+    let token = stored.token_response.as_ref()?;
+    token.refresh_token()?;
+    let lifetime = token.expires_in()?;
+    let received_at = stored.token_received_at?;
+
+    let lead = (lifetime / 10).clamp(MIN_LEAD, MAX_LEAD).min(lifetime / 2);
+    let refresh_after = (lifetime - lead).max(MIN_REFRESH_INTERVAL);
+    let refresh_at = received_at.saturating_add(refresh_after.as_secs());
+    Some(Duration::from_secs(refresh_at.saturating_sub(now)))
+}
+
+fn retry_backoff(failures: u32) -> Duration {
+    Duration::from_secs(15u64.saturating_mul(1 << failures.min(4)))
+}
+
+/// Spawn the background refresher for one proxied server. Abort the handle when the bridge exits.
+///
+/// `store` must point at the same entry the manager was configured with; it is only
+/// read here, to schedule against the latest `token_received_at`.
+pub fn spawn_background_refresh(
+    manager: Arc<Mutex<AuthorizationManager>>,
+    store: ConfiguredCredentialStore,
+    server_name: String,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let server = server_name.as_str();
+        let mut failures = 0u32;
+        loop {
+            let stored = match store.load().await {
+                Ok(Some(stored)) => stored,
+                Ok(None) => {
+                    debug!(server, "refresh: no stored credentials; background refresh stopped");
+                    return;
+                }
+                Err(e) => {
+                    failures += 1;
+                    if failures >= MAX_CONSECUTIVE_FAILURES {
+                        warn!(server, error = %e, "refresh: credential store unreadable; giving up");
+                        return;
+                    }
+                    warn!(server, error = %e, attempt = failures, "refresh: credential store read failed");
+                    tokio::time::sleep(retry_backoff(failures)).await;
+                    continue;
+                }
+            };
+
+            let Some(delay) = refresh_delay(&stored, now_epoch_secs()) else {
+                debug!(
+                    server,
+                    "refresh: token has no expiry or refresh token; background refresh disabled"
+                );
+                return;
+            };
+
+            if !delay.is_zero() {
+                debug!(server, in_secs = delay.as_secs(), "refresh: scheduled");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+
+            let result = manager.lock().await.refresh_token().await;
+            match result {
+                Ok(_) => {
+                    failures = 0;
+                    info!(server, "refresh: access token refreshed ahead of expiry");
+                    // Guards against a store that didn't record the new `token_received_at`.
+                    tokio::time::sleep(MIN_REFRESH_INTERVAL).await;
+                }
+                Err(e) => {
+                    failures += 1;
+                    if failures >= MAX_CONSECUTIVE_FAILURES {
+                        warn!(server, error = %e, "refresh: giving up after repeated failures");
+                        return;
+                    }
+                    warn!(server, error = %e, attempt = failures, "refresh: failed; retrying");
+                    tokio::time::sleep(retry_backoff(failures)).await;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::transport::auth::OAuthTokenResponse;
+
+    fn stored(expires_in: Option<u64>, refresh: bool, received_at: Option<u64>) -> StoredCredentials {
+        let mut token = serde_json::json!({
+            "access_token": "a",
+            "token_type": "Bearer",
+        });
+        if let Some(secs) = expires_in {
+            token["expires_in"] = secs.into();
+        }
+        if refresh {
+            token["refresh_token"] = "r".into();
+        }
+        let token: OAuthTokenResponse = serde_json::from_value(token).unwrap();
+        StoredCredentials::new("c".to_string(), Some(token), vec![], received_at)
+    }
+
+    #[test]
+    fn one_hour_token_refreshes_five_minutes_early() {
+        let creds = stored(Some(3600), true, Some(1_000));
+        assert_eq!(refresh_delay(&creds, 1_000), Some(Duration::from_secs(3300)));
+        assert_eq!(refresh_delay(&creds, 2_000), Some(Duration::from_secs(2300)));
+    }
+
+    #[test]
+    fn short_token_uses_minimum_lead() {
+        let creds = stored(Some(300), true, Some(0));
+        assert_eq!(refresh_delay(&creds, 0), Some(Duration::from_secs(240)));
+    }
+
+    #[test]
+    fn overdue_token_is_due_immediately() {
+        let creds = stored(Some(3600), true, Some(0));
+        assert_eq!(refresh_delay(&creds, 10_000), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn lifetime_shorter_than_lead_still_waits() {
+        let creds = stored(Some(90), true, Some(100));
+        assert_eq!(refresh_delay(&creds, 100), Some(Duration::from_secs(45)));
+        let creds = stored(Some(30), true, Some(100));
+        assert_eq!(refresh_delay(&creds, 100), Some(MIN_REFRESH_INTERVAL));
+        let creds = stored(Some(0), true, Some(100));
+        assert_eq!(refresh_delay(&creds, 110), Some(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn unschedulable_without_refresh_token_expiry_or_timestamp() {
+        assert_eq!(refresh_delay(&stored(Some(3600), false, Some(0)), 0), None);
+        assert_eq!(refresh_delay(&stored(None, true, Some(0)), 0), None);
+        assert_eq!(refresh_delay(&stored(Some(3600), true, None), 0), None);
+        let no_token = StoredCredentials::new("c".to_string(), None, vec![], Some(0));
+        assert_eq!(refresh_delay(&no_token, 0), None);
+    }
+
+    #[test]
+    fn retry_backoff_is_capped() {
+        assert_eq!(retry_backoff(1), Duration::from_secs(30));
+        assert_eq!(retry_backoff(4), Duration::from_secs(240));
+        assert_eq!(retry_backoff(9), Duration::from_secs(240));
+    }
+}
@@ -0,0 +1,222 @@
+//! Token revocation (RFC 7009) for `trg mcp auth logout`.
+//!
+//! Deleting the local entry leaves both tokens valid at the authorization
+//! server until they expire — for refresh tokens that can be months. When the
+//! AS metadata advertises `revocation_endpoint`, logout revokes the refresh
+//! token first (most servers then drop the access tokens derived from it) and
+//! the access token second. Revocation is best effort: failures are reported
+//! but never keep the local entry around.
+
+use oauth2::TokenResponse;
+use rmcp::transport::auth::{AuthorizationManager, AuthorizationMetadata, StoredCredentials};
+use secrecy::ExposeSecret;
+
+use crate::config::{self, OAuthSettings};
+
+/// What logout managed to revoke.
+#[derive(Debug)]
+pub enum RevocationOutcome {
+    /// One entry per token sent to `endpoint`; `Err` carries the failure.
+    Attempted {
+        endpoint: String,
+        results: Vec<(TokenKind, Result<(), String>)>,
+    },
+    /// The AS metadata has no `revocation_endpoint`.
+    NotAdvertised,
+    /// Revocation could not be attempted (server gone from config, discovery failed, ...).
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Refresh,
+    Access,
+}
+
+impl TokenKind {
+    /// RFC 7009 `token_type_hint`.
+    pub fn hint(self) -> &'static str {
+        match self {
+            TokenKind::Refresh => "refresh_token",
+            TokenKind::Access => "access_token",
+        }
+    }
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TokenKind::Refresh => "refresh token",
+            TokenKind::Access => "access token",
+        })
+    }
+}
+
+pub fn revocation_endpoint(metadata: &AuthorizationMetadata) -> Option<&str> {
+    metadata
+        .additional_fields
+        .get("revocation_endpoint")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// Revoke `stored`'s tokens at `server_name`'s authorization server.
+pub async fn revoke_stored_tokens(server_name: &str, stored: &StoredCredentials) -> RevocationOutcome {
+    let Some(token) = stored.token_response.as_ref() else {
+        return RevocationOutcome::Skipped("no token stored".to_string());
+    };
+    let profile = match config::load_mcp_server(server_name) {
+        Ok(p) => p,
+        Err(e) => return RevocationOutcome::Skipped(format!("cannot resolve server: {e}")),
+    };
+    let metadata = match discover(profile.url.expose_secret()).await {
+        Ok(m) => m,
+        Err(e) => return RevocationOutcome::Skipped(format!("metadata discovery failed: {e}")),
+    };
+    let Some(endpoint) = revocation_endpoint(&metadata) else {
+        return RevocationOutcome::NotAdvertised;
+    };
+
+    let client = reqwest::Client::new();
+    let auth = ClientAuth::for_stored(&profile.oauth, &stored.client_id);
+    let mut tokens = Vec::new();
+    if let Some(refresh) = token.refresh_token() {
+        tokens.push((TokenKind::Refresh, refresh.secret().as_str()));
+    }
+    tokens.push((TokenKind::Access, token.access_token().secret().as_str()));
+
+    let mut results = Vec::new();
+    for (kind, value) in tokens {
+        results.push((kind, revoke_token(&client, endpoint, value, kind, &auth).await));
+    }
+    RevocationOutcome::Attempted {
+        endpoint: endpoint.to_string(),
+        results,
+    }
+}
+
+async fn discover(url: &str) -> Result<AuthorizationMetadata, rmcp::transport::auth::AuthError> {
+    AuthorizationManager::new(url).await?.discover_metadata().await
+}
+
+/// How the revocation request authenticates the client (RFC 7009 §2.1 reuses the token
+/// endpoint's client authentication).
+pub struct ClientAuth<'a> {
+    client_id: &'a str,
+    secret: Option<&'a str>,
+}
+
+impl<'a> ClientAuth<'a> {
+    /// The configured secret only applies when the tokens were issued to the configured client.
+    pub fn for_stored(oauth: &'a OAuthSettings, stored_client_id: &'a str) -> Self {
+        let secret = match (&oauth.client_id, &oauth.client_secret) {
+            (Some(id), Some(secret)) if id == stored_client_id => Some(secret.expose_secret()),
+            _ => None,
+        };
+        Self {
+            client_id: stored_client_id,
+            secret,
+        }
+    }
+}
+
+/// POST one token to `endpoint`. Per RFC 7009 §2.2 the AS answers 200 for unknown or
+/// already-invalid tokens too, so any 200 is success.
+pub async fn revoke_token(
+    client: &reqwest::Client,
+    endpoint: &str,
+    token: &str,
+    kind: TokenKind,
+    auth: &ClientAuth<'_>,
+) -> Result<(), String> {
+    let mut form = vec![("token", token), ("token_type_hint", kind.hint())];
+    let request = match auth.secret {
+        Some(secret) => client.post(endpoint).basic_auth(auth.client_id, Some(secret)),
+        None => {
+            form.push(("client_id", auth.client_id));
+            client.post(endpoint)
+        }
+    };
+    let response = request.form(&form).send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(format!("HTTP {status}: {}", body.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revocation_endpoint_comes_from_additional_metadata() {
+        let mut metadata = AuthorizationMetadata::default();
+        assert_eq!(revocation_endpoint(&metadata), None);
+        metadata
+            .additional_fields
+            .insert("revocation_endpoint".into(), "https://as.example/revoke".into());
+        assert_eq!(revocation_endpoint(&metadata), Some("https://as.example/revoke"));
+    }
+
+    #[test]
+    fn configured_secret_only_used_for_matching_client() {
+        let oauth = OAuthSettings {
+            client_id: Some("configured".into()),
+            client_secret: Some("s3cret".to_string().into()),
+            ..OAuthSettings::default()
+        };
+        assert_eq!(ClientAuth::for_stored(&oauth, "configured").secret, Some("s3cret"));
+        assert_eq!(ClientAuth::for_stored(&oauth, "dynamic").secret, None);
+    }
+
+    fn serve_once(status: u16) -> (String, std::thread::JoinHandle<(String, Option<String>)>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let authorization = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.to_string());
+            request.respond(tiny_http::Response::empty(status)).unwrap();
+            (body, authorization)
+        });
+        (format!("http://127.0.0.1:{port}/revoke"), handle)
+    }
+
+    #[tokio::test]
+    async fn public_client_sends_client_id_in_form() {
+        let (endpoint, server) = serve_once(200);
+        let auth = ClientAuth {
+            client_id: "cid",
+            secret: None,
+        };
+        revoke_token(&reqwest::Client::new(), &endpoint, "rt-1", TokenKind::Refresh, &auth)
+            .await
+            .unwrap();
+        let (body, authorization) = server.join().unwrap();
+        assert_eq!(body, "token=rt-1&token_type_hint=refresh_token&client_id=cid");
+        assert_eq!(authorization, None);
+    }
+
+    #[tokio::test]
+    async fn confidential_client_uses_basic_auth_and_reports_failures() {
+        let (endpoint, server) = serve_once(503);
+        let auth = ClientAuth {
+            client_id: "cid",
+            secret: Some("pw"),
+        };
+        let err = revoke_token(&reqwest::Client::new(), &endpoint, "at-1", TokenKind::Access, &auth)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("HTTP 503"), "{err}");
+        let (body, authorization) = server.join().unwrap();
+        assert_eq!(body, "token=at-1&token_type_hint=access_token");
+        assert_eq!(authorization.as_deref(), Some("Basic Y2lkOnB3"));
+    }
+}
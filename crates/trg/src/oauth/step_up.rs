@@ -0,0 +1,263 @@
+//! Step-up authorization for `403 insufficient_scope` challenges (RFC 6750 §3.1).
+//!
+//! A resource server that needs more than the token carries answers
+//! `WWW-Authenticate: Bearer error="insufficient_scope", scope="..."`. The
+//! scopes it names are merged with what is already granted (and what config
+//! asks for), so the new grant is a superset and an earlier step-up is not
+//! lost. Re-authorizing needs a browser, so headless callers get
+//! [`StepUpError::Headless`] with the exact `trg mcp auth login` command.
+
+use std::collections::HashMap;
+
+use http::{HeaderName, HeaderValue};
+use rmcp::{
+    model::ClientJsonRpcMessage,
+    transport::{
+        auth::{AuthClient, AuthError, AuthorizationManager, CredentialStore},
+        streamable_http_client::{InsufficientScopeError, StreamableHttpClient, StreamableHttpError},
+    },
+};
+use secrecy::ExposeSecret;
+use serde_json::json;
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::{OAuthSettings, ResolvedMcpServer},
+    oauth::{
+        flow::{is_interactive, run_authorization, FlowConfig},
+        manager_from_store,
+        store::ConfiguredCredentialStore,
+        EnsureError,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StepUpError {
+    #[error(
+        "`{server}` requires additional OAuth scope(s) `{}`; re-authorization needs a browser. \
+         Run `{}` in a terminal, then restart the MCP host",
+        required.join(" "),
+        login_command(server, account.as_deref(), required)
+    )]
+    Headless {
+        server: String,
+        account: Option<String>,
+        required: Vec<String>,
+    },
+
+    #[error(
+        "`{server}` rejected the token with insufficient_scope but did not name the required scope \
+         (WWW-Authenticate: {header}); add it to `[mcp.servers.{server}.oauth] scopes`"
+    )]
+    UnknownScope { server: String, header: String },
+
+    #[error("{0}")]
+    Ensure(#[from] EnsureError),
+}
+
+fn login_command(server: &str, account: Option<&str>, required: &[String]) -> String {
+    let mut cmd = format!("trg mcp auth login --server {server}");
+    if let Some(account) = account {
+        cmd.push_str(" --account ");
+        cmd.push_str(account);
+    }
+    for scope in required {
+        cmd.push_str(" --scope ");
+        cmd.push_str(scope);
+    }
+    cmd
+}
+
+/// Scopes named by the challenge's `scope` parameter (space-delimited per RFC 6750).
+pub fn required_scopes(challenge: &InsufficientScopeError) -> Vec<String> {
+    challenge
+        .get_required_scope()
+        .map(|s| s.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Order-preserving union; blank entries (rmcp stores `[""]` for an empty `scope`) are dropped.
+pub fn merge_scopes<'a>(sets: impl IntoIterator<Item = &'a [String]>) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::new();
+    for scope in sets.into_iter().flatten() {
+        if !scope.trim().is_empty() && !merged.contains(scope) {
+            merged.push(scope.clone());
+        }
+    }
+    merged
+}
+
+/// `required` scopes not already in `granted`.
+pub fn missing_scopes(granted: &[String], required: &[String]) -> Vec<String> {
+    required.iter().filter(|s| !granted.contains(s)).cloned().collect()
+}
+
+/// Re-run authorization for `server_name` requesting configured ∪ granted ∪ `required` scopes
+/// and return a manager over the new credentials.
+///
+/// When the AS omits `scope` from the token response, the requested set is recorded as the
+/// grant so the next step-up still asks for everything.
+pub async fn step_up(
+    profile: &ResolvedMcpServer,
+    server_name: &str,
+    required: &[String],
+    flow: FlowConfig,
+) -> Result<AuthorizationManager, StepUpError> {
+    let store =
+        ConfiguredCredentialStore::for_server(&profile.credential_store, server_name, profile.account.as_deref());
+    let granted = store
+        .load()
+        .await
+        .map_err(EnsureError::from)?
+        .map(|stored| stored.granted_scopes)
+        .unwrap_or_default();
+    let requested = merge_scopes([profile.oauth.scopes.as_slice(), granted.as_slice(), required]);
+
+    if !is_interactive() {
+        return Err(StepUpError::Headless {
+            server: server_name.to_string(),
+            account: profile.account.clone(),
+            required: missing_scopes(&granted, &requested),
+        });
+    }
+    info!(server = server_name, scopes = %requested.join(" "), "step-up: re-authorizing");
+
+    let mut manager = AuthorizationManager::new(profile.url.expose_secret())
+        .await
+        .map_err(EnsureError::from)?;
+    let metadata = manager.discover_metadata().await.map_err(EnsureError::from)?;
+    manager.set_metadata(metadata.clone());
+    manager.set_credential_store(ConfiguredCredentialStore::for_server(
+        &profile.credential_store,
+        server_name,
+        profile.account.as_deref(),
+    ));
+
+    let oauth = OAuthSettings {
+        scopes: requested.clone(),
+        ..profile.oauth.clone()
+    };
+    let mut issued = run_authorization(manager, &oauth, flow)
+        .await
+        .map_err(EnsureError::from)?;
+    if merge_scopes([issued.granted_scopes.as_slice()]).is_empty() {
+        issued.granted_scopes = requested;
+        store.save(issued).await.map_err(EnsureError::from)?;
+    }
+
+    Ok(manager_from_store(profile, server_name, metadata).await?)
+}
+
+/// Send an `initialize` with the stored token and report an `insufficient_scope` challenge,
+/// so `trg mcp auth login` can step up before the host ever sees a 403.
+///
+/// Any other failure is logged and treated as "no challenge": login itself succeeded.
+pub async fn probe_insufficient_scope(
+    profile: &ResolvedMcpServer,
+    manager: AuthorizationManager,
+) -> Option<InsufficientScopeError> {
+    let client = AuthClient::new(reqwest::Client::new(), manager);
+    let initialize: ClientJsonRpcMessage = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": { "name": "trg", "version": env!("CARGO_PKG_VERSION") },
+        },
+    }))
+    .expect("static initialize request deserializes");
+
+    let headers: HashMap<HeaderName, HeaderValue> = profile
+        .http_headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.clone(), HeaderValue::try_from(value.expose_secret()).ok()?)))
+        .collect();
+    let uri: std::sync::Arc<str> = profile.url.expose_secret().into();
+
+    match client
+        .post_message(uri.clone(), initialize, None, None, headers.clone())
+        .await
+    {
+        Ok(response) => {
+            if let Ok((_, Some(session_id))) = response.expect_initialized::<reqwest::Error>().await {
+                let _ = client.delete_session(uri, session_id.into(), None, headers).await;
+            }
+            None
+        }
+        Err(StreamableHttpError::InsufficientScope(challenge)) => Some(challenge),
+        Err(StreamableHttpError::Auth(AuthError::AuthorizationRequired)) => {
+            debug!("step-up probe: token rejected (401); leaving it to the proxy's refresh path");
+            None
+        }
+        Err(e) => {
+            warn!(error = %e, "step-up probe: initialize failed");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn required_scopes_splits_challenge_scope() {
+        let challenge = InsufficientScopeError::new(
+            r#"Bearer error="insufficient_scope", scope="files:read  files:write""#.to_string(),
+            Some("files:read  files:write".to_string()),
+        );
+        assert_eq!(required_scopes(&challenge), strings(&["files:read", "files:write"]));
+
+        let bare = InsufficientScopeError::new(r#"Bearer error="insufficient_scope""#.to_string(), None);
+        assert!(required_scopes(&bare).is_empty());
+    }
+
+    #[test]
+    fn merge_scopes_keeps_order_and_drops_duplicates_and_blanks() {
+        let configured = strings(&["read"]);
+        let granted = strings(&["", "read", "offline_access"]);
+        let required = strings(&["write", "read"]);
+        assert_eq!(
+            merge_scopes([configured.as_slice(), granted.as_slice(), required.as_slice()]),
+            strings(&["read", "offline_access", "write"])
+        );
+    }
+
+    #[test]
+    fn missing_scopes_lists_only_new_ones() {
+        assert_eq!(
+            missing_scopes(&strings(&["read"]), &strings(&["read", "write"])),
+            strings(&["write"])
+        );
+        assert!(missing_scopes(&strings(&["read", "write"]), &strings(&["write"])).is_empty());
+    }
+
+    #[test]
+    fn headless_error_names_login_command() {
+        let err = StepUpError::Headless {
+            server: "linear".to_string(),
+            account: None,
+            required: strings(&["write", "admin"]),
+        };
+        assert_eq!(
+            err.to_string(),
+            "`linear` requires additional OAuth scope(s) `write admin`; re-authorization needs a browser. \
+             Run `trg mcp auth login --server linear --scope write --scope admin` in a terminal, then restart the MCP host"
+        );
+
+        let err = StepUpError::Headless {
+            server: "linear".to_string(),
+            account: Some("svc".to_string()),
+            required: strings(&["write"]),
+        };
+        assert!(err
+            .to_string()
+            .contains("`trg mcp auth login --server linear --account svc --scope write`"));
+    }
+}
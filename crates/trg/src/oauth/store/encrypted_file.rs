@@ -0,0 +1,597 @@
+//! Passphrase-encrypted file backend (`credential_store = "encrypted-file"`).
+//!
+//! For hosts with no keyring daemon — containers, headless CI boxes, SSH-only
+//! workstations. Opt-in only: nothing falls back to this backend silently.
+//!
+//! Each server gets one file under `[mcp.credential_file].dir` (default
+//! `$XDG_DATA_HOME/trg/mcp-credentials/`). The file is a small JSON envelope:
+//! the key is derived from the passphrase with Argon2id (per-file random salt,
+//! cost parameters recorded in the envelope), and the `StoredCredentials`
+//! payload is sealed with XChaCha20-Poly1305 using the server name as
+//! associated data, so renaming one server's file onto another fails to
+//! decrypt instead of silently swapping identities.
+//!
+//! Files are written `0600` inside a `0700` directory via write-to-temp +
+//! `rename(2)`, so a crash mid-save never leaves a truncated entry behind.
+//! Loading refuses files readable by group or others.
+//!
+//! The passphrase comes from an env var (default `TRG_CREDENTIALS_PASSPHRASE`)
+//! or the stdout of `passphrase_command` (e.g. `["pass", "show", "trg"]`),
+//! resolved on every load/save so it never has to sit in the proxy's memory
+//! between token refreshes.
+
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use async_trait::async_trait;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rmcp::transport::auth::{AuthError, CredentialStore, StoredCredentials};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use super::err;
+use crate::config::{CredentialFileSettings, PassphraseSource};
+use crate::encoding::{hex_decode, hex_encode};
+
+const ENVELOPE_VERSION: u32 = 1;
+const KDF_ARGON2ID: &str = "argon2id";
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+/// Upper bounds on the cost read back from an envelope. A corrupted or edited file must
+/// not be able to make every `trg` start attempt a huge Argon2 allocation.
+const MAX_M_COST_KIB: u32 = 1024 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct KdfCost {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl KdfCost {
+    fn is_within_limits(&self) -> bool {
+        self.m_cost <= MAX_M_COST_KIB && self.t_cost <= MAX_T_COST && self.p_cost <= MAX_P_COST
+    }
+}
+
+impl Default for KdfCost {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Kdf {
+    algorithm: String,
+    #[serde(flatten)]
+    cost: KdfCost,
+    salt: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    account: String,
+    kdf: Kdf,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub struct EncryptedFileCredentialStore {
+    server_name: String,
+    dir: PathBuf,
+    passphrase: PassphraseSource,
+    kdf_cost: KdfCost,
+}
+
+impl EncryptedFileCredentialStore {
+    pub fn new(server_name: impl Into<String>, settings: &CredentialFileSettings) -> Self {
+        Self {
+            server_name: server_name.into(),
+            dir: settings.dir.clone(),
+            passphrase: settings.passphrase.clone(),
+            kdf_cost: KdfCost::default(),
+        }
+    }
+
+    /// File holding this server's entry.
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(file_name_for(&self.server_name))
+    }
+
+    async fn resolve_passphrase(&self) -> Result<SecretString, AuthError> {
+        let passphrase = match &self.passphrase {
+            PassphraseSource::Env(name) => std::env::var(name).map_err(|_| {
+                err(format!(
+                    "encrypted credential store needs a passphrase: set `{name}` or configure \
+                     `passphrase_command` under `[mcp.credential_file]`"
+                ))
+            })?,
+            PassphraseSource::Command(argv) => {
+                let out = Command::new(&argv[0])
+                    .args(&argv[1..])
+                    .stdin(Stdio::null())
+                    .output()
+                    .await
+                    .map_err(|e| err(format!("passphrase_command `{}`: {e}", argv[0])))?;
+                if !out.status.success() {
+                    let stderr = String::from_utf8_lossy(&out.stderr);
+                    return Err(err(format!(
+                        "passphrase_command `{}` failed ({}): {}",
+                        argv[0],
+                        out.status,
+                        stderr.trim()
+                    )));
+                }
+                let stdout = String::from_utf8(out.stdout)
+                    .map_err(|_| err(format!("passphrase_command `{}` printed non-UTF-8 output", argv[0])))?;
+                stdout.trim_end_matches(['\n', '\r']).to_string()
+            }
+        };
+        if passphrase.is_empty() {
+            return Err(err("encrypted credential store passphrase is empty"));
+        }
+        Ok(SecretString::new(passphrase.into_boxed_str()))
+    }
+}
+
+/// Server names are arbitrary TOML keys; keep `[A-Za-z0-9_-]` (and non-leading `.`)
+/// and percent-escape every other byte so a name can never traverse out of `dir`.
+fn file_name_for(server_name: &str) -> String {
+    let mut out = String::with_capacity(server_name.len() + 5);
+    for (i, b) in server_name.bytes().enumerate() {
+        let keep = b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || (b == b'.' && i > 0);
+        if keep {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out.push_str(".json");
+    out
+}
+
+fn derive_key(passphrase: &SecretString, salt: &[u8], cost: KdfCost) -> Result<[u8; KEY_LEN], AuthError> {
+    let params = Params::new(cost.m_cost, cost.t_cost, cost.p_cost, Some(KEY_LEN))
+        .map_err(|e| err(format!("invalid Argon2 parameters: {e}")))?;
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.expose_secret().as_bytes(), salt, &mut key)
+        .map_err(|e| err(format!("derive credential file key: {e}")))?;
+    Ok(key)
+}
+
+fn seal(server_name: &str, plaintext: &[u8], passphrase: &SecretString, cost: KdfCost) -> Result<Envelope, AuthError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt, cost)?;
+    let cipher = XChaCha20Poly1305::new_from_slice(&key).map_err(|e| err(e.to_string()))?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: server_name.as_bytes(),
+            },
+        )
+        .map_err(|_| err("encrypt credential file payload"))?;
+
+    Ok(Envelope {
+        version: ENVELOPE_VERSION,
+        account: server_name.to_string(),
+        kdf: Kdf {
+            algorithm: KDF_ARGON2ID.to_string(),
+            cost,
+            salt: hex_encode(salt),
+        },
+        nonce: hex_encode(nonce),
+        ciphertext: hex_encode(ciphertext),
+    })
+}
+
+fn open(server_name: &str, envelope: &Envelope, passphrase: &SecretString, path: &Path) -> Result<Vec<u8>, AuthError> {
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(err(format!(
+            "{}: unsupported credential file version {}",
+            path.display(),
+            envelope.version
+        )));
+    }
+    if envelope.kdf.algorithm != KDF_ARGON2ID {
+        return Err(err(format!(
+            "{}: unsupported key derivation `{}`",
+            path.display(),
+            envelope.kdf.algorithm
+        )));
+    }
+    if envelope.account != server_name {
+        return Err(err(format!(
+            "{}: entry belongs to `{}`, not `{server_name}`",
+            path.display(),
+            envelope.account
+        )));
+    }
+    let malformed = || err(format!("{}: malformed credential file", path.display()));
+    let salt = hex_decode(&envelope.kdf.salt).ok_or_else(malformed)?;
+    let nonce = hex_decode(&envelope.nonce).ok_or_else(malformed)?;
+    let ciphertext = hex_decode(&envelope.ciphertext).ok_or_else(malformed)?;
+    if nonce.len() != 24 {
+        return Err(malformed());
+    }
+
+    let cost = envelope.kdf.cost;
+    if !cost.is_within_limits() {
+        return Err(err(format!(
+            "{}: key derivation cost (m_cost={}, t_cost={}, p_cost={}) exceeds the supported limits \
+             (m_cost<={MAX_M_COST_KIB}, t_cost<={MAX_T_COST}, p_cost<={MAX_P_COST})",
+            path.display(),
+            cost.m_cost,
+            cost.t_cost,
+            cost.p_cost
+        )));
+    }
+
+    let key = derive_key(passphrase, &salt, cost)?;
+    let cipher = XChaCha20Poly1305::new_from_slice(&key).map_err(|e| err(e.to_string()))?;
+    cipher
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: server_name.as_bytes(),
+            },
+        )
+        .map_err(|_| {
+            err(format!(
+                "{}: cannot decrypt credential file (wrong passphrase or tampered file)",
+                path.display()
+            ))
+        })
+}
+
+fn read_entry(path: &Path) -> Result<Option<Envelope>, AuthError> {
+    let metadata = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(err(format!("{}: {e}", path.display()))),
+    };
+    let mode = metadata.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Err(err(format!(
+            "{}: permissions {mode:o} are too open; run `chmod 600` on it",
+            path.display()
+        )));
+    }
+    let text = fs::read_to_string(path).map_err(|e| err(format!("{}: {e}", path.display())))?;
+    let envelope = serde_json::from_str(&text).map_err(|e| err(format!("{}: {e}", path.display())))?;
+    Ok(Some(envelope))
+}
+
+fn write_entry_atomically(dir: &Path, path: &Path, contents: &[u8]) -> Result<(), AuthError> {
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .map_err(|e| err(format!("create {}: {e}", dir.display())))?;
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("entry");
+    let tmp = dir.join(format!(".{file_name}.{}.tmp", std::process::id()));
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(err(format!("write {}: {e}", path.display())));
+    }
+    Ok(())
+}
+
+/// Accounts (server names) with an entry under `settings.dir`, read from each envelope's
+/// plaintext `account`, so no passphrase is needed. A missing directory lists nothing.
+pub async fn list_accounts(settings: &CredentialFileSettings) -> Result<Vec<String>, AuthError> {
+    let dir = settings.dir.clone();
+    tokio::task::spawn_blocking(move || {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(err(format!("{}: {e}", dir.display()))),
+        };
+        let mut accounts = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_entry = path.extension().is_some_and(|ext| ext == "json")
+                && !entry.file_name().to_string_lossy().starts_with('.');
+            if !is_entry {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(envelope) = serde_json::from_str::<Envelope>(&text) {
+                accounts.push(envelope.account);
+            }
+        }
+        accounts.sort();
+        Ok(accounts)
+    })
+    .await
+    .map_err(join_err)?
+}
+
+fn join_err(e: tokio::task::JoinError) -> AuthError {
+    err(format!("credential file task failed to run: {e}"))
+}
+
+#[async_trait]
+impl CredentialStore for EncryptedFileCredentialStore {
+    async fn load(&self) -> Result<Option<StoredCredentials>, AuthError> {
+        let path = self.path();
+        let Some(envelope) = ({
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || read_entry(&path))
+                .await
+                .map_err(join_err)??
+        }) else {
+            return Ok(None);
+        };
+
+        let passphrase = self.resolve_passphrase().await?;
+        let server_name = self.server_name.clone();
+        let plaintext = tokio::task::spawn_blocking(move || open(&server_name, &envelope, &passphrase, &path))
+            .await
+            .map_err(join_err)??;
+
+        let credentials: StoredCredentials =
+            serde_json::from_slice(&plaintext).map_err(|e| err(format!("decode credential file payload: {e}")))?;
+        Ok(Some(credentials))
+    }
+
+    async fn save(&self, credentials: StoredCredentials) -> Result<(), AuthError> {
+        let json = serde_json::to_vec(&credentials).map_err(|e| err(e.to_string()))?;
+        let passphrase = self.resolve_passphrase().await?;
+        let server_name = self.server_name.clone();
+        let dir = self.dir.clone();
+        let path = self.path();
+        let cost = self.kdf_cost;
+
+        tokio::task::spawn_blocking(move || {
+            let envelope = seal(&server_name, &json, &passphrase, cost)?;
+            let contents = serde_json::to_vec_pretty(&envelope).map_err(|e| err(e.to_string()))?;
+            write_entry_atomically(&dir, &path, &contents)
+        })
+        .await
+        .map_err(join_err)?
+    }
+
+    async fn clear(&self) -> Result<(), AuthError> {
+        let path = self.path();
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(err(format!("remove {}: {e}", path.display()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tempfile::tempdir;
+
+    static ENV_SEQ: AtomicU64 = AtomicU64::new(0);
+
+    fn passphrase_env(value: &str) -> PassphraseSource {
+        let name = format!(
+            "TRG_TEST_CRED_PASSPHRASE_{}_{}",
+            std::process::id(),
+            ENV_SEQ.fetch_add(1, Ordering::Relaxed)
+        );
+        std::env::set_var(&name, value);
+        PassphraseSource::Env(name)
+    }
+
+    /// Argon2 at production cost takes seconds in debug builds; the envelope records
+    /// the cost, so cheap parameters exercise exactly the same code path.
+    fn store(dir: &Path, server: &str, passphrase: PassphraseSource) -> EncryptedFileCredentialStore {
+        let mut store = EncryptedFileCredentialStore::new(
+            server,
+            &CredentialFileSettings {
+                dir: dir.to_path_buf(),
+                passphrase,
+            },
+        );
+        store.kdf_cost = KdfCost {
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        store
+    }
+
+    fn credentials(client_id: &str) -> StoredCredentials {
+        StoredCredentials::new(client_id.to_string(), None, vec!["read".to_string()], Some(7))
+    }
+
+    #[tokio::test]
+    async fn load_missing_returns_none() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path(), "linear", passphrase_env("pw"));
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn save_load_roundtrip_with_private_permissions() {
+        let dir = tempdir().unwrap();
+        let creds_dir = dir.path().join("creds");
+        let store = store(&creds_dir, "linear", passphrase_env("pw"));
+
+        store.save(credentials("client-abc")).await.unwrap();
+        let loaded = store.load().await.unwrap().expect("some");
+        assert_eq!(loaded.client_id, "client-abc");
+        assert_eq!(loaded.granted_scopes, vec!["read".to_string()]);
+
+        let file_mode = fs::metadata(store.path()).unwrap().permissions().mode() & 0o777;
+        assert_eq!(file_mode, 0o600);
+        let dir_mode = fs::metadata(&creds_dir).unwrap().permissions().mode() & 0o777;
+        assert_eq!(dir_mode, 0o700);
+
+        let on_disk = fs::read_to_string(store.path()).unwrap();
+        assert!(!on_disk.contains("client-abc"), "{on_disk}");
+    }
+
+    #[tokio::test]
+    async fn save_overwrites_without_leaving_temp_files() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path(), "linear", passphrase_env("pw"));
+        store.save(credentials("first")).await.unwrap();
+        store.save(credentials("second")).await.unwrap();
+
+        assert_eq!(store.load().await.unwrap().unwrap().client_id, "second");
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(names, vec!["linear.json".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn wrong_passphrase_fails_to_decrypt() {
+        let dir = tempdir().unwrap();
+        store(dir.path(), "linear", passphrase_env("right"))
+            .save(credentials("c"))
+            .await
+            .unwrap();
+        let e = store(dir.path(), "linear", passphrase_env("wrong"))
+            .load()
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("wrong passphrase"), "{e}");
+    }
+
+    #[tokio::test]
+    async fn entry_copied_to_another_server_is_rejected() {
+        let dir = tempdir().unwrap();
+        let pw = passphrase_env("pw");
+        let linear = store(dir.path(), "linear", pw.clone());
+        linear.save(credentials("c")).await.unwrap();
+
+        let notion = store(dir.path(), "notion", pw);
+        fs::copy(linear.path(), notion.path()).unwrap();
+        let e = notion.load().await.unwrap_err();
+        assert!(e.to_string().contains("belongs to `linear`"), "{e}");
+    }
+
+    #[tokio::test]
+    async fn oversized_kdf_cost_is_rejected_before_deriving() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path(), "linear", passphrase_env("pw"));
+        store.save(credentials("c")).await.unwrap();
+        let mut envelope: serde_json::Value = serde_json::from_str(&fs::read_to_string(store.path()).unwrap()).unwrap();
+        envelope["kdf"]["m_cost"] = serde_json::json!(u32::MAX - 1);
+        fs::write(store.path(), serde_json::to_vec(&envelope).unwrap()).unwrap();
+
+        let e = store.load().await.unwrap_err();
+        assert!(e.to_string().contains("exceeds the supported limits"), "{e}");
+    }
+
+    #[tokio::test]
+    async fn load_refuses_group_readable_file() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path(), "linear", passphrase_env("pw"));
+        store.save(credentials("c")).await.unwrap();
+        fs::set_permissions(store.path(), fs::Permissions::from_mode(0o640)).unwrap();
+
+        let e = store.load().await.unwrap_err();
+        assert!(e.to_string().contains("too open"), "{e}");
+    }
+
+    #[tokio::test]
+    async fn missing_passphrase_env_names_the_variable() {
+        let dir = tempdir().unwrap();
+        let name = format!("TRG_TEST_CRED_UNSET_{}", std::process::id());
+        std::env::remove_var(&name);
+        let e = store(dir.path(), "linear", PassphraseSource::Env(name.clone()))
+            .save(credentials("c"))
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains(&name), "{e}");
+    }
+
+    #[tokio::test]
+    async fn passphrase_command_stdout_is_used() {
+        let dir = tempdir().unwrap();
+        let cmd = PassphraseSource::Command(vec!["sh".into(), "-c".into(), "echo from-helper".into()]);
+        store(dir.path(), "linear", cmd).save(credentials("c")).await.unwrap();
+
+        let loaded = store(dir.path(), "linear", passphrase_env("from-helper"))
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(loaded.unwrap().client_id, "c");
+    }
+
+    #[tokio::test]
+    async fn clear_is_idempotent() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path(), "linear", passphrase_env("pw"));
+        store.save(credentials("c")).await.unwrap();
+        store.clear().await.unwrap();
+        store.clear().await.unwrap();
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn list_accounts_reads_envelope_accounts() {
+        let dir = tempdir().unwrap();
+        let passphrase = passphrase_env("list-secret");
+        for server in ["zeta", "a/b"] {
+            store(dir.path(), server, passphrase.clone())
+                .save(credentials("c"))
+                .await
+                .unwrap();
+        }
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        let settings = CredentialFileSettings {
+            dir: dir.path().to_path_buf(),
+            passphrase: passphrase.clone(),
+        };
+        assert_eq!(list_accounts(&settings).await.unwrap(), ["a/b", "zeta"]);
+
+        let missing = CredentialFileSettings {
+            dir: dir.path().join("missing"),
+            passphrase,
+        };
+        assert!(list_accounts(&missing).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn file_names_cannot_escape_the_directory() {
+        assert_eq!(file_name_for("linear"), "linear.json");
+        assert_eq!(file_name_for("my.server_1"), "my.server_1.json");
+        assert_eq!(file_name_for("../etc/passwd"), "%2E.%2Fetc%2Fpasswd.json");
+        assert_eq!(file_name_for("a b"), "a%20b.json");
+    }
+}
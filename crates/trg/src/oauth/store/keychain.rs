@@ -1,8 +1,8 @@
-//! Persistent credential storage backing rmcp's `CredentialStore`.
+//! macOS Keychain backend (`credential_store = "keychain"`, the macOS default).
 //!
-//! macOS Keychain only for this milestone (see `crates/trg/PLAN.md`). No file
-//! fallback by design: if Keychain is unavailable we surface the error rather
-//! than spill OAuth tokens onto disk under `$HOME`.
+//! No silent file fallback by design: if Keychain is unavailable we surface
+//! the error rather than spill OAuth tokens onto disk under `$HOME`. The
+//! encrypted file backend is strictly opt-in.
 //!
 //! # Why we shell out to `/usr/bin/security` instead of using the `keyring` crate
 //!
@@ -67,6 +67,8 @@
 //!   genuine error.
 //! - `delete-generic-password`: idempotent on missing items via the same
 //!   stderr check.
+//! - `dump-keychain` (without `-d`): item attributes only, no secrets; parsed
+//!   by [`list_accounts`] to enumerate our entries for `trg mcp auth list`.
 //!
 //! Cost is one process spawn per call (~3-5 ms on Apple Silicon). For the
 //! `trg mcp proxy` workload (one load at startup, one save per token refresh)
@@ -79,7 +81,7 @@ use async_trait::async_trait;
 use rmcp::transport::auth::{AuthError, CredentialStore, StoredCredentials};
 use tokio::process::Command;
 
-pub const KEYRING_SERVICE: &str = "trg MCP Credentials";
+use super::{err, KEYRING_SERVICE};
 
 const SECURITY_BIN: &str = "/usr/bin/security";
 
@@ -95,12 +97,9 @@ impl KeychainCredentialStore {
     }
 }
 
-fn err(msg: impl Into<String>) -> AuthError {
-    AuthError::InternalError(msg.into())
-}
-
 fn unsupported_platform() -> AuthError {
-    AuthError::InternalError("OAuth credential storage is supported only on macOS in this milestone".to_string())
+    err("the macOS Keychain credential store is only available on macOS; \
+         set `credential_store = \"secret-service\"` or `\"encrypted-file\"` in config")
 }
 
 #[async_trait]
@@ -196,6 +195,91 @@ impl CredentialStore for KeychainCredentialStore {
     }
 }
 
+/// Accounts (server names) holding an item under [`KEYRING_SERVICE`].
+pub async fn list_accounts() -> Result<Vec<String>, AuthError> {
+    if !cfg!(target_os = "macos") {
+        return Err(unsupported_platform());
+    }
+    let out = Command::new(SECURITY_BIN)
+        .arg("dump-keychain")
+        .output()
+        .await
+        .map_err(|e| err(format!("security dump-keychain: {e}")))?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(err(format!("security dump-keychain failed: {}", stderr.trim())));
+    }
+    Ok(accounts_from_dump(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// Pull `acct` out of every `dump-keychain` item whose `svce` is ours.
+///
+/// Items start with a `keychain: "..."` line; attributes look like
+/// `    "acct"<blob>="linear"`. Non-ASCII values are printed as hex and skipped.
+fn accounts_from_dump(dump: &str) -> Vec<String> {
+    fn quoted_attr<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+        let rest = line.trim().strip_prefix(&format!("\"{name}\"<blob>=\""))?;
+        rest.strip_suffix('"')
+    }
+
+    let mut accounts = Vec::new();
+    let mut account: Option<&str> = None;
+    let mut ours = false;
+    let mut flush = |account: &mut Option<&str>, ours: &mut bool| {
+        if let (Some(a), true) = (account.take(), *ours) {
+            if !accounts.iter().any(|seen| seen == a) {
+                accounts.push(a.to_string());
+            }
+        }
+        *ours = false;
+    };
+    for line in dump.lines() {
+        if line.starts_with("keychain:") {
+            flush(&mut account, &mut ours);
+        } else if let Some(a) = quoted_attr(line, "acct") {
+            account = Some(a);
+        } else if let Some(service) = quoted_attr(line, "svce") {
+            ours = service == KEYRING_SERVICE;
+        }
+    }
+    flush(&mut account, &mut ours);
+    accounts.sort();
+    accounts
+}
+
+#[cfg(test)]
+mod dump_tests {
+    use super::*;
+
+    #[test]
+    fn accounts_from_dump_keeps_only_our_service() {
+        let dump = r#"keychain: "/Users/me/Library/Keychains/login.keychain-db"
+version: 512
+class: "genp"
+attributes:
+    0x00000007 <blob>="trg MCP Credentials"
+    "acct"<blob>="linear"
+    "svce"<blob>="trg MCP Credentials"
+keychain: "/Users/me/Library/Keychains/login.keychain-db"
+class: "genp"
+attributes:
+    "acct"<blob>="me@example.com"
+    "svce"<blob>="Some Other App"
+keychain: "/Users/me/Library/Keychains/login.keychain-db"
+class: "genp"
+attributes:
+    "acct"<blob>="github"
+    "svce"<blob>="trg MCP Credentials"
+keychain: "/Users/me/Library/Keychains/login.keychain-db"
+class: "genp"
+attributes:
+    "acct"<blob>=0x6CC3A9  "l\303\251"
+    "svce"<blob>="trg MCP Credentials"
+"#;
+        assert_eq!(accounts_from_dump(dump), ["github", "linear"]);
+    }
+}
+
 #[cfg(all(test, target_os = "macos"))]
 mod tests {
     use super::*;
@@ -0,0 +1,198 @@
+//! Persistent credential storage backing rmcp's `CredentialStore`.
+//!
+//! The backend is selected by `credential_store` (globally under `[mcp]` or
+//! per server, see [`crate::config::CredentialBackend`]):
+//!
+//! - [`keychain`]: macOS login Keychain via `/usr/bin/security` (macOS default).
+//! - [`secret_service`]: freedesktop.org Secret Service over D-Bus via
+//!   `secret-tool` (GNOME Keyring, KWallet, KeePassXC; default elsewhere).
+//! - [`encrypted_file`]: one passphrase-encrypted file per server, for hosts
+//!   without a keyring daemon (containers, SSH-only workstations).
+//!
+//! Every backend keys entries by [`credential_key`] — the server name from
+//! `[mcp.servers.<name>]`, plus `#<account>` for a non-default account — and
+//! stores the JSON-serialised `StoredCredentials` payload verbatim.
+
+pub mod encrypted_file;
+pub mod keychain;
+pub mod secret_service;
+
+use async_trait::async_trait;
+use rmcp::transport::auth::{AuthError, CredentialStore, StoredCredentials};
+
+use crate::config::{CredentialBackend, CredentialStoreSettings};
+
+pub use encrypted_file::EncryptedFileCredentialStore;
+pub use keychain::KeychainCredentialStore;
+pub use secret_service::SecretServiceCredentialStore;
+
+/// Keychain service / Secret Service `service` attribute shared by the keyring backends.
+pub const KEYRING_SERVICE: &str = "trg MCP Credentials";
+
+const ACCOUNT_SEPARATOR: char = '#';
+
+/// Store key for `server`'s credentials under `account`. The default account keeps the bare
+/// server name, so entries written before accounts existed stay readable. `%` and `#` in the
+/// server name are percent-escaped, so the separator only ever precedes the account.
+pub fn credential_key(server: &str, account: Option<&str>) -> String {
+    let server = server.replace('%', "%25").replace(ACCOUNT_SEPARATOR, "%23");
+    match account {
+        Some(account) => format!("{server}{ACCOUNT_SEPARATOR}{account}"),
+        None => server,
+    }
+}
+
+/// Inverse of [`credential_key`]. Account labels never contain the separator, so the last one splits.
+pub fn split_credential_key(key: &str) -> (String, Option<&str>) {
+    let (server, account) = match key.rsplit_once(ACCOUNT_SEPARATOR) {
+        Some((server, account)) if !server.is_empty() && !account.is_empty() => (server, Some(account)),
+        _ => (key, None),
+    };
+    (unescape_server(server), account)
+}
+
+fn unescape_server(server: &str) -> String {
+    let mut out = String::with_capacity(server.len());
+    let mut rest = server;
+    while let Some(at) = rest.find('%') {
+        out.push_str(&rest[..at]);
+        let escaped = &rest[at..];
+        if let Some(after) = escaped.strip_prefix("%25") {
+            out.push('%');
+            rest = after;
+        } else if let Some(after) = escaped.strip_prefix("%23") {
+            out.push(ACCOUNT_SEPARATOR);
+            rest = after;
+        } else {
+            out.push('%');
+            rest = &escaped[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Store keys (see [`credential_key`]) with an entry in the store `settings` selects.
+pub async fn list_accounts(settings: &CredentialStoreSettings) -> Result<Vec<String>, AuthError> {
+    match settings.backend {
+        CredentialBackend::Keychain => keychain::list_accounts().await,
+        CredentialBackend::SecretService => secret_service::list_accounts().await,
+        CredentialBackend::EncryptedFile => encrypted_file::list_accounts(&settings.file).await,
+    }
+}
+
+fn err(msg: impl Into<String>) -> AuthError {
+    AuthError::InternalError(msg.into())
+}
+
+enum BackendStore {
+    Keychain(KeychainCredentialStore),
+    SecretService(SecretServiceCredentialStore),
+    EncryptedFile(EncryptedFileCredentialStore),
+}
+
+/// The credential store selected for one server by its [`CredentialStoreSettings`].
+pub struct ConfiguredCredentialStore {
+    account: String,
+    inner: BackendStore,
+}
+
+impl ConfiguredCredentialStore {
+    pub fn new(settings: &CredentialStoreSettings, account: impl Into<String>) -> Self {
+        let account = account.into();
+        let inner = match settings.backend {
+            CredentialBackend::Keychain => BackendStore::Keychain(KeychainCredentialStore::new(&account)),
+            CredentialBackend::SecretService => {
+                BackendStore::SecretService(SecretServiceCredentialStore::new(&account))
+            }
+            CredentialBackend::EncryptedFile => {
+                BackendStore::EncryptedFile(EncryptedFileCredentialStore::new(&account, &settings.file))
+            }
+        };
+        Self { account, inner }
+    }
+
+    /// The store for `server`'s `account` login (`None` = the default login).
+    pub fn for_server(settings: &CredentialStoreSettings, server: &str, account: Option<&str>) -> Self {
+        Self::new(settings, credential_key(server, account))
+    }
+
+    pub fn backend(&self) -> CredentialBackend {
+        match self.inner {
+            BackendStore::Keychain(_) => CredentialBackend::Keychain,
+            BackendStore::SecretService(_) => CredentialBackend::SecretService,
+            BackendStore::EncryptedFile(_) => CredentialBackend::EncryptedFile,
+        }
+    }
+
+    /// Human-readable location of this server's entry, for CLI messages.
+    pub fn location(&self) -> String {
+        let account = &self.account;
+        match &self.inner {
+            BackendStore::Keychain(_) => {
+                format!("the macOS Keychain (service `{KEYRING_SERVICE}`, account `{account}`)")
+            }
+            BackendStore::SecretService(_) => {
+                format!("the Secret Service keyring (service `{KEYRING_SERVICE}`, account `{account}`)")
+            }
+            BackendStore::EncryptedFile(store) => {
+                format!("the encrypted credential file `{}`", store.path().display())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialStore for ConfiguredCredentialStore {
+    async fn load(&self) -> Result<Option<StoredCredentials>, AuthError> {
+        match &self.inner {
+            BackendStore::Keychain(s) => s.load().await,
+            BackendStore::SecretService(s) => s.load().await,
+            BackendStore::EncryptedFile(s) => s.load().await,
+        }
+    }
+
+    async fn save(&self, credentials: StoredCredentials) -> Result<(), AuthError> {
+        match &self.inner {
+            BackendStore::Keychain(s) => s.save(credentials).await,
+            BackendStore::SecretService(s) => s.save(credentials).await,
+            BackendStore::EncryptedFile(s) => s.save(credentials).await,
+        }
+    }
+
+    async fn clear(&self) -> Result<(), AuthError> {
+        match &self.inner {
+            BackendStore::Keychain(s) => s.clear().await,
+            BackendStore::SecretService(s) => s.clear().await,
+            BackendStore::EncryptedFile(s) => s.clear().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credential_key_roundtrips() {
+        assert_eq!(credential_key("linear", None), "linear");
+        assert_eq!(credential_key("linear", Some("work")), "linear#work");
+        assert_eq!(
+            split_credential_key("linear#work"),
+            ("linear".to_string(), Some("work"))
+        );
+        assert_eq!(split_credential_key("linear"), ("linear".to_string(), None));
+        assert_eq!(split_credential_key("trailing#"), ("trailing#".to_string(), None));
+    }
+
+    #[test]
+    fn server_names_with_separator_roundtrip() {
+        for server in ["team#eu", "a#b#c", "100%", "%23", "#"] {
+            for account in [None, Some("work")] {
+                let key = credential_key(server, account);
+                assert_eq!(split_credential_key(&key), (server.to_string(), account), "{key}");
+            }
+        }
+        assert_eq!(credential_key("team#eu", None), "team%23eu");
+    }
+}
@@ -0,0 +1,220 @@
+//! freedesktop.org Secret Service backend (`credential_store = "secret-service"`,
+//! the default off macOS).
+//!
+//! Same reasoning as the Keychain backend: we shell out to libsecret's
+//! `secret-tool` instead of linking a D-Bus client (`secret-service` / `zbus`
+//! pull in an async runtime bridge, crypto for the session transfer, and a
+//! large dependency tree). `secret-tool` talks to whichever provider owns
+//! `org.freedesktop.secrets` on the session bus — GNOME Keyring, KWallet
+//! (5.97+), KeePassXC — so one code path covers every desktop we run.
+//!
+//! - `store`: reads the secret from **stdin**, so unlike the Keychain backend
+//!   the payload never appears on argv.
+//! - `lookup`: prints the secret to stdout. A missing item exits non-zero with
+//!   an empty stderr; anything on stderr (no session bus, locked collection
+//!   the user refused to unlock) is a genuine error.
+//! - `clear`: idempotent on missing items via the same stderr check.
+//! - `search --all`: enumerates our items for `trg mcp auth list`. It prints each
+//!   item's secret too, so its output is streamed line by line: only
+//!   `attribute.account` lines are kept, and every other line (the `secret =`
+//!   line included) is zeroed and discarded as soon as it is read.
+//!
+//! Items carry the attributes `service = "trg MCP Credentials"` and
+//! `account = <server name>`, matching the Keychain layout.
+
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use rmcp::transport::auth::{AuthError, CredentialStore, StoredCredentials};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+use super::{err, KEYRING_SERVICE};
+
+const SECRET_TOOL_BIN: &str = "secret-tool";
+
+pub struct SecretServiceCredentialStore {
+    server_name: String,
+}
+
+impl SecretServiceCredentialStore {
+    pub fn new(server_name: impl Into<String>) -> Self {
+        Self {
+            server_name: server_name.into(),
+        }
+    }
+}
+
+fn spawn_err(action: &str, e: std::io::Error) -> AuthError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        return err(
+            "`secret-tool` not found on PATH; install libsecret-tools (Debian/Ubuntu) or libsecret \
+             (Fedora/Arch), or set `credential_store = \"encrypted-file\"` in config",
+        );
+    }
+    err(format!("secret-tool {action}: {e}"))
+}
+
+#[async_trait]
+impl CredentialStore for SecretServiceCredentialStore {
+    async fn load(&self) -> Result<Option<StoredCredentials>, AuthError> {
+        let out = Command::new(SECRET_TOOL_BIN)
+            .args(["lookup", "service", KEYRING_SERVICE, "account", &self.server_name])
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| spawn_err("lookup", e))?;
+
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            if stderr.trim().is_empty() {
+                return Ok(None);
+            }
+            return Err(err(format!("secret-tool lookup failed: {}", stderr.trim())));
+        }
+
+        let secret = String::from_utf8_lossy(&out.stdout);
+        let secret = secret.trim_end_matches('\n');
+        if secret.is_empty() {
+            return Ok(None);
+        }
+        let credentials: StoredCredentials =
+            serde_json::from_str(secret).map_err(|e| err(format!("decode Secret Service payload: {e}")))?;
+        Ok(Some(credentials))
+    }
+
+    async fn save(&self, credentials: StoredCredentials) -> Result<(), AuthError> {
+        let json = serde_json::to_string(&credentials).map_err(|e| err(e.to_string()))?;
+        let label = format!("{KEYRING_SERVICE}: {}", self.server_name);
+
+        let mut child = Command::new(SECRET_TOOL_BIN)
+            .args([
+                "store",
+                "--label",
+                &label,
+                "service",
+                KEYRING_SERVICE,
+                "account",
+                &self.server_name,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_err("store", e))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(json.as_bytes())
+                .await
+                .map_err(|e| err(format!("secret-tool store: write payload: {e}")))?;
+        }
+
+        let out = child
+            .wait_with_output()
+            .await
+            .map_err(|e| err(format!("secret-tool store: {e}")))?;
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            return Err(err(format!("secret-tool store failed: {}", stderr.trim())));
+        }
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), AuthError> {
+        let out = Command::new(SECRET_TOOL_BIN)
+            .args(["clear", "service", KEYRING_SERVICE, "account", &self.server_name])
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| spawn_err("clear", e))?;
+
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            if stderr.trim().is_empty() {
+                return Ok(());
+            }
+            return Err(err(format!("secret-tool clear failed: {}", stderr.trim())));
+        }
+        Ok(())
+    }
+}
+
+/// Accounts (server names) holding an item with `service = KEYRING_SERVICE`.
+pub async fn list_accounts() -> Result<Vec<String>, AuthError> {
+    let mut child = Command::new(SECRET_TOOL_BIN)
+        .args(["search", "--all", "service", KEYRING_SERVICE])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| spawn_err("search", e))?;
+
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut accounts = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = stdout.read_until(b'\n', &mut line).await;
+        let account = account_attribute(&line);
+        line.fill(0);
+        match read {
+            Ok(0) => break,
+            Ok(_) => accounts.extend(account),
+            Err(e) => return Err(err(format!("secret-tool search: read output: {e}"))),
+        }
+    }
+    drop(stdout);
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr).await;
+    }
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| err(format!("secret-tool search: {e}")))?;
+    if !status.success() {
+        if stderr.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        return Err(err(format!("secret-tool search failed: {}", stderr.trim())));
+    }
+    accounts.sort();
+    accounts.dedup();
+    Ok(accounts)
+}
+
+/// The server name on an `attribute.account = <name>` line of `secret-tool search` output.
+fn account_attribute(line: &[u8]) -> Option<String> {
+    let line = std::str::from_utf8(line).ok()?;
+    line.trim().strip_prefix("attribute.account = ").map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_only_account_attribute_lines() {
+        let output = "[/org/freedesktop/secrets/collection/login/7]
+label = trg MCP Credentials: linear
+secret = {\"client_id\":\"x\"}
+created = 2026-01-01 10:00:00
+modified = 2026-01-01 10:00:00
+schema = org.freedesktop.Secret.Generic
+attribute.service = trg MCP Credentials
+attribute.account = linear
+[/org/freedesktop/secrets/collection/login/9]
+label = trg MCP Credentials: github
+secret = {}
+attribute.account = github
+attribute.service = trg MCP Credentials
+";
+        let accounts: Vec<String> = output
+            .split_inclusive('\n')
+            .filter_map(|line| account_attribute(line.as_bytes()))
+            .collect();
+        assert_eq!(accounts, ["linear", "github"]);
+    }
+}
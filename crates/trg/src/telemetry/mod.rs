@@ -0,0 +1,555 @@
+//! File-based `tracing` subscriber shared by every `trg` subcommand.
+//!
+//! `trg mcp proxy` runs as a headless child of an MCP host (Cursor, Claude
+//! Code) which swallows stdout and stderr. Without a file sink, rmcp's
+//! internal `tracing::debug!`/`warn!` events — OAuth refresh attempts,
+//! AS metadata fetches, 401 retries — are invisible when something breaks.
+//! Other subcommands run interactively, but a single shared subscriber keeps
+//! the wiring obvious and makes future cross-command diagnostics trivial.
+//!
+//! Default filter is `info,trg=debug,rmcp=debug` so refresh failures show up
+//! without extra configuration; `RUST_LOG` overrides if you want more.
+//!
+//! Every line carries the pid of the process that wrote it, so concurrent
+//! proxies sharing `trg.log` can be told apart (`trg logs --server`). The
+//! file rotates by size or by day and keeps a bounded number of old files;
+//! see [`LogSettings`].
+//!
+//! Built with the `otel` feature, spans can also be exported over OTLP; see `otel.rs`.
+
+use std::{
+    fmt as stdfmt,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{
+        self,
+        format::{self as fmt_format, FormatEvent, FormatFields, Writer},
+        FmtContext,
+    },
+    prelude::*,
+    registry::LookupSpan,
+    EnvFilter,
+};
+
+use crate::fs::{lock_rotation, rotate_numbered, rotated_path};
+
+#[cfg(feature = "otel")]
+mod otel;
+
+#[cfg(not(feature = "otel"))]
+mod otel {
+    pub(super) fn layer() -> Option<tracing_subscriber::layer::Identity> {
+        None
+    }
+
+    pub(super) fn shutdown() {}
+}
+
+/// Initialise the global `tracing` subscriber, writing to
+/// `$XDG_CACHE_HOME/trg/trg.log` (or `~/.cache/trg/trg.log`).
+///
+/// Idempotent: safe to call from multiple entry points; only the first call
+/// succeeds (subsequent calls become no-ops via `try_init`). Silently does
+/// nothing if neither `$XDG_CACHE_HOME` nor `$HOME` is set, or if the file
+/// can't be opened — diagnostic logging must never crash the CLI.
+pub fn init() {
+    let Some(path) = log_path() else { return };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let settings = LogSettings::from_env();
+    let Ok(file) = RotatingFile::open(path, settings) else {
+        return;
+    };
+    let writer = Mutex::new(file);
+    let filter = EnvFilter::try_from_env("RUST_LOG").unwrap_or_else(|_| EnvFilter::new("info,trg=debug,rmcp=debug"));
+    let layer = fmt::layer().with_writer(writer).with_ansi(false);
+    let _ = match settings.format {
+        LogFormat::Text => tracing_subscriber::registry()
+            .with(filter)
+            .with(layer.event_format(PidText::default()))
+            .with(otel::layer())
+            .try_init(),
+        LogFormat::Json => tracing_subscriber::registry()
+            .with(filter)
+            .with(layer.event_format(PidJson))
+            .with(otel::layer())
+            .try_init(),
+    };
+}
+
+/// Flush anything still buffered for export. Call before `std::process::exit`.
+pub fn shutdown() {
+    otel::shutdown();
+}
+
+/// `$XDG_CACHE_HOME/trg/trg.log`; rotated copies are `trg.log.1` (newest) and up.
+pub fn log_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+    Some(base.join("trg").join("trg.log"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `<rfc3339> [<pid>]  LEVEL target: message key=value…`
+    Text,
+    /// One JSON object per line: `timestamp`, `level`, `pid`, `target`, `fields`, `spans`.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Rotate before a write would take the file past this many bytes.
+    Size(u64),
+    /// Rotate on the first write of a new UTC day.
+    Daily,
+}
+
+/// Log settings from the environment. Malformed values fall back to the defaults rather
+/// than failing, for the same reason a missing log file is tolerated.
+///
+/// | Variable | Values | Default |
+/// | --- | --- | --- |
+/// | `TRG_LOG_FORMAT` | `text`, `json` | `text` |
+/// | `TRG_LOG_ROTATE` | `size`, `daily` | `size` |
+/// | `TRG_LOG_MAX_SIZE` | bytes, or with a `K`/`M`/`G` suffix | `10M` |
+/// | `TRG_LOG_MAX_FILES` | rotated files kept, at least 1 | `5` |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogSettings {
+    pub format: LogFormat,
+    pub rotation: Rotation,
+    pub max_files: u32,
+}
+
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_FILES: u32 = 5;
+
+impl LogSettings {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(var: impl Fn(&str) -> Option<String>) -> Self {
+        let format = match var("TRG_LOG_FORMAT").as_deref().map(str::trim) {
+            Some(f) if f.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        };
+        let rotation = match var("TRG_LOG_ROTATE").as_deref().map(str::trim) {
+            Some(r) if r.eq_ignore_ascii_case("daily") => Rotation::Daily,
+            _ => Rotation::Size(
+                var("TRG_LOG_MAX_SIZE")
+                    .as_deref()
+                    .and_then(parse_size)
+                    .unwrap_or(DEFAULT_MAX_SIZE),
+            ),
+        };
+        let max_files = var("TRG_LOG_MAX_FILES")
+            .and_then(|n| n.trim().parse().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(DEFAULT_MAX_FILES);
+        Self {
+            format,
+            rotation,
+            max_files,
+        }
+    }
+}
+
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (digits, unit) = match value.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&value[..i], c.to_ascii_uppercase()),
+        _ => (value, 'B'),
+    };
+    let shift = match unit {
+        'B' => 0,
+        'K' => 10,
+        'M' => 20,
+        'G' => 30,
+        _ => return None,
+    };
+    let size = digits.trim().parse::<u64>().ok()?.checked_mul(1 << shift)?;
+    (size > 0).then_some(size)
+}
+
+/// Append-only log file that rotates itself and follows rotations done by other processes.
+///
+/// Before every write it checks the path: if another `trg` renamed the file away, this one
+/// reopens the new file instead of writing into `trg.log.1`.
+pub struct RotatingFile {
+    path: PathBuf,
+    settings: LogSettings,
+    file: File,
+    inode: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, settings: LogSettings) -> io::Result<Self> {
+        let (file, inode) = open_append(&path)?;
+        Ok(Self {
+            path,
+            settings,
+            file,
+            inode,
+        })
+    }
+
+    fn prepare(&mut self, incoming: usize, now: SystemTime) -> io::Result<()> {
+        let Some(meta) = self.current_metadata()? else {
+            return self.reopen();
+        };
+        if !self.due(&meta, incoming, now) {
+            return Ok(());
+        }
+        // Proxies sharing the file may all find it due at once. Decide again under the lock,
+        // so only the first renames it and the others just reopen the fresh file.
+        let _lock = lock_rotation(&self.path)?;
+        if let Some(meta) = self.current_metadata()? {
+            if self.due(&meta, incoming, now) {
+                rotate_numbered(&self.path, self.settings.max_files)?;
+            }
+        }
+        self.reopen()
+    }
+
+    /// Metadata of the file at `path`, or `None` when it is missing or is no longer the
+    /// file we hold open (another process rotated it).
+    fn current_metadata(&self) -> io::Result<Option<std::fs::Metadata>> {
+        match std::fs::metadata(&self.path) {
+            Ok(meta) if meta.ino() == self.inode => Ok(Some(meta)),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn due(&self, meta: &std::fs::Metadata, incoming: usize, now: SystemTime) -> bool {
+        if meta.len() == 0 {
+            return false;
+        }
+        match self.settings.rotation {
+            Rotation::Size(max) => meta.len() + incoming as u64 > max,
+            Rotation::Daily => meta.modified().is_ok_and(|modified| utc_day(modified) != utc_day(now)),
+        }
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        (self.file, self.inode) = open_append(&self.path)?;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A failed rotation must not lose the line; keep writing to the current file.
+        let _ = self.prepare(buf.len(), SystemTime::now());
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let inode = file.metadata()?.ino();
+    Ok((file, inode))
+}
+
+fn utc_day(time: SystemTime) -> chrono::NaiveDate {
+    DateTime::<Utc>::from(time).date_naive()
+}
+
+/// Current and rotated log files, oldest first.
+pub fn log_files(path: &Path) -> Vec<PathBuf> {
+    let mut rotated: Vec<(u32, PathBuf)> = (1..)
+        .map(|n| (n, rotated_path(path, n)))
+        .take_while(|(_, p)| p.exists())
+        .collect();
+    rotated.sort_by_key(|(n, _)| std::cmp::Reverse(*n));
+    rotated
+        .into_iter()
+        .map(|(_, p)| p)
+        .chain([path.to_path_buf()])
+        .collect()
+}
+
+fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// tracing-subscriber's `Full` format behind our own timestamp and the pid.
+struct PidText {
+    inner: fmt_format::Format<fmt_format::Full, ()>,
+}
+
+impl Default for PidText {
+    fn default() -> Self {
+        Self {
+            inner: fmt_format::format().without_time().with_ansi(false).with_target(true),
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for PidText
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> stdfmt::Result {
+        write!(writer, "{} [{}] ", now_rfc3339(), std::process::id())?;
+        self.inner.format_event(ctx, writer, event)
+    }
+}
+
+/// One JSON object per event. Hand-rolled because tracing-subscriber's `json` feature is not
+/// enabled in this build.
+struct PidJson;
+
+impl<S, N> FormatEvent<S, N> for PidJson
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> stdfmt::Result {
+        let meta = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let spans: Vec<&str> = ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| span.name())
+            .collect();
+        let line = json!({
+            "timestamp": now_rfc3339(),
+            "level": meta.level().as_str(),
+            "pid": std::process::id(),
+            "target": meta.target(),
+            "fields": fields.0,
+            "spans": spans,
+        });
+        writeln!(writer, "{line}")
+    }
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn stdfmt::Debug) {
+        self.0.insert(field.name().into(), Value::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), Value::String(value.into()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.0.insert(field.name().into(), Value::String(value.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use tempfile::tempdir;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+    use crate::fs::lock_path;
+
+    fn settings(rotation: Rotation) -> LogSettings {
+        LogSettings {
+            format: LogFormat::Text,
+            rotation,
+            max_files: 2,
+        }
+    }
+
+    #[test]
+    fn settings_come_from_the_environment() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(
+            LogSettings::from_lookup(env(&[])),
+            LogSettings {
+                format: LogFormat::Text,
+                rotation: Rotation::Size(DEFAULT_MAX_SIZE),
+                max_files: DEFAULT_MAX_FILES,
+            }
+        );
+        assert_eq!(
+            LogSettings::from_lookup(env(&[
+                ("TRG_LOG_FORMAT", "JSON"),
+                ("TRG_LOG_MAX_SIZE", "512k"),
+                ("TRG_LOG_MAX_FILES", "3"),
+            ])),
+            LogSettings {
+                format: LogFormat::Json,
+                rotation: Rotation::Size(512 * 1024),
+                max_files: 3,
+            }
+        );
+        let daily = LogSettings::from_lookup(env(&[("TRG_LOG_ROTATE", "daily"), ("TRG_LOG_MAX_FILES", "0")]));
+        assert_eq!(daily.rotation, Rotation::Daily);
+        assert_eq!(daily.max_files, DEFAULT_MAX_FILES);
+        assert_eq!(parse_size("2G"), Some(2 << 30));
+        assert_eq!(parse_size("0"), None);
+        assert_eq!(parse_size("10X"), None);
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trg.log");
+        let mut file = RotatingFile::open(path.clone(), settings(Rotation::Size(6))).unwrap();
+        for line in ["one\n", "two\n", "three\n", "four\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "four\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "three\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 2)).unwrap(), "two\n");
+        assert!(!rotated_path(&path, 3).exists());
+        assert_eq!(
+            log_files(&path),
+            [rotated_path(&path, 2), rotated_path(&path, 1), path.clone()]
+        );
+    }
+
+    #[test]
+    fn daily_rotation_and_foreign_rotations() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trg.log");
+        let mut file = RotatingFile::open(path.clone(), settings(Rotation::Daily)).unwrap();
+        file.write_all(b"today\n").unwrap();
+
+        file.prepare(1, SystemTime::now()).unwrap();
+        assert!(!rotated_path(&path, 1).exists());
+        file.prepare(1, SystemTime::now() + Duration::from_secs(86_400))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "today\n");
+
+        // Another process rotates the file away: the next write lands in the new file.
+        file.write_all(b"tomorrow\n").unwrap();
+        rotate_numbered(&path, 2).unwrap();
+        file.write_all(b"later\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "later\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "tomorrow\n");
+        assert_eq!(utc_day(UNIX_EPOCH).to_string(), "1970-01-01");
+    }
+
+    #[test]
+    fn concurrent_writers_rotate_once() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trg.log");
+        let mut first = RotatingFile::open(path.clone(), settings(Rotation::Size(6))).unwrap();
+        first.write_all(b"old\n").unwrap();
+        let mut second = RotatingFile::open(path.clone(), settings(Rotation::Size(6))).unwrap();
+
+        // `first` is mid-rotation: `second` finds the file due and waits for the lock.
+        let lock = File::create(lock_path(&path)).unwrap();
+        lock.lock().unwrap();
+        let writer = std::thread::spawn(move || second.write_all(b"second\n").unwrap());
+        std::thread::sleep(Duration::from_millis(100));
+        rotate_numbered(&path, 2).unwrap();
+        std::fs::write(&path, "first\n").unwrap();
+        drop(lock);
+        writer.join().unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "old\n");
+        assert!(!rotated_path(&path, 2).exists());
+    }
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Capture;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn lines_carry_the_pid_in_both_formats() {
+        let text = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(
+            fmt::layer()
+                .with_writer(text.clone())
+                .with_ansi(false)
+                .event_format(PidText::default()),
+        );
+        tracing::subscriber::with_default(subscriber, || tracing::info!(server = "linear", "startup"));
+        let line = String::from_utf8(text.0.lock().unwrap().clone()).unwrap();
+        assert!(line.contains(&format!(" [{}]  INFO ", std::process::id())), "{line}");
+        assert!(line.trim_end().ends_with(r#"startup server="linear""#), "{line}");
+
+        let json = Capture::default();
+        let subscriber =
+            tracing_subscriber::registry().with(fmt::layer().with_writer(json.clone()).event_format(PidJson));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("bridge");
+            let _guard = span.enter();
+            tracing::warn!(server = "linear", attempts = 3, "retrying");
+        });
+        let line: Value = serde_json::from_slice(&json.0.lock().unwrap()).unwrap();
+        assert_eq!(line["pid"], std::process::id());
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(
+            line["fields"],
+            json!({"message": "retrying", "server": "linear", "attempts": 3})
+        );
+        assert_eq!(line["spans"], json!(["bridge"]));
+    }
+}
@@ -0,0 +1,82 @@
+//! OTLP export of `trg`'s spans (the `otel` cargo feature).
+//!
+//! Off unless `OTEL_EXPORTER_OTLP_ENDPOINT` or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is set,
+//! so a build with the feature never dials a collector nobody asked for. The rest of the
+//! standard `OTEL_*` variables apply as the OpenTelemetry SDK defines them:
+//! `OTEL_EXPORTER_OTLP_PROTOCOL` (`grpc`, the default, or `http/protobuf`), `OTEL_SERVICE_NAME`
+//! (default `trg`), `OTEL_RESOURCE_ATTRIBUTES` and `OTEL_EXPORTER_OTLP_HEADERS`.
+//!
+//! Only spans and events from `trg` itself at `info` or above are exported; rmcp's internal
+//! debug spans stay in `trg.log`.
+
+use std::sync::OnceLock;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::{filter::Targets, registry::LookupSpan, Layer};
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// The export layer, or `None` when no endpoint is configured or the exporter can't be built.
+pub(super) fn layer<S>() -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let configured = ["OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", "OTEL_EXPORTER_OTLP_ENDPOINT"]
+        .into_iter()
+        .any(|name| std::env::var_os(name).is_some_and(|v| !v.is_empty()));
+    if !configured {
+        return None;
+    }
+    let provider = match provider() {
+        Ok(provider) => provider,
+        Err(e) => {
+            // The subscriber isn't installed yet, so stderr is the only place this can go.
+            eprintln!("trg: OpenTelemetry export disabled: {e}");
+            return None;
+        }
+    };
+    let tracer = provider.tracer("trg");
+    let _ = PROVIDER.set(provider);
+    Some(
+        tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(Targets::new().with_target("trg", Level::INFO)),
+    )
+}
+
+/// Flush buffered spans; `main` calls this before `process::exit`, which skips destructors.
+pub(super) fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        let _ = provider.shutdown();
+    }
+}
+
+fn provider() -> Result<SdkTracerProvider, String> {
+    let protocol = std::env::var("OTEL_EXPORTER_OTLP_TRACES_PROTOCOL")
+        .or_else(|_| std::env::var("OTEL_EXPORTER_OTLP_PROTOCOL"))
+        .unwrap_or_default();
+    let exporter = match protocol.trim() {
+        "" | "grpc" => SpanExporter::builder().with_tonic().build(),
+        "http/protobuf" => SpanExporter::builder()
+            .with_http()
+            .with_protocol(Protocol::HttpBinary)
+            .build(),
+        other => return Err(format!("unsupported OTEL_EXPORTER_OTLP_PROTOCOL `{other}`")),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let mut resource = Resource::builder().with_attribute(opentelemetry::KeyValue::new(
+        "service.version",
+        env!("CARGO_PKG_VERSION"),
+    ));
+    if std::env::var_os("OTEL_SERVICE_NAME").is_none() {
+        resource = resource.with_service_name("trg");
+    }
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build())
+}
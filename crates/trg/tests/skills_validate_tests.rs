@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::Command;
+
+fn write_skill(dir: &Path, description: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        dir.join("SKILL.md"),
+        format!("---\nname: charts\ndescription: {description}\n---\n\n# Charts\n"),
+    )
+    .unwrap();
+}
+
+fn validate(dir: &Path) -> std::process::Output {
+    Command::cargo_bin("trg")
+        .unwrap()
+        .args(["ai", "skills", "validate"])
+        .arg(dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn description_budget_fails_validate_by_default() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skill = tmp.path().join("charts");
+    // About 300 tokens in under 1024 characters, so only the token budget trips.
+    let description = "Plot a bar, pie or line of a CSV, TSV or XLS. ".repeat(20);
+    write_skill(&skill, description.trim());
+
+    let output = validate(&skill);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("description-budget"), "{stderr}");
+    assert!(stderr.contains("256-token budget"), "{stderr}");
+
+    fs::write(tmp.path().join(".trg-lint.toml"), "[budgets]\ndescription = 0\n").unwrap();
+    let output = validate(&skill);
+    assert!(
+        output.status.success(),
+        "description budget turned off: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}